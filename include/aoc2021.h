@@ -0,0 +1,28 @@
+/*
+** include/aoc2021.h
+**
+** C interface to the solutions, built with: cargo build --release --features ffi
+** and linked against target/release/libaoc2021.a
+*/
+
+#ifndef AOC2021_H
+#define AOC2021_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define AOC2021_OK            0
+#define AOC2021_ERROR         1
+#define AOC2021_INVALID_INPUT 2
+
+/*
+** solves a single part of a day's puzzle for the given input
+** on return, out holds either the answer or an error message, which must be
+** released with aoc2021_free
+*/
+int aoc2021_solve(size_t day, size_t part, const uint8_t *input, size_t len, char **out);
+
+/* releases a string returned by aoc2021_solve */
+void aoc2021_free(char *s);
+
+#endif /* AOC2021_H */
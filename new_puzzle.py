@@ -11,16 +11,33 @@ mod_template = """\
 
 <M>
 
-use crate::types::Puzzle;
+use crate::inputs;
+use crate::types::{Puzzle, Result};
 
-const INPUTS: [&str; <N>] = [
+pub const N_DAYS: usize = <N>;
+
+// note: inputs are only bundled with the bundled-inputs feature, see inputs::load
+#[cfg(feature = "bundled-inputs")]
+pub const INPUTS: [&str; N_DAYS] = [
 <I>
 ];
 
-pub fn all() -> Vec<Box<dyn Puzzle>> {
-    vec![
+pub fn new(day: usize, input: &'static str) -> Option<Box<dyn Puzzle>> {
+    let _span = tracing::info_span!("parse", day).entered();
+    let puzzle: Box<dyn Puzzle> = match day {
 <P>
-    ]
+        _ => return None,
+    };
+    Some(puzzle)
+}
+
+pub fn all() -> Result<Vec<Box<dyn Puzzle>>> {
+    (1..=N_DAYS)
+        .map(|day| {
+            let input = inputs::load(day)?;
+            new(day, input).ok_or_else(|| format!("day {} is not a known puzzle", day).into())
+        })
+        .collect()
 }
 """
 
@@ -74,12 +91,12 @@ if __name__ == "__main__":
 
     # write the mod.rs file
     with open(os.path.join(puzzle_dir, "mod.rs"), "w") as mod_file:
-        mods = "\n".join(f"mod day_{i + 1};" for i in range(n))
+        mods = "\n".join(f"pub mod day_{i + 1};" for i in range(n))
         inputs = "\n".join(
             f"    include_str!(\"../../input/{i + 1}.txt\"),"
             for i in range(n))
         puzzles = "\n".join(
-            f"        Box::new(day_{i + 1}::Day{i + 1}::new(INPUTS[{i}])),"
+            f"        {i + 1} => Box::new(day_{i + 1}::Day{i + 1}::new(input)),"
             for i in range(n))
         mod_file.write(
             mod_template
@@ -0,0 +1,153 @@
+/*
+** src/bench.rs
+*/
+
+use aoc2021::inputs;
+use aoc2021::types::RunResult;
+use aoc2021::variants;
+
+use std::process;
+use std::time::{Duration, Instant};
+
+// the widest bar of a histogram, in characters
+const HISTOGRAM_WIDTH: usize = 40;
+// histogram buckets grow geometrically, each this many times wider than the last
+const BUCKET_RATIO: f64 = 1.25;
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// counts the durations into geometric buckets of microseconds, from the bucket of the fastest to
+// that of the slowest, as the lower bound of each bucket alongside its count
+// note: geometric buckets keep a slow outlier from squashing the spread of the fast runs
+fn histogram(durations: &[Duration]) -> Vec<(Duration, usize)> {
+    let bucket = |duration: &Duration| {
+        let micros = duration.as_secs_f64() * 1_000_000.0;
+        micros.max(1.0).log(BUCKET_RATIO).floor() as usize
+    };
+    let (first, last) = match (
+        durations.iter().map(bucket).min(),
+        durations.iter().map(bucket).max(),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return vec![],
+    };
+    let mut counts = vec![0; last - first + 1];
+    for duration in durations.iter() {
+        counts[bucket(duration) - first] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let lower = BUCKET_RATIO.powi((first + i) as i32);
+            (Duration::from_secs_f64(lower / 1_000_000.0), count)
+        })
+        .collect()
+}
+
+fn print_histogram(durations: &[Duration]) {
+    let buckets = histogram(durations);
+    let max_count = buckets.iter().map(|&(_, count)| count).max().unwrap_or(0);
+    for (lower, count) in buckets {
+        let width = (count * HISTOGRAM_WIDTH).div_ceil(max_count.max(1));
+        println!(
+            "  >= {:>10.3}ms {:>6} {}",
+            ms(lower),
+            count,
+            "#".repeat(width)
+        );
+    }
+}
+
+// prints the spread of the sorted durations, and optionally their histogram
+fn print_summary(label: &str, durations: &[Duration], show_histogram: bool) {
+    let runs = durations.len();
+    let mean = durations.iter().sum::<Duration>() / runs as u32;
+    println!(
+        "{}: min {:.3}ms, median {:.3}ms, mean {:.3}ms, max {:.3}ms over {} runs",
+        label,
+        ms(durations[0]),
+        ms(durations[runs / 2]),
+        ms(mean),
+        ms(durations[runs - 1]),
+        runs
+    );
+    if show_histogram {
+        print_histogram(durations);
+    }
+}
+
+// parses and solves each part of a day the given number of times, with the named
+// implementation if there is one, printing a summary of the parse and solve runtimes apart, and
+// optionally their histograms
+pub fn bench(day: usize, runs: usize, implementation: Option<&str>, show_histogram: bool) {
+    if let Some(name) = implementation {
+        if variants::variant(day, name).is_none() {
+            eprintln!("day {} has no {} implementation", day, name);
+            process::exit(1);
+        }
+    }
+    let input = inputs::load(day).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    let mut parse_durations = Vec::with_capacity(runs * 2);
+    let mut solve_durations = [vec![], vec![]];
+    for part in 1..=2 {
+        for _ in 0..runs {
+            // note: each run solves a freshly parsed puzzle, since some puzzles reuse work
+            // between calls
+            let start = Instant::now();
+            let puzzle = variants::new(day, implementation, input)
+                .unwrap_or_else(|| {
+                    eprintln!("day {} is not a known puzzle", day);
+                    process::exit(1);
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("day {}: {}", day, err);
+                    process::exit(1);
+                });
+            parse_durations.push(start.elapsed());
+            let result = RunResult::new(day, part, puzzle.as_ref());
+            if let Err(err) = result.answer {
+                eprintln!("day {} part {}: {}", day, part, err);
+                process::exit(1);
+            }
+            solve_durations[part - 1].push(result.duration);
+        }
+    }
+
+    parse_durations.sort();
+    print_summary(
+        &format!("day {:02} parse", day),
+        &parse_durations,
+        show_histogram,
+    );
+    for (part, durations) in solve_durations.iter_mut().enumerate() {
+        durations.sort();
+        let label = format!("day {:02} part {}", day, part + 1);
+        print_summary(&label, durations, show_histogram);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let durations = [100, 101, 110, 120, 200]
+            .map(Duration::from_micros)
+            .to_vec();
+        let buckets = histogram(&durations);
+        // buckets from 87us up to 212us, each 25% wider than the last
+        let counts = buckets.iter().map(|&(_, count)| count).collect::<Vec<_>>();
+        assert_eq!(counts, vec![2, 2, 0, 1]);
+        assert!(buckets.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(buckets[0].0 <= durations[0] && buckets[3].0 <= durations[4]);
+        assert!(histogram(&[]).is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+/*
+** src/cache.rs
+*/
+
+use aoc2021::types::Solution;
+use aoc2021::utils;
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const CACHE_DIR: &str = ".aoc-cache";
+
+// answers are cached on disk, keyed by day, part and input digest, such that a changed input is
+// never served a stale answer
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self {
+            dir: PathBuf::from(CACHE_DIR),
+        }
+    }
+
+    fn path(&self, day: usize, part: usize, input: &str) -> PathBuf {
+        let digest = utils::input_digest(input);
+        self.dir
+            .join(format!("day{:02}-part{}-{}.txt", day, part, &digest[..16]))
+    }
+
+    pub fn get(&self, day: usize, part: usize, input: &str) -> Option<String> {
+        fs::read_to_string(self.path(day, part, input)).ok()
+    }
+
+    pub fn insert(
+        &self,
+        day: usize,
+        part: usize,
+        input: &str,
+        solution: &Solution,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(day, part, input), solution.to_string())
+    }
+
+    // pages fetched from adventofcode.com are cached by name
+    pub fn get_page(&self, name: &str) -> Option<String> {
+        fs::read_to_string(self.dir.join(name)).ok()
+    }
+
+    pub fn insert_page(&self, name: &str, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(name), contents)
+    }
+}
@@ -0,0 +1,72 @@
+/*
+** src/cargo_aoc.rs
+*/
+
+// each day's generator and solvers in the shape which cargo-aoc expects: the generator parses the
+// input into the puzzle, and each part borrows the parsed puzzle and returns a displayable result
+// e.g. to register day 1 with aoc-runner:
+//     #[aoc_generator(day1)]
+//     fn generator(input: &str) -> Result<Day1> { aoc2021::cargo_aoc::day1::generator(input) }
+//     #[aoc(day1, part1)]
+//     fn part1(puzzle: &Day1) -> Result<Solution> { aoc2021::cargo_aoc::day1::part1(puzzle) }
+
+macro_rules! cargo_aoc_days {
+    ($($day:ident => $module:ident::$puzzle:ident),+ $(,)?) => {
+        $(
+            pub mod $day {
+                pub use crate::puzzles::$module::$puzzle;
+                use crate::types::{FromLines, Puzzle, Result, Solution};
+
+                pub fn generator(input: &str) -> Result<$puzzle> {
+                    $puzzle::from_lines(input.lines())
+                }
+
+                pub fn part1(puzzle: &$puzzle) -> Result<Solution> {
+                    puzzle.part_1()
+                }
+
+                pub fn part2(puzzle: &$puzzle) -> Result<Solution> {
+                    puzzle.part_2()
+                }
+            }
+        )+
+    };
+}
+
+cargo_aoc_days! {
+    day1 => day_1::Day1,
+    day2 => day_2::Day2,
+    day3 => day_3::Day3,
+    day4 => day_4::Day4,
+    day5 => day_5::Day5,
+    day6 => day_6::Day6,
+    day7 => day_7::Day7,
+    day8 => day_8::Day8,
+    day9 => day_9::Day9,
+    day10 => day_10::Day10,
+    day11 => day_11::Day11,
+    day12 => day_12::Day12,
+    day13 => day_13::Day13,
+    day14 => day_14::Day14,
+    day15 => day_15::Day15,
+    day16 => day_16::Day16,
+    day17 => day_17::Day17,
+    day18 => day_18::Day18,
+    day19 => day_19::Day19,
+    day20 => day_20::Day20,
+    day21 => day_21::Day21,
+    day22 => day_22::Day22,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day1() {
+        let puzzle = day1::generator("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n").unwrap();
+        assert!(day2::generator("sideways 3").is_err());
+        assert_eq!(day1::part1(&puzzle).unwrap().to_string(), "7");
+        assert_eq!(day1::part2(&puzzle).unwrap().to_string(), "5");
+    }
+}
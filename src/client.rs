@@ -0,0 +1,42 @@
+/*
+** src/client.rs
+*/
+
+use aoc2021::types::Result;
+
+use std::env;
+use std::time::Duration;
+
+const BASE_URL: &str = "https://adventofcode.com/2021";
+// identifies the tool to the Advent of Code servers, as they request
+const USER_AGENT: &str = "github.com/ianbrault/aoc2021";
+
+// an HTTP client for adventofcode.com, authenticated with the session cookie from AOC_SESSION
+pub struct Client {
+    agent: ureq::Agent,
+    session: String,
+}
+
+impl Client {
+    pub fn new() -> Result<Self> {
+        let session = env::var("AOC_SESSION")
+            .map_err(|_| "AOC_SESSION must be set to your adventofcode.com session cookie")?;
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build();
+        Ok(Self { agent, session })
+    }
+
+    // fetches a path relative to the 2021 event
+    pub fn get(&self, path: &str) -> Result<String> {
+        let url = format!("{}/{}", BASE_URL, path.trim_start_matches('/'));
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .map_err(|err| format!("GET {} failed: {}", url, err))?;
+        Ok(response.into_string()?)
+    }
+}
@@ -0,0 +1,152 @@
+/*
+** src/differential.rs
+*/
+
+// days with two implementations of the same computation, which are checked against one another
+// on generated and real inputs
+
+use crate::puzzles::day_21::GameRules;
+use crate::puzzles::{day_14, day_21, day_22, day_5};
+use crate::stress::{self, Rng};
+use crate::types;
+
+pub struct Differential {
+    pub day: usize,
+    // names the reference and alternative implementations
+    pub name: &'static str,
+    // the answers of the reference and alternative implementations to the given input
+    pub run: fn(&'static str) -> types::Result<(i64, i64)>,
+}
+
+pub const DIFFERENTIALS: [Differential; 6] = [
+    Differential {
+        day: 5,
+        name: "pairwise intersections vs point counter, without diagonals",
+        run: |input| {
+            let day = day_5::Day5::new(input)?;
+            Ok((
+                day.overlaps(false) as i64,
+                day.overlaps_counted(false) as i64,
+            ))
+        },
+    },
+    Differential {
+        day: 5,
+        name: "pairwise intersections vs point counter",
+        run: |input| {
+            let day = day_5::Day5::new(input)?;
+            Ok((day.overlaps(true) as i64, day.overlaps_counted(true) as i64))
+        },
+    },
+    Differential {
+        day: 14,
+        name: "pair counter vs transition matrix, to 40 steps",
+        run: |input| {
+            let day = day_14::Day14::new(input)?;
+            // note: an overflowing matrix power shows up as a disagreement
+            Ok((
+                day.element_spread(40) as i64,
+                day.element_spread_after(40)
+                    .map_or(-1, |spread| spread as i64),
+            ))
+        },
+    },
+    Differential {
+        day: 21,
+        name: "recursive vs memoized, to 14 points",
+        run: |input| {
+            let day = day_21::Day21::new(input)?;
+            // note: the recursive implementation takes seconds to play to 21 points, which is
+            // too slow to check many inputs, so the game is shortened
+            let rules = GameRules {
+                win_score: 14,
+                ..GameRules::DIRAC
+            };
+            Ok((
+                day.play_game_dirac(&rules) as i64,
+                day.play_game_dirac_memoized(&rules) as i64,
+            ))
+        },
+    },
+    Differential {
+        day: 22,
+        name: "bit cube vs clipped inclusion-exclusion, within the initialization region",
+        run: |input| {
+            let day = day_22::Day22::new(input)?;
+            Ok((
+                day.execute_initialization(),
+                day.execute_initialization_by_volume(),
+            ))
+        },
+    },
+    Differential {
+        day: 22,
+        name: "inclusion-exclusion vs splitting",
+        run: |input| {
+            let day = day_22::Day22::new(input)?;
+            Ok((
+                day.execute_procedure(),
+                day.execute_procedure_by_splitting(),
+            ))
+        },
+    },
+];
+
+pub fn differentials(day: usize) -> impl Iterator<Item = &'static Differential> {
+    DIFFERENTIALS
+        .iter()
+        .filter(move |differential| differential.day == day)
+}
+
+impl Differential {
+    // runs both implementations, describing any disagreement between them
+    pub fn check(&self, input: &'static str) -> Result<(), String> {
+        match (self.run)(input) {
+            Ok((a, b)) if a == b => Ok(()),
+            Ok((a, b)) => Err(format!("day {} {}: {} != {}", self.day, self.name, a, b)),
+            Err(err) => Err(format!("day {} {}: {}", self.day, self.name, err)),
+        }
+    }
+
+    // checks the implementations against the generated inputs seeded 0..rounds
+    pub fn check_generated(&self, rounds: u64, size: usize) -> Result<(), String> {
+        let generator = stress::generator(self.day)
+            .ok_or_else(|| format!("day {} has no input generator", self.day))?;
+        for seed in 0..rounds {
+            let input = (generator.generate)(&mut Rng::new(seed), size);
+            // puzzles borrow their input for the lifetime of the program
+            let input = Box::leak(input.into_boxed_str());
+            self.check(input)
+                .map_err(|err| format!("seed {}: {}", seed, err))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs;
+
+    #[test]
+    fn test_differentials_generated() {
+        for differential in DIFFERENTIALS.iter() {
+            // note: small inputs keep the slower implementations quick in debug builds
+            let size = stress::generator(differential.day)
+                .unwrap()
+                .default_size
+                .min(40);
+            assert_eq!(differential.check_generated(10, size), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_differentials_inputs() {
+        for differential in DIFFERENTIALS.iter() {
+            // note: the puzzle inputs are optional, so missing inputs are skipped
+            if let Ok(input) = inputs::load(differential.day) {
+                assert_eq!(differential.check(input), Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+/*
+** src/explain.rs
+*/
+
+use serde::Serialize;
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+
+// puzzles report key intermediate values as they solve, which are only collected when explain
+// mode is enabled; note that collection is per-thread, so values reported from worker threads
+// are dropped
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static EXPLANATIONS: RefCell<Vec<Explanation>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Explanation {
+    pub key: &'static str,
+    pub value: String,
+}
+
+pub fn enable() {
+    ENABLED.with(|enabled| enabled.set(true));
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.with(|enabled| enabled.get())
+}
+
+// note: the value is only formatted if explain mode is enabled
+pub fn report<T>(key: &'static str, value: T)
+where
+    T: Display,
+{
+    if is_enabled() {
+        let value = value.to_string();
+        EXPLANATIONS
+            .with(|explanations| explanations.borrow_mut().push(Explanation { key, value }));
+    }
+}
+
+// drains the values reported since the last call
+pub fn take() -> Vec<Explanation> {
+    EXPLANATIONS.with(|explanations| explanations.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        report("ignored", 1);
+        assert!(take().is_empty());
+
+        enable();
+        report("gamma", 22);
+        report("epsilon", format!("{:05b}", 9));
+        assert_eq!(
+            take(),
+            vec![
+                Explanation {
+                    key: "gamma",
+                    value: String::from("22")
+                },
+                Explanation {
+                    key: "epsilon",
+                    value: String::from("01001")
+                },
+            ]
+        );
+        assert!(take().is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+/*
+** src/ffi.rs
+*/
+
+use crate::types::RunError;
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::panic;
+use std::slice;
+use std::str;
+
+// status codes returned by aoc2021_solve, see include/aoc2021.h
+const AOC2021_OK: c_int = 0;
+const AOC2021_ERROR: c_int = 1;
+const AOC2021_INVALID_INPUT: c_int = 2;
+
+// hands ownership of a string over to the caller, who must release it with aoc2021_free
+fn into_c_string(s: String) -> *mut c_char {
+    // interior NUL bytes cannot be represented, so the string is truncated at the first one
+    let bytes = s.into_bytes();
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    CString::new(&bytes[..end]).unwrap().into_raw()
+}
+
+/// Solves a single part of a day's puzzle for the given input.
+///
+/// On success, `out` receives the answer and `AOC2021_OK` is returned; otherwise `out` receives
+/// an error message. In both cases the string must be released with `aoc2021_free`.
+///
+/// # Safety
+///
+/// `input_ptr` must point to `len` readable bytes and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2021_solve(
+    day: usize,
+    part: usize,
+    input_ptr: *const u8,
+    len: usize,
+    out: *mut *mut c_char,
+) -> c_int {
+    if input_ptr.is_null() || out.is_null() {
+        return AOC2021_INVALID_INPUT;
+    }
+
+    let input = match str::from_utf8(slice::from_raw_parts(input_ptr, len)) {
+        Ok(input) => input,
+        Err(_) => {
+            *out = into_c_string(String::from("puzzle input must be UTF-8"));
+            return AOC2021_INVALID_INPUT;
+        }
+    };
+    // note: a panic cannot unwind across the C boundary, where it would abort the caller's
+    // process, so it is reported as an error like any other
+    match panic::catch_unwind(|| crate::solve(day, part, input)) {
+        Ok(Ok(solution)) => {
+            *out = into_c_string(solution.to_string());
+            AOC2021_OK
+        }
+        Ok(Err(err)) => {
+            *out = into_c_string(err.to_string());
+            AOC2021_ERROR
+        }
+        Err(payload) => {
+            *out = into_c_string(RunError::from_panic(payload).to_string());
+            AOC2021_ERROR
+        }
+    }
+}
+
+/// Releases a string returned by `aoc2021_solve`.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by `aoc2021_solve` which has not yet been released.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2021_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::CStr;
+    use std::ptr;
+
+    fn solve(day: usize, part: usize, input: &str) -> (c_int, String) {
+        let mut out = ptr::null_mut();
+        unsafe {
+            let status = aoc2021_solve(day, part, input.as_ptr(), input.len(), &mut out);
+            let answer = CStr::from_ptr(out).to_str().unwrap().to_string();
+            aoc2021_free(out);
+            (status, answer)
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+        assert_eq!(solve(1, 1, input), (AOC2021_OK, String::from("7")));
+        assert_eq!(solve(1, 2, input), (AOC2021_OK, String::from("5")));
+        assert_eq!(solve(99, 1, input).0, AOC2021_ERROR);
+        assert_eq!(
+            solve(2, 1, "x,y\n\nzz 3"),
+            (
+                AOC2021_ERROR,
+                String::from("command 1: invalid command \"x,y\"")
+            )
+        );
+        // an input without incomplete lines has no middle score, which panics
+        let (status, message) = solve(10, 2, "");
+        assert_eq!(status, AOC2021_ERROR);
+        assert!(message.starts_with("panic: "), "{}", message);
+    }
+
+    #[test]
+    fn test_solve_invalid_input() {
+        let mut out = ptr::null_mut();
+        let input = [0xFF, 0xFE];
+        unsafe {
+            let status = aoc2021_solve(1, 1, input.as_ptr(), input.len(), &mut out);
+            assert_eq!(status, AOC2021_INVALID_INPUT);
+            aoc2021_free(out);
+            let status = aoc2021_solve(1, 1, ptr::null(), 0, &mut out);
+            assert_eq!(status, AOC2021_INVALID_INPUT);
+        }
+    }
+}
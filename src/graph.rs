@@ -0,0 +1,158 @@
+/*
+** src/graph.rs
+*/
+
+use crate::types::PriorityMap;
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+// shortest distances from the start to every reachable node of a graph whose edges all have a
+// weight of 0 or 1, where neighbors yields each neighbor of a node with the weight of the edge
+// this runs in linear time: the deque holds nodes at the current distance at its front and nodes
+// one further at its back, so free edges are pushed to the front and unit edges to the back
+pub fn zero_one_bfs<N, F, I>(start: N, mut neighbors: F) -> HashMap<N, u64>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut deque = VecDeque::from([(start, 0)]);
+
+    while let Some((node, distance)) = deque.pop_front() {
+        // skip nodes which were reached again by a shorter path after being queued
+        if distances.get(&node).is_some_and(|&d| d < distance) {
+            continue;
+        }
+        for (neighbor, weight) in neighbors(&node) {
+            assert!(weight <= 1, "0-1 BFS edge with weight {}", weight);
+            let neighbor_distance = distance + weight;
+            if distances
+                .get(&neighbor)
+                .is_some_and(|&d| d <= neighbor_distance)
+            {
+                continue;
+            }
+            distances.insert(neighbor.clone(), neighbor_distance);
+            if weight == 0 {
+                deque.push_front((neighbor, neighbor_distance));
+            } else {
+                deque.push_back((neighbor, neighbor_distance));
+            }
+        }
+    }
+
+    distances
+}
+
+// the cheapest route found by a search, as its total cost, the states along it from the start to
+// the goal, and the actions taken between them
+// note: there is one fewer action than there are states
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route<S, A> {
+    pub cost: u64,
+    pub states: Vec<S>,
+    pub actions: Vec<A>,
+}
+
+// cheapest route from the start to any state which satisfies is_goal, where successors yields
+// each action which can be taken from a state with the state it leads to and its cost
+// states are expanded in order of their distance from the start, recording the state and action
+// by which each was most cheaply reached so that the route can be walked back from the goal
+pub fn uniform_cost_search<S, A, F, I, G>(
+    start: S,
+    mut successors: F,
+    mut is_goal: G,
+) -> Option<Route<S, A>>
+where
+    S: Clone + Eq + Hash,
+    A: Clone,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (A, S, u64)>,
+    G: FnMut(&S) -> bool,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut predecessors: HashMap<S, (S, A)> = HashMap::new();
+    let mut queue = PriorityMap::new();
+    queue.push(start, 0);
+
+    while let Some((state, distance)) = queue.pop() {
+        if is_goal(&state) {
+            let mut states = vec![state];
+            let mut actions = vec![];
+            while let Some((previous, action)) = predecessors.get(states.last().unwrap()) {
+                actions.push(action.clone());
+                states.push(previous.clone());
+            }
+            states.reverse();
+            actions.reverse();
+            return Some(Route {
+                cost: distance,
+                states,
+                actions,
+            });
+        }
+        for (action, next, cost) in successors(&state) {
+            let next_distance = distance + cost;
+            if distances.get(&next).is_some_and(|&d| d <= next_distance) {
+                continue;
+            }
+            distances.insert(next.clone(), next_distance);
+            predecessors.insert(next.clone(), (state.clone(), action));
+            queue.push(next, next_distance);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Coord;
+
+    #[test]
+    fn test_zero_one_bfs() {
+        // a 5x5 grid in which moving right is free but moving down or left costs 1
+        let size = 5;
+        let distances = zero_one_bfs(Coord::default(), |&coord| {
+            [
+                coord.offset(0, 1).map(|c| (c, 0)),
+                coord.offset(0, -1).map(|c| (c, 1)),
+                coord.offset(1, 0).map(|c| (c, 1)),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|(c, _)| c.row < size && c.col < size)
+        });
+        assert_eq!(distances.len(), size * size);
+        assert_eq!(distances[&Coord::new(0, 4)], 0);
+        assert_eq!(distances[&Coord::new(3, 0)], 3);
+        assert_eq!(distances[&Coord::new(4, 4)], 4);
+    }
+
+    #[test]
+    fn test_uniform_cost_search() {
+        // a 4x4 grid in which entering a cell costs its value, moving in any of 4 directions
+        let costs = [[1, 9, 1, 1], [1, 9, 1, 9], [1, 1, 1, 9], [9, 9, 1, 1]];
+        let successors = |&coord: &Coord| {
+            [('v', 1, 0), ('^', -1, 0), ('>', 0, 1), ('<', 0, -1)]
+                .into_iter()
+                .filter_map(move |(action, dr, dc)| coord.offset(dr, dc).map(|c| (action, c)))
+                .filter(|(_, c)| c.row < 4 && c.col < 4)
+                .map(|(action, c)| (action, c, costs[c.row][c.col]))
+        };
+        let route =
+            uniform_cost_search(Coord::default(), successors, |&c| c == Coord::new(3, 3)).unwrap();
+        assert_eq!(route.cost, 6);
+        assert_eq!(route.actions.iter().collect::<String>(), "vv>>v>");
+        assert_eq!(route.states.len(), route.actions.len() + 1);
+        assert_eq!(route.states[0], Coord::default());
+
+        // the start is its own route, and an unreachable goal has none
+        let route = uniform_cost_search(Coord::default(), successors, |_| true).unwrap();
+        assert_eq!((route.cost, route.states.len()), (0, 1));
+        assert!(uniform_cost_search(Coord::default(), successors, |c| c.row > 4).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+/*
+** src/history.rs
+*/
+
+use aoc2021::types::RunResult;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = ".aoc-history.jsonl";
+// the structured results of the most recent run, for tooling to consume
+pub const RESULTS_FILE: &str = "target/aoc-results.json";
+
+// durations this much slower than the baseline, in percent, are flagged as regressions
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+// slowdowns smaller than this are timer noise rather than regressions
+const MIN_REGRESSION_MS: f64 = 0.1;
+
+#[derive(Serialize)]
+struct Run<'a> {
+    commit: String,
+    timestamp: u64,
+    results: &'a [RunResult],
+}
+
+impl<'a> Run<'a> {
+    fn new(results: &'a [RunResult]) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            commit: current_commit(),
+            timestamp,
+            results,
+        }
+    }
+}
+
+// a run as written to the results file, which reads back as a RecordedRun
+#[derive(Serialize)]
+struct ResultsArtifact<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    run: Run<'a>,
+}
+
+// the recorded form of a run, as read back from the history file
+#[derive(Deserialize)]
+struct RecordedRun {
+    commit: String,
+    timestamp: u64,
+    results: Vec<RecordedResult>,
+}
+
+#[derive(Deserialize)]
+struct RecordedResult {
+    day: usize,
+    part: usize,
+    answer: serde_json::Value,
+    duration_ms: f64,
+    // note: runs recorded before provenance was kept have none
+    #[serde(default)]
+    provenance: RecordedProvenance,
+}
+
+// the recorded form of a result's provenance
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RecordedProvenance {
+    pub variant: Option<String>,
+    pub parse_ms: Option<f64>,
+    pub input_hash: Option<String>,
+}
+
+fn current_commit() -> String {
+    process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+// appends a run to the history file, one JSON object per line
+pub fn record(results: &[RunResult]) -> io::Result<()> {
+    let run = Run::new(results);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_FILE)?;
+    writeln!(file, "{}", serde_json::to_string(&run)?)
+}
+
+// overwrites the results file with the given run
+pub fn write_results(results: &[RunResult]) -> io::Result<()> {
+    let artifact = ResultsArtifact {
+        version: env!("CARGO_PKG_VERSION"),
+        run: Run::new(results),
+    };
+    let path = Path::new(RESULTS_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&artifact)?)
+}
+
+fn load() -> io::Result<Vec<RecordedRun>> {
+    let history = fs::read_to_string(HISTORY_FILE)?;
+    history
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+        .collect()
+}
+
+// the most recent recorded duration of each day and part, in milliseconds
+pub fn latest_durations() -> io::Result<HashMap<(usize, usize), f64>> {
+    let mut durations = HashMap::new();
+    for run in load()?.iter() {
+        for result in run.results.iter() {
+            durations.insert((result.day, result.part), result.duration_ms);
+        }
+    }
+    Ok(durations)
+}
+
+// the provenance of the most recent recorded result of each day and part
+pub fn latest_provenance() -> io::Result<HashMap<(usize, usize), RecordedProvenance>> {
+    let mut provenance = HashMap::new();
+    for run in load()?.into_iter() {
+        for result in run.results.into_iter() {
+            provenance.insert((result.day, result.part), result.provenance);
+        }
+    }
+    Ok(provenance)
+}
+
+fn format_answer(answer: &serde_json::Value) -> String {
+    match answer {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn differs<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a != b)
+}
+
+fn compare_runs(
+    baseline: &HashMap<(usize, usize), &RecordedResult>,
+    latest: &RecordedRun,
+    threshold: f64,
+) -> usize {
+    let mut n_regressions = 0;
+    for result in latest.results.iter() {
+        let label = format!("day {:02} part {}", result.day, result.part);
+        let base = match baseline.get(&(result.day, result.part)) {
+            Some(base) => base,
+            None => {
+                println!("{}: {:.3}ms (no baseline)", label, result.duration_ms);
+                continue;
+            }
+        };
+
+        let change = if base.duration_ms > 0.0 {
+            (result.duration_ms - base.duration_ms) / base.duration_ms * 100.0
+        } else {
+            0.0
+        };
+        let mut flags = vec![];
+        if change > threshold && result.duration_ms - base.duration_ms > MIN_REGRESSION_MS {
+            flags.push("REGRESSION");
+            n_regressions += 1;
+        }
+        if result.answer != base.answer {
+            flags.push("ANSWER CHANGED");
+        }
+        // note: results which do not record their provenance are assumed to match
+        let (now, then) = (&result.provenance, &base.provenance);
+        if differs(&now.input_hash, &then.input_hash) {
+            flags.push("INPUT CHANGED");
+        }
+        if differs(&now.variant, &then.variant) {
+            flags.push("VARIANT CHANGED");
+        }
+        println!(
+            "{}: {:.3}ms -> {:.3}ms ({:+.1}%){}",
+            label,
+            base.duration_ms,
+            result.duration_ms,
+            change,
+            flags
+                .iter()
+                .map(|flag| format!(" {}", flag))
+                .collect::<String>()
+        );
+        if result.answer != base.answer {
+            println!(
+                "    answer: {} -> {}",
+                format_answer(&base.answer),
+                format_answer(&result.answer)
+            );
+        }
+    }
+    n_regressions
+}
+
+// compares the latest run against the most recent earlier result for each day and part, only
+// considering runs at the baseline commit if one is given
+pub fn compare(baseline: Option<&str>, threshold: f64) {
+    let mut history = match load() {
+        Ok(history) => history,
+        Err(err) => {
+            eprintln!("failed to read {}: {}", HISTORY_FILE, err);
+            process::exit(1);
+        }
+    };
+
+    let latest = match history.pop() {
+        Some(latest) => latest,
+        None => {
+            eprintln!("no runs have been recorded");
+            process::exit(1);
+        }
+    };
+    let mut base = HashMap::new();
+    // runs are in chronological order, so later results replace earlier ones
+    for run in history.iter() {
+        if baseline.is_none_or(|commit| run.commit.starts_with(commit)) {
+            for result in run.results.iter() {
+                base.insert((result.day, result.part), result);
+            }
+        }
+    }
+    if base.is_empty() {
+        eprintln!("no baseline run found");
+        process::exit(1);
+    }
+
+    println!(
+        "comparing {} ({}) against baseline {}",
+        latest.commit,
+        latest.timestamp,
+        baseline.unwrap_or("(previous runs)")
+    );
+    let n_regressions = compare_runs(&base, &latest, threshold);
+    if n_regressions > 0 {
+        println!("{} regression(s) above {}%", n_regressions, threshold);
+        process::exit(2);
+    }
+}
@@ -0,0 +1,133 @@
+/*
+** src/info.rs
+*/
+
+use crate::cache::Cache;
+use crate::client::Client;
+use crate::history;
+
+use aoc2021::inputs;
+use aoc2021::output;
+use aoc2021::puzzles;
+use aoc2021::types::Result;
+
+use std::process;
+
+fn page_name(day: usize) -> String {
+    format!("day{:02}.html", day)
+}
+
+// the title is in a heading like "<h2>--- Day 1: Sonar Sweep ---</h2>"
+fn parse_title(page: &str) -> Option<String> {
+    let start = page.find("<h2>--- Day ")? + "<h2>--- Day ".len();
+    let heading = &page[start..];
+    let heading = &heading[..heading.find(" ---</h2>")?];
+    heading.split_once(": ").map(|(_, title)| title.to_string())
+}
+
+// the title of a day's puzzle, if its page has been fetched
+pub fn cached_title(cache: &Cache, day: usize) -> Option<String> {
+    cache
+        .get_page(&page_name(day))
+        .and_then(|page| parse_title(&page))
+}
+
+// once logged in, the page shows the accepted answer below each solved part
+fn parse_answers(page: &str) -> Vec<String> {
+    page.split("Your puzzle answer was <code>")
+        .skip(1)
+        .filter_map(|s| {
+            s.split_once("</code>")
+                .map(|(answer, _)| answer.to_string())
+        })
+        .collect()
+}
+
+// fetches the puzzle page for a day, which is cached once both parts have been answered since it
+// no longer changes after that
+fn day_page(cache: &Cache, day: usize) -> Result<String> {
+    let name = page_name(day);
+    let cached = cache.get_page(&name);
+    if let Some(page) = &cached {
+        if parse_answers(page).len() == 2 {
+            return Ok(page.clone());
+        }
+    }
+    let page = match Client::new().and_then(|client| client.get(&format!("day/{}", day))) {
+        Ok(page) => page,
+        // fall back to a page which only lacks answers
+        Err(_) if cached.is_some() => return Ok(cached.unwrap()),
+        Err(err) => return Err(err),
+    };
+    cache.insert_page(&name, &page)?;
+    Ok(page)
+}
+
+pub fn info(day: usize) {
+    if !puzzles::is_known(day) {
+        eprintln!("day {} is not a known puzzle", day);
+        process::exit(1);
+    }
+    let cache = Cache::new();
+    let (title, answers) = match day_page(&cache, day) {
+        Ok(page) => (parse_title(&page), parse_answers(&page)),
+        Err(err) => {
+            eprintln!("failed to fetch the puzzle page: {}", err);
+            (None, vec![])
+        }
+    };
+    let input = inputs::load(day).ok();
+    let durations = history::latest_durations().unwrap_or_default();
+
+    println!(
+        "day {:02}: {}",
+        day,
+        title.as_deref().unwrap_or("(title unavailable)")
+    );
+    println!("    https://adventofcode.com/2021/day/{}", day);
+    for part in 1..=2 {
+        // answers are only known locally once they have been computed and cached
+        let answer = input.and_then(|input| cache.get(day, part, input));
+        let status = match (&answer, answers.get(part - 1)) {
+            (Some(answer), Some(accepted)) if answer == accepted => "verified",
+            (Some(_), Some(_)) => "WRONG",
+            (Some(_), None) => "unverified",
+            (None, _) if input.is_none() => "no input",
+            (None, _) => "not run",
+        };
+        let timing = durations
+            .get(&(day, part))
+            .map(|ms| format!("{:.3}ms", ms))
+            .unwrap_or_else(|| "-".into());
+        match answer.as_deref() {
+            // note: a multi-line answer follows its status, rather than splitting the line
+            Some(answer) if output::is_multiline(answer) => {
+                println!("    part {}: ({}), {}", part, status, timing);
+                println!("{}", output::indented(answer));
+            }
+            answer => println!(
+                "    part {}: {} ({}), {}",
+                part,
+                answer.unwrap_or("-"),
+                status,
+                timing
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page() {
+        let page = "<main>\n<article class=\"day-desc\"><h2>--- Day 1: Sonar Sweep ---</h2>\
+                    <p>...</p></article>\n<p>Your puzzle answer was <code>1521</code>.</p>\
+                    <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2></article>\n\
+                    <p>Your puzzle answer was <code>1543</code>.</p>";
+        assert_eq!(parse_title(page), Some(String::from("Sonar Sweep")));
+        assert_eq!(parse_answers(page), vec!["1521", "1543"]);
+        assert_eq!(parse_title("<h2>--- Part Two ---</h2>"), None);
+    }
+}
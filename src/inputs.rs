@@ -0,0 +1,194 @@
+/*
+** src/inputs.rs
+*/
+
+// puzzle inputs are personal and are only compiled into the binary with the bundled-inputs
+// feature, otherwise they are read from the input directory when they are first needed, so that
+// the crate builds and tests without them
+//
+// inputs may also be stored encrypted, as <day>.txt.enc next to where the plaintext would be, so
+// that they can be committed without publishing them; these hold a random nonce followed by the
+// AES-256-GCM ciphertext, and are decrypted with the hex-encoded key in AOC_INPUT_KEY
+// note: encryption draws its nonces from the OS, so is left out of the wasm32 build, which has no
+// input files to read anyway
+
+use crate::puzzles;
+use crate::types::Result;
+
+use std::env;
+use std::path::PathBuf;
+
+// overrides the directory from which inputs are read at runtime
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+// the hex-encoded 256-bit key for encrypted inputs
+pub const INPUT_KEY_VAR: &str = "AOC_INPUT_KEY";
+
+fn check_day(day: usize) -> Result<()> {
+    if !puzzles::is_known(day) {
+        return Err(format!("day {} is not a known puzzle", day).into());
+    }
+    Ok(())
+}
+
+// the path from which the input for the given day is read at runtime
+pub fn path(day: usize) -> PathBuf {
+    let dir = env::var_os(INPUT_DIR_VAR)
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or_else(|| "input".into());
+    PathBuf::from(dir).join(format!("{}.txt", day))
+}
+
+// the path from which the encrypted input for the given day is read, if there is no plaintext
+pub fn encrypted_path(day: usize) -> PathBuf {
+    path(day).with_extension("txt.enc")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::encryption::{decrypt, encrypt, key_from_env};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod encryption {
+    use super::INPUT_KEY_VAR;
+    use crate::types::Result;
+
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+
+    use std::env;
+
+    const KEY_SIZE: usize = 32;
+    const NONCE_SIZE: usize = 12;
+
+    fn parse_key(hex: &str) -> Result<[u8; KEY_SIZE]> {
+        let hex = hex.trim();
+        if hex.len() != KEY_SIZE * 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(
+                format!("{} must be {} hex characters", INPUT_KEY_VAR, KEY_SIZE * 2).into(),
+            );
+        }
+        let mut key = [0; KEY_SIZE];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+        }
+        Ok(key)
+    }
+
+    pub fn key_from_env() -> Result<[u8; KEY_SIZE]> {
+        let hex = env::var(INPUT_KEY_VAR).map_err(|_| format!("{} is not set", INPUT_KEY_VAR))?;
+        parse_key(&hex)
+    }
+
+    pub fn encrypt(input: &str, key: &[u8; KEY_SIZE]) -> Result<Vec<u8>> {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, input.as_bytes())
+            .map_err(|_| "failed to encrypt input")?;
+        Ok(nonce.into_iter().chain(ciphertext).collect())
+    }
+
+    // reads and decrypts the encrypted input for the given day
+    #[cfg(not(feature = "bundled-inputs"))]
+    pub fn read(day: usize) -> Result<String> {
+        use crate::types::Context;
+        use std::fs;
+
+        let path = super::encrypted_path(day);
+        let data = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        decrypt(&data, &key_from_env()?).context(path.display())
+    }
+
+    pub fn decrypt(data: &[u8], key: &[u8; KEY_SIZE]) -> Result<String> {
+        if data.len() < NONCE_SIZE {
+            return Err("encrypted input is truncated".into());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        // note: decryption fails both for the wrong key and for a tampered file
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| format!("failed to decrypt input, check {}", INPUT_KEY_VAR))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_encryption() {
+            let key = parse_key(&"0123456789abcdef".repeat(4)).unwrap();
+            let input = "forward 5\ndown 5\nup 3\n";
+            let data = encrypt(input, &key).unwrap();
+            assert_eq!(data.len(), NONCE_SIZE + input.len() + 16);
+            assert_eq!(decrypt(&data, &key).unwrap(), input);
+
+            let mut wrong_key = key;
+            wrong_key[0] ^= 1;
+            assert!(decrypt(&data, &wrong_key).is_err());
+            assert!(decrypt(&data[..NONCE_SIZE - 1], &key).is_err());
+        }
+
+        #[test]
+        fn test_parse_key() {
+            assert!(parse_key("0123").is_err());
+            assert!(parse_key(&"xy".repeat(KEY_SIZE)).is_err());
+            assert_eq!(parse_key(&"ff".repeat(KEY_SIZE)).unwrap(), [0xff; KEY_SIZE]);
+        }
+    }
+}
+
+#[cfg(not(feature = "bundled-inputs"))]
+fn read(day: usize) -> Result<String> {
+    use std::fs;
+
+    let path = path(day);
+    match fs::read_to_string(&path) {
+        Ok(input) => Ok(input),
+        #[cfg(not(target_arch = "wasm32"))]
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && encrypted_path(day).exists() => {
+            encryption::read(day)
+        }
+        Err(err) => Err(format!("failed to read {}: {}", path.display(), err).into()),
+    }
+}
+
+#[cfg(feature = "bundled-inputs")]
+pub fn load(day: usize) -> Result<&'static str> {
+    check_day(day)?;
+    // note: registered puzzles have no bundled inputs
+    puzzles::INPUTS
+        .get(day - 1)
+        .copied()
+        .ok_or_else(|| format!("day {} has no bundled input", day).into())
+}
+
+#[cfg(not(feature = "bundled-inputs"))]
+pub fn load(day: usize) -> Result<&'static str> {
+    use std::sync::Mutex;
+
+    // puzzles borrow their input for the lifetime of the program, so each input is leaked once
+    // and then reused
+    static LOADED: Mutex<[Option<&'static str>; puzzles::MAX_DAY]> =
+        Mutex::new([None; puzzles::MAX_DAY]);
+
+    check_day(day)?;
+    let mut loaded = LOADED.lock().unwrap();
+    if let Some(input) = loaded[day - 1] {
+        return Ok(input);
+    }
+    let input: &'static str = Box::leak(read(day)?.into_boxed_str());
+    loaded[day - 1] = Some(input);
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_unknown_day() {
+        assert!(load(0).is_err());
+        assert!(load(puzzles::N_DAYS + 1).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+/*
+** src/leaderboard.rs
+*/
+
+use crate::client::Client;
+
+use aoc2021::types::Result;
+
+use serde::Deserialize;
+
+use std::collections::HashMap;
+use std::process;
+
+const N_DAYS: usize = 25;
+
+#[derive(Deserialize)]
+struct Leaderboard {
+    members: HashMap<String, Member>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    id: u64,
+    name: Option<String>,
+    stars: u64,
+    local_score: u64,
+    last_star_ts: u64,
+    // day -> part -> completion
+    completion_day_level: HashMap<String, HashMap<String, Star>>,
+}
+
+#[derive(Deserialize)]
+struct Star {
+    get_star_ts: u64,
+}
+
+impl Member {
+    fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+
+    // '*' for both stars on a day, '.' for only the first, and ' ' for neither
+    fn day_markers(&self) -> String {
+        (1..=N_DAYS)
+            .map(
+                |day| match self.completion_day_level.get(&day.to_string()) {
+                    Some(parts) if parts.len() == 2 => '*',
+                    Some(_) => '.',
+                    None => ' ',
+                },
+            )
+            .collect()
+    }
+
+    // seconds between the first star of a day and the second
+    fn part_2_delta(&self, day: usize) -> Option<u64> {
+        let parts = self.completion_day_level.get(&day.to_string())?;
+        let part_1 = parts.get("1")?.get_star_ts;
+        let part_2 = parts.get("2")?.get_star_ts;
+        Some(part_2.saturating_sub(part_1))
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+fn render(leaderboard: &Leaderboard) -> String {
+    let mut members = leaderboard.members.values().collect::<Vec<_>>();
+    // ties are broken by whoever reached the score first
+    members.sort_by_key(|member| (std::cmp::Reverse(member.local_score), member.last_star_ts));
+
+    let name_width = members
+        .iter()
+        .map(|member| member.name().len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut lines = vec![format!(
+        "{:>4}  {:<width$}  {:>5}  {:>5}  {:<25}  {}",
+        "rank",
+        "name",
+        "score",
+        "stars",
+        "days",
+        "fastest part 2",
+        width = name_width
+    )];
+    for (rank, member) in members.iter().enumerate() {
+        let fastest = (1..=N_DAYS)
+            .filter_map(|day| member.part_2_delta(day).map(|delta| (delta, day)))
+            .min()
+            .map(|(delta, day)| format!("{} (day {})", format_duration(delta), day))
+            .unwrap_or_default();
+        lines.push(format!(
+            "{:>4}  {:<width$}  {:>5}  {:>5}  {}  {}",
+            rank + 1,
+            member.name(),
+            member.local_score,
+            member.stars,
+            member.day_markers(),
+            fastest,
+            width = name_width
+        ));
+    }
+    lines.join("\n")
+}
+
+fn fetch(id: &str) -> Result<Leaderboard> {
+    let client = Client::new()?;
+    let body = client.get(&format!("leaderboard/private/view/{}.json", id))?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+pub fn leaderboard(id: &str) {
+    match fetch(id) {
+        Ok(leaderboard) => println!("{}", render(&leaderboard)),
+        Err(err) => {
+            eprintln!("failed to fetch leaderboard {}: {}", id, err);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_LEADERBOARD: &str = r#"{
+        "owner_id": 1,
+        "event": "2021",
+        "members": {
+            "1": {
+                "id": 1, "name": "alice", "stars": 3, "local_score": 10, "global_score": 0,
+                "last_star_ts": 1638400000,
+                "completion_day_level": {
+                    "1": {"1": {"get_star_ts": 1638336000, "star_index": 0},
+                          "2": {"get_star_ts": 1638336090, "star_index": 1}},
+                    "2": {"1": {"get_star_ts": 1638400000, "star_index": 2}}
+                }
+            },
+            "2": {
+                "id": 2, "name": null, "stars": 0, "local_score": 0, "global_score": 0,
+                "last_star_ts": 0, "completion_day_level": {}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_render() {
+        let leaderboard = serde_json::from_str(TEST_LEADERBOARD).unwrap();
+        let table = render(&leaderboard);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("alice"));
+        assert!(lines[1].contains("*.   "));
+        assert!(lines[1].ends_with("00:01:30 (day 1)"));
+        assert!(lines[2].contains("(anonymous user #2)"));
+    }
+}
@@ -0,0 +1,54 @@
+/*
+** src/lib.rs
+*/
+
+#[macro_use]
+pub mod utils;
+
+pub mod differential;
+pub mod explain;
+pub mod graph;
+pub mod inputs;
+pub mod output;
+pub mod puzzles;
+pub mod stress;
+pub mod types;
+pub mod validate;
+pub mod variants;
+pub mod viz;
+
+#[cfg(feature = "cargo-aoc")]
+pub mod cargo_aoc;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+
+use crate::types::{Result, Solution};
+
+// solves a single part of a day's puzzle for the given input, where malformed input is an error
+// rather than a panic
+pub fn solve(day: usize, part: usize, input: &str) -> Result<Solution> {
+    let puzzle = puzzles::from_lines(day, input.lines()).ok_or("invalid day")??;
+    match part {
+        1 => puzzle.part_1(),
+        2 => puzzle.part_2(),
+        _ => Err("invalid part".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(solve(2, 1, "forward 5\ndown 5").unwrap().to_string(), "25");
+        assert_eq!(
+            solve(2, 1, "x,y\n\nzz 3").unwrap_err().to_string(),
+            "command 1: invalid command \"x,y\""
+        );
+        assert!(solve(99, 1, "").is_err());
+        assert!(solve(2, 3, "forward 5").is_err());
+    }
+}
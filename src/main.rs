@@ -2,48 +2,825 @@
 ** src/main.rs
 */
 
-#[macro_use]
-mod utils;
+mod bench;
+mod cache;
+mod client;
+mod history;
+mod info;
+mod leaderboard;
+mod notes;
+mod server;
+mod stats;
+mod summary;
+mod verify;
 
-mod puzzles;
-mod types;
+use aoc2021::types::Context;
+use aoc2021::{
+    differential, explain, inputs, output, puzzles, stress, types, utils, validate, variants, viz,
+};
+
+use tracing_chrome::ChromeLayerBuilder;
+use tracing_subscriber::prelude::*;
 
 use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::panic;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
 
 enum Day {
     Which(usize),
     All,
 }
 
-fn parse_args() -> Day {
-    match env::args().nth(1) {
-        Some(n) => Day::Which(n.parse().unwrap()),
-        None => Day::All,
+struct Options {
+    // recompute answers even if they are cached
+    force: bool,
+    // keep puzzle input out of error reports, referring to it by digest instead
+    redact_input: bool,
+    // percent slowdown flagged as a regression by the compare command
+    threshold: f64,
+    // size of the worker pool for parallel solutions, where 1 runs them deterministically on a
+    // single thread; defaults to rayon's choice, which honors RAYON_NUM_THREADS
+    threads: Option<usize>,
+    // print the key intermediate values of each solution
+    explain: bool,
+    // print the backtrace of each part which panics beneath its error
+    verbose: bool,
+    // exit as soon as an answer deviates from the one recorded in verify::ANSWERS_FILE
+    assert: bool,
+    // solve the days which have more than one implementation with the named one, one of
+    // variants::NAMES, rather than the puzzle's own choice
+    implementation: Option<String>,
+    // write the computed results of the run to history::RESULTS_FILE
+    // note: cached answers are not recomputed, so pair with force for the results of every day
+    write_results: bool,
+    // write a Chrome trace of the run, viewable in chrome://tracing or Perfetto
+    trace: bool,
+    // print a histogram of each part's runtimes from the bench command
+    histogram: bool,
+    // visualize the given day instead of solving it
+    visualize: Option<usize>,
+    // visualize the given day's values as a heatmap rather than as its frames
+    heatmap: bool,
+    // write visualizations as SVG files into this directory rather than printing them
+    svg_dir: Option<String>,
+    // write visualizations as an animated GIF to this path rather than printing them
+    gif_path: Option<String>,
+    // delay between frames of an animated GIF, in milliseconds
+    delay_ms: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            force: false,
+            redact_input: false,
+            threshold: history::DEFAULT_THRESHOLD,
+            threads: None,
+            explain: false,
+            verbose: false,
+            assert: false,
+            implementation: None,
+            write_results: false,
+            trace: false,
+            histogram: false,
+            visualize: None,
+            heatmap: false,
+            svg_dir: None,
+            gif_path: None,
+            delay_ms: 100,
+        }
     }
 }
 
-fn main() {
-    // determine which puzzle to run
-    let which_puzzle = parse_args();
-    let puzzles = match which_puzzle {
-        Day::Which(n) => vec![puzzles::all().into_iter().nth(n - 1).unwrap()],
-        Day::All => puzzles::all(),
+enum Command {
+    Run(Day),
+    // apply the day 20 image enhancement algorithm an arbitrary number of times
+    Enhance(usize),
+    // count the day 6 lanternfish after an arbitrary number of days
+    Lanternfish(u64),
+    // decode day 16 BITS transmissions read interactively from stdin
+    Bits,
+    // print a day's parsed input without solving it
+    ParseDump(usize),
+    // serve solutions over HTTP
+    Serve(u16),
+    // compare the latest recorded run against a baseline commit
+    Compare(Option<String>),
+    // solve each part of a day repeatedly, summarizing the runtimes
+    Bench(usize, usize),
+    // render a private leaderboard
+    Leaderboard(String),
+    // compare my solve times against the program's runtimes, optionally as JSON
+    Stats(bool),
+    // run a day against two inputs side by side
+    CompareInputs(usize, String, String),
+    // show a day's puzzle title alongside its local status
+    Info(usize),
+    // print the notes on a day's solution
+    Notes(usize),
+    // print a markdown table of answers, runtimes and notes
+    Summary,
+    // print the digest of a day's input, to refer to it without sharing it
+    HashInput(usize),
+    // check that the input files have the expected shape
+    Validate(Day),
+    // check every day's answers against the examples and the expected answers to the inputs
+    VerifyAll,
+    // solve a number of random inputs for a day, of an optional size, reporting any failures
+    Stress(usize, u64, Option<usize>),
+    // write an encrypted copy of each input, to commit in place of the plaintext
+    EncryptInputs,
+    // render the frames of a day's visualization
+    Visualize(usize),
+}
+
+fn usage() -> ! {
+    eprintln!("usage: aoc2021 [--force] [--redact-input] [--explain] [--verbose] [--assert] [--impl naive|fast] [--results] [--threads N] [--trace-output json] [DAY]");
+    eprintln!("       aoc2021 enhance ROUNDS");
+    eprintln!("       aoc2021 lanternfish DAYS");
+    eprintln!("       aoc2021 bits");
+    eprintln!("       aoc2021 serve [PORT]");
+    eprintln!("       aoc2021 [--redact-input] validate [DAY]");
+    eprintln!("       aoc2021 verify-all");
+    eprintln!("       aoc2021 stress DAY ROUNDS [SIZE]");
+    eprintln!("       aoc2021 encrypt-inputs");
+    eprintln!("       aoc2021 info DAY");
+    eprintln!("       aoc2021 notes DAY");
+    eprintln!("       aoc2021 parse-dump DAY");
+    eprintln!("       aoc2021 summary");
+    eprintln!("       aoc2021 hash-input DAY");
+    eprintln!("       aoc2021 compare-inputs DAY FILE FILE");
+    eprintln!("       aoc2021 leaderboard ID");
+    eprintln!("       aoc2021 stats [json]");
+    eprintln!("       aoc2021 [--threshold PERCENT] compare [COMMIT]");
+    eprintln!("       aoc2021 [--histogram] [--impl naive|fast] bench DAY [RUNS]");
+    eprintln!("       aoc2021 --visualize DAY [--svg DIR | --gif PATH [--delay MS]]");
+    eprintln!("       aoc2021 --visualize DAY --heatmap [--svg DIR]");
+    process::exit(1);
+}
+
+fn parse_args() -> (Command, Options) {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let mut options = Options::default();
+    let mut positional = vec![];
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        match arg {
+            "--force" => options.force = true,
+            "--redact-input" => options.redact_input = true,
+            "--explain" => options.explain = true,
+            "--verbose" => options.verbose = true,
+            "--assert" => options.assert = true,
+            "--impl" => {
+                options.implementation = args
+                    .next()
+                    .filter(|name| variants::NAMES.contains(name))
+                    .map(|name| Some(name.to_string()))
+                    .unwrap_or_else(|| usage())
+            }
+            "--results" => options.write_results = true,
+            "--histogram" => options.histogram = true,
+            "--threads" => {
+                options.threads = args
+                    .next()
+                    .and_then(|threads| threads.parse().ok())
+                    .filter(|&threads| threads > 0)
+                    .map(Some)
+                    .unwrap_or_else(|| usage())
+            }
+            "--trace-output" => match args.next() {
+                Some("json") => options.trace = true,
+                _ => usage(),
+            },
+            "--visualize" => {
+                options.visualize = args
+                    .next()
+                    .and_then(|day| day.parse().ok())
+                    .map(Some)
+                    .unwrap_or_else(|| usage())
+            }
+            "--heatmap" => options.heatmap = true,
+            "--svg" => options.svg_dir = Some(args.next().unwrap_or_else(|| usage()).to_string()),
+            "--gif" => options.gif_path = Some(args.next().unwrap_or_else(|| usage()).to_string()),
+            "--delay" => {
+                options.delay_ms = args
+                    .next()
+                    .and_then(|delay| delay.parse().ok())
+                    .unwrap_or_else(|| usage())
+            }
+            "--threshold" => {
+                options.threshold = args
+                    .next()
+                    .and_then(|threshold| threshold.parse().ok())
+                    .unwrap_or_else(|| usage())
+            }
+            _ if arg.starts_with("--") => usage(),
+            _ => positional.push(arg),
+        }
+    }
+
+    let command = match positional.as_slice() {
+        [] if options.visualize.is_some() => Command::Visualize(options.visualize.unwrap()),
+        _ if options.visualize.is_some() => usage(),
+        [] => Command::Run(Day::All),
+        ["enhance", n] => Command::Enhance(n.parse().unwrap_or_else(|_| usage())),
+        ["lanternfish", n] => Command::Lanternfish(n.parse().unwrap_or_else(|_| usage())),
+        ["bits"] => Command::Bits,
+        ["serve"] => Command::Serve(8080),
+        ["serve", port] => Command::Serve(port.parse().unwrap_or_else(|_| usage())),
+        ["leaderboard", id] => Command::Leaderboard(id.to_string()),
+        ["stats"] => Command::Stats(false),
+        ["stats", "json"] => Command::Stats(true),
+        ["compare-inputs", n, a, b] => Command::CompareInputs(
+            n.parse().unwrap_or_else(|_| usage()),
+            a.to_string(),
+            b.to_string(),
+        ),
+        ["info", n] => Command::Info(n.parse().unwrap_or_else(|_| usage())),
+        ["notes", n] => Command::Notes(n.parse().unwrap_or_else(|_| usage())),
+        ["parse-dump", n] => Command::ParseDump(n.parse().unwrap_or_else(|_| usage())),
+        ["summary"] => Command::Summary,
+        ["hash-input", n] => Command::HashInput(n.parse().unwrap_or_else(|_| usage())),
+        ["encrypt-inputs"] => Command::EncryptInputs,
+        ["validate"] => Command::Validate(Day::All),
+        ["validate", n] => Command::Validate(Day::Which(n.parse().unwrap_or_else(|_| usage()))),
+        ["verify-all"] => Command::VerifyAll,
+        ["stress", n, rounds] => Command::Stress(
+            n.parse().unwrap_or_else(|_| usage()),
+            rounds.parse().unwrap_or_else(|_| usage()),
+            None,
+        ),
+        ["stress", n, rounds, size] => Command::Stress(
+            n.parse().unwrap_or_else(|_| usage()),
+            rounds.parse().unwrap_or_else(|_| usage()),
+            Some(size.parse().unwrap_or_else(|_| usage())),
+        ),
+        ["bench", n] => Command::Bench(n.parse().unwrap_or_else(|_| usage()), 100),
+        ["bench", n, runs] => Command::Bench(
+            n.parse().unwrap_or_else(|_| usage()),
+            runs.parse()
+                .ok()
+                .filter(|&runs| runs > 0)
+                .unwrap_or_else(|| usage()),
+        ),
+        ["compare"] => Command::Compare(None),
+        ["compare", commit] => Command::Compare(Some(commit.to_string())),
+        [n] => Command::Run(Day::Which(n.parse().unwrap_or_else(|_| usage()))),
+        _ => usage(),
     };
+    (command, options)
+}
 
-    for (day, puzzle) in puzzles.into_iter().enumerate() {
-        let day = match which_puzzle {
-            Day::Which(n) => n,
-            Day::All => day + 1,
+fn run(which_puzzle: Day, options: &Options) {
+    let days = match which_puzzle {
+        Day::Which(n) if puzzles::is_known(n) => vec![n],
+        Day::Which(_) => usage(),
+        Day::All => puzzles::days(),
+    };
+    // note: a single day must have the chosen implementation, but running every day only
+    // swaps it in for the days which have it
+    if let (Day::Which(day), Some(name)) = (&which_puzzle, &options.implementation) {
+        if variants::variant(*day, name).is_none() {
+            eprintln!("day {} has no {} implementation", day, name);
+            process::exit(1);
+        }
+    }
+    let cache = cache::Cache::new();
+    // note: without an answers file there is nothing to assert against
+    let answers = if options.assert {
+        verify::load_answers().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        })
+    } else {
+        verify::Answers::new()
+    };
+    // results which were computed rather than read from the cache
+    let mut results = vec![];
+
+    for day in days {
+        let _span = tracing::info_span!("day", day).entered();
+        let input = match inputs::load(day) {
+            Ok(input) => input,
+            Err(err) => {
+                println!("day {:02}: {}", day, err);
+                continue;
+            }
         };
-        // part 1
-        match puzzle.part_1() {
-            Ok(solution) => println!("day {:02} part 1: {}", day, solution),
-            Err(err) => println!("day {:02} part 1: {}", day, err),
+        // puzzles are only constructed if an answer is missing from the cache
+        let mut puzzle = None;
+        let mut parse_duration = None;
+        let input_hash = utils::input_digest(input);
+        for part in 1..=2 {
+            // note: explanations are only reported when the answer is computed, and the cache
+            // does not know which implementation computed an answer
+            if !options.force && !options.explain && options.implementation.is_none() {
+                if let Some(answer) = cache.get(day, part, input) {
+                    verify::assert_answer(&answers, day, part, &answer);
+                    let label = format!("day {:02} part {}", day, part);
+                    println!("{}", output::text(&label, &answer));
+                    continue;
+                }
+            }
+
+            // note: a malformed input fails both parts, rather than the whole run
+            let puzzle = puzzle.get_or_insert_with(|| {
+                let start = Instant::now();
+                let puzzle = types::RunError::catch(|| {
+                    variants::new(day, options.implementation.as_deref(), input)
+                        .ok_or_else(|| format!("day {} is not a known puzzle", day))?
+                        .context("parsing input")
+                });
+                parse_duration = Some(start.elapsed());
+                puzzle
+            });
+            // note: both parts share the one parse of the input
+            let provenance = types::Provenance {
+                variant: options.implementation.clone(),
+                iterations: Some(1),
+                parse_duration,
+                input_hash: Some(input_hash.clone()),
+            };
+            let result = match puzzle {
+                Ok(puzzle) => types::RunResult::new(day, part, puzzle.as_ref()),
+                Err(err) => types::RunResult::failed(day, part, err.clone()),
+            };
+            let result = result.with_provenance(provenance);
+            match &result.answer {
+                Ok(solution) => verify::assert_answer(&answers, day, part, &solution.to_string()),
+                Err(err) => verify::assert_answer(&answers, day, part, &err.message),
+            }
+            match &result.answer {
+                Ok(solution) => {
+                    if let Err(err) = cache.insert(day, part, input, solution) {
+                        eprintln!("failed to cache day {} part {}: {}", day, part, err);
+                    }
+                    let label = format!("day {:02} part {}", day, part);
+                    println!("{}", output::text(&label, &solution.to_string()));
+                }
+                Err(err) => {
+                    let label = format!("day {:02} part {}", day, part);
+                    let mut report = err.to_string();
+                    if options.redact_input {
+                        // panic messages may quote the input being parsed
+                        if err.severity == types::Severity::Panic {
+                            report = format!("{}: message redacted", err.severity);
+                        }
+                        report = format!("{} [input {}]", report, short_digest(input));
+                    }
+                    println!("{}", output::text(&label, &report));
+                    if let Some(backtrace) = &err.backtrace {
+                        println!("{}", output::indented(backtrace));
+                    }
+                }
+            };
+            for explanation in explain::take() {
+                println!("    {}: {}", explanation.key, explanation.value);
+            }
+            results.push(result);
+        }
+    }
+
+    if !results.is_empty() {
+        if let Err(err) = history::record(&results) {
+            eprintln!("failed to record run history: {}", err);
+        }
+    }
+    if options.write_results {
+        if let Err(err) = history::write_results(&results) {
+            eprintln!("failed to write {}: {}", history::RESULTS_FILE, err);
+        }
+    }
+}
+
+// loads the input for a day which a command cannot do without
+fn load_input(day: usize) -> &'static str {
+    inputs::load(day).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    })
+}
+
+// the parsed input for a day which a command cannot do without
+fn parse_input<T>(day: usize, parsed: types::Result<T>) -> T {
+    parsed.unwrap_or_else(|err| {
+        eprintln!("day {}: {}", day, err);
+        process::exit(1);
+    })
+}
+
+fn enhance(n_rounds: usize) {
+    let puzzle = parse_input(20, puzzles::day_20::Day20::new(load_input(20)));
+    match puzzle.enhanced(n_rounds) {
+        Ok(lit) => println!("day 20 after {} rounds: {}", n_rounds, lit),
+        Err(err) => println!("day 20 after {} rounds: {}", n_rounds, err),
+    };
+}
+
+fn lanternfish(days: u64) {
+    let puzzle = parse_input(6, puzzles::day_6::Day6::new(load_input(6)));
+    match puzzle.count_after(days) {
+        Some(n_fish) => println!("day 6 after {} days: {}", days, n_fish),
+        None => println!("day 6 after {} days: more fish than fit into a u128", days),
+    };
+}
+
+// prints the packet tree, version sum, value and expression of each transmission pasted in, until
+// end of input
+fn bits() {
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("failed to read from stdin: {}", err);
+                process::exit(1);
+            }
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let packets = match types::bits::decode(&line) {
+            Ok(packets) => packets,
+            Err(err) => {
+                println!("error: {}", err);
+                continue;
+            }
         };
-        // part 2
-        match puzzle.part_2() {
-            Ok(solution) => println!("day {:02} part 2: {}", day, solution),
-            Err(err) => println!("day {:02} part 1: {}", day, err),
+        for packet in packets.iter() {
+            println!("{}", packet.tree());
+            println!("version sum: {}", packet.version_sum());
+            match packet.evaluate() {
+                Ok(value) => println!("value: {}", value),
+                Err(err) => println!("value: {}", err),
+            }
+            println!("expression: {}", packet);
+            // note: only shown where folding changes something
+            match types::Expression::from(packet).simplify() {
+                Ok(simplified) if simplified.to_string() != packet.to_string() => {
+                    println!("simplified: {}", simplified)
+                }
+                Ok(_) => {}
+                Err(err) => println!("simplified: {}", err),
+            }
+            println!("stats: {}", packet.stats());
+        }
+    }
+    println!();
+}
+
+fn parse_dump(day: usize) {
+    let lines = load_input(day).lines();
+    let puzzle = parse_input(
+        day,
+        puzzles::from_lines(day, lines).unwrap_or_else(|| usage()),
+    );
+    match puzzle.dump() {
+        Some(dump) => println!("{}", dump),
+        None => {
+            eprintln!("day {} parses its input within its solutions", day);
+            process::exit(1);
+        }
+    }
+}
+
+// solves random inputs seeded 0..rounds, reporting each which is invalid, fails or panics, or on
+// which alternative implementations disagree, such that it can be reproduced from its seed
+fn stress(day: usize, rounds: u64, size: Option<usize>) {
+    let generator = stress::generator(day).unwrap_or_else(|| {
+        eprintln!("day {} has no input generator", day);
+        process::exit(1);
+    });
+    let size = size.unwrap_or(generator.default_size);
+
+    let mut n_failures = 0;
+    for seed in 0..rounds {
+        let input = (generator.generate)(&mut stress::Rng::new(seed), size);
+        if let Err(err) = validate::validate(day, &input) {
+            println!("seed {}: generated an invalid input: {}", seed, err);
+            n_failures += 1;
+            continue;
+        }
+        // puzzles borrow their input for the lifetime of the program
+        let input = Box::leak(input.into_boxed_str());
+        for part in 1..=2 {
+            match panic::catch_unwind(|| aoc2021::solve(day, part, input)) {
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => {
+                    println!("seed {}: part {} failed: {}", seed, part, err);
+                    n_failures += 1;
+                }
+                Err(_) => {
+                    println!("seed {}: part {} panicked", seed, part);
+                    n_failures += 1;
+                }
+            }
+        }
+        for differential in differential::differentials(day) {
+            match panic::catch_unwind(|| differential.check(input)) {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => {
+                    println!("seed {}: {}", seed, err);
+                    n_failures += 1;
+                }
+                Err(_) => {
+                    println!("seed {}: {} panicked", seed, differential.name);
+                    n_failures += 1;
+                }
+            }
+        }
+    }
+
+    println!(
+        "day {}: {} failures in {} rounds of size {}",
+        day, n_failures, rounds, size
+    );
+    if n_failures > 0 {
+        process::exit(1);
+    }
+}
+
+fn short_digest(input: &str) -> String {
+    format!("sha256:{}", &utils::input_digest(input)[..16])
+}
+
+// parses the input at the given path and solves both parts, describing the runtime of each step,
+// or describes why the input cannot be solved
+fn solve_input(day: usize, path: &str) -> Vec<String> {
+    let input = match fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(err) => return vec![format!("failed to read {}: {}", path, err); 3],
+    };
+    // validate first, since puzzles panic on inputs of the wrong shape
+    if let Err(err) = validate::validate(day, &input) {
+        return vec![err; 3];
+    }
+    let start = Instant::now();
+    let puzzle = match puzzles::from_lines(day, input.lines()).unwrap() {
+        Ok(puzzle) => puzzle,
+        Err(err) => return vec![err.to_string(); 3],
+    };
+    let mut cells = vec![format!(
+        "parsed ({:.3}ms)",
+        start.elapsed().as_secs_f64() * 1000.0
+    )];
+
+    cells.extend((1..=2).map(|part| {
+        let result = types::RunResult::new(day, part, puzzle.as_ref());
+        let answer = match result.answer {
+            Ok(solution) => solution.to_string(),
+            Err(err) => err.to_string(),
         };
+        format!(
+            "{} ({:.3}ms)",
+            answer,
+            result.duration.as_secs_f64() * 1000.0
+        )
+    }));
+    cells
+}
+
+fn compare_inputs(day: usize, paths: &[String]) {
+    if !puzzles::is_known(day) {
+        usage();
+    }
+    let columns = paths
+        .iter()
+        .map(|path| {
+            let mut column = vec![path.clone()];
+            column.extend(solve_input(day, path));
+            column
+        })
+        .collect::<Vec<_>>();
+
+    let width = columns
+        .iter()
+        .flatten()
+        .map(|cell| cell.len())
+        .max()
+        .unwrap_or(0);
+    let labels = [
+        format!("day {:02}", day),
+        "parse".into(),
+        "part 1".into(),
+        "part 2".into(),
+    ];
+    for (row, label) in labels.iter().enumerate() {
+        let cells = columns
+            .iter()
+            .map(|column| format!("{:<width$}", column[row], width = width))
+            .collect::<Vec<_>>();
+        println!("{:<8}  {}", label, cells.join("  ").trim_end());
+    }
+}
+
+fn hash_input(day: usize) {
+    if !puzzles::is_known(day) {
+        usage();
+    }
+    let input = load_input(day);
+    println!("day {:02}: sha256:{}", day, utils::input_digest(input));
+}
+
+fn encrypt_inputs() {
+    let key = inputs::key_from_env().unwrap_or_else(|err| {
+        eprintln!("{} (generate one with `openssl rand -hex 32`)", err);
+        process::exit(1);
+    });
+    for day in puzzles::days() {
+        let path = inputs::path(day);
+        // note: days without a plaintext input are skipped, leaving any existing encrypted copy
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                eprintln!("failed to read {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        };
+        let encrypted_path = inputs::encrypted_path(day);
+        let result = inputs::encrypt(&input, &key)
+            .and_then(|data| fs::write(&encrypted_path, data).map_err(|err| err.into()));
+        match result {
+            Ok(()) => println!("day {:02}: wrote {}", day, encrypted_path.display()),
+            Err(err) => {
+                eprintln!("failed to write {}: {}", encrypted_path.display(), err);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn validate(which_puzzle: Day, options: &Options) {
+    let days = match which_puzzle {
+        Day::Which(n) if puzzles::is_known(n) => vec![n],
+        Day::Which(_) => usage(),
+        Day::All => puzzles::days(),
+    };
+
+    let mut n_invalid = 0;
+    for day in days {
+        let path = inputs::path(day);
+        let result = match fs::read_to_string(&path) {
+            Ok(input) if options.redact_input => validate::validate_redacted(day, &input),
+            Ok(input) => validate::validate(day, &input),
+            Err(err) => Err(format!("day {} input {}: {}", day, path.display(), err)),
+        };
+        match result {
+            Ok(()) => println!("day {:02}: ok", day),
+            Err(err) => {
+                println!("{}", err);
+                n_invalid += 1;
+            }
+        }
+    }
+    if n_invalid > 0 {
+        process::exit(1);
+    }
+}
+
+fn visualize_heatmap(day: usize, input: &'static str, options: &Options) {
+    let frame = viz::heatmap(day, input).unwrap_or_else(|| {
+        eprintln!("day {} has no heatmap", day);
+        process::exit(1);
+    });
+    let frame = parse_input(day, frame);
+    match &options.svg_dir {
+        Some(dir) => {
+            let path = Path::new(dir).join(format!("day{:02}-heatmap.svg", day));
+            if let Err(err) = fs::write(&path, viz::render_heatmap_svg(&frame)) {
+                eprintln!("failed to write {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        }
+        None => println!("day {:02} heatmap:\n{}", day, viz::render_ascii(&frame)),
+    }
+}
+
+fn visualize(day: usize, options: &Options) {
+    if !puzzles::is_known(day) {
+        usage();
+    }
+    let input = load_input(day);
+    if options.heatmap {
+        return visualize_heatmap(day, input, options);
+    }
+    // note: days drawn as shapes have a single svg rather than frames
+    if let Some(svg) = viz::drawing(day, input) {
+        let svg = parse_input(day, svg);
+        let dir = options.svg_dir.as_deref().unwrap_or_else(|| {
+            eprintln!("day {} is only drawn as svg, with --svg DIR", day);
+            process::exit(1);
+        });
+        let path = Path::new(dir).join(format!("day{:02}.svg", day));
+        if let Err(err) = fs::write(&path, svg) {
+            eprintln!("failed to write {}: {}", path.display(), err);
+            process::exit(1);
+        }
+        return;
+    }
+    let frames = match viz::frames(day, input) {
+        Some(frames) => parse_input(day, frames),
+        None => {
+            eprintln!("day {} has no visualization", day);
+            process::exit(1);
+        }
+    };
+
+    if let Some(path) = &options.gif_path {
+        let result = fs::File::create(path)
+            .map_err(|err| err.into())
+            .and_then(|file| viz::render_gif(&frames, options.delay_ms, io::BufWriter::new(file)));
+        if let Err(err) = result {
+            eprintln!("failed to write {}: {}", path, err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    for (i, frame) in frames.iter().enumerate() {
+        match &options.svg_dir {
+            Some(dir) => {
+                let path = Path::new(dir).join(format!("day{:02}-{:03}.svg", day, i));
+                if let Err(err) = fs::write(&path, viz::render_svg(frame)) {
+                    eprintln!("failed to write {}: {}", path.display(), err);
+                    process::exit(1);
+                }
+            }
+            None => println!(
+                "day {:02} frame {}:\n{}\n",
+                day,
+                i,
+                viz::render_ascii(frame)
+            ),
+        }
+    }
+}
+
+fn main() {
+    let (command, options) = parse_args();
+    if options.explain {
+        explain::enable();
+    }
+    if options.verbose {
+        // note: this hook reports only where the panic was too, so also serves to redact input
+        types::capture_panic_backtraces();
+    } else if options.redact_input {
+        // panic messages may quote the input being parsed, so only report where the panic was
+        panic::set_hook(Box::new(|info| match info.location() {
+            Some(location) => eprintln!("panicked at {} (input redacted)", location),
+            None => eprintln!("panicked (input redacted)"),
+        }));
+    }
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("failed to build the worker pool");
+    }
+    // the trace file is written out when the guard is dropped
+    let _guard = if options.trace {
+        let (layer, guard) = ChromeLayerBuilder::new().include_args(true).build();
+        tracing_subscriber::registry().with(layer).init();
+        Some(guard)
+    } else {
+        None
+    };
+
+    match command {
+        Command::Run(which_puzzle) => run(which_puzzle, &options),
+        Command::Enhance(n_rounds) => enhance(n_rounds),
+        Command::Lanternfish(days) => lanternfish(days),
+        Command::Bits => bits(),
+        Command::Serve(port) => server::serve(port),
+        Command::Leaderboard(id) => leaderboard::leaderboard(&id),
+        Command::Stats(json) => stats::stats(json),
+        Command::Validate(which_puzzle) => validate(which_puzzle, &options),
+        Command::VerifyAll => verify::verify_all(),
+        Command::Stress(day, rounds, size) => stress(day, rounds, size),
+        Command::Info(day) => info::info(day),
+        Command::Notes(day) => notes::notes(day),
+        Command::ParseDump(day) => parse_dump(day),
+        Command::Summary => summary::summary(),
+        Command::HashInput(day) => hash_input(day),
+        Command::EncryptInputs => encrypt_inputs(),
+        Command::CompareInputs(day, a, b) => compare_inputs(day, &[a, b]),
+        Command::Visualize(day) => visualize(day, &options),
+        Command::Bench(day, runs) => bench::bench(
+            day,
+            runs,
+            options.implementation.as_deref(),
+            options.histogram,
+        ),
+        Command::Compare(baseline) => history::compare(baseline.as_deref(), options.threshold),
     }
 }
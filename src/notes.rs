@@ -0,0 +1,55 @@
+/*
+** src/notes.rs
+*/
+
+// notes on a day's solution are kept in notes/day_N.md, next to the code that they describe
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+const NOTES_DIR: &str = "notes";
+
+fn path(day: usize) -> PathBuf {
+    PathBuf::from(NOTES_DIR).join(format!("day_{}.md", day))
+}
+
+pub fn get(day: usize) -> Option<String> {
+    fs::read_to_string(path(day)).ok()
+}
+
+// the first line of the notes, without any markdown heading marker, summarizes them
+pub fn first_line(notes: &str) -> Option<&str> {
+    notes
+        .lines()
+        .map(|line| line.trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+}
+
+pub fn notes(day: usize) {
+    match get(day) {
+        Some(notes) => print!("{}", notes),
+        None => {
+            eprintln!(
+                "day {} has no notes, add them to {}",
+                day,
+                path(day).display()
+            );
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_line() {
+        assert_eq!(
+            first_line("\n# signed cuboids\n\nsome detail\n"),
+            Some("signed cuboids")
+        );
+        assert_eq!(first_line("\n\n"), None);
+    }
+}
@@ -0,0 +1,61 @@
+/*
+** src/output.rs
+*/
+
+// formatting of answers for display, where answers spanning several lines (such as letters drawn
+// in a grid) are set apart from the label which introduces them rather than run on from it
+
+// the prefix of each line of a multi-line answer, which keeps it clear of the surrounding output
+const GUTTER: &str = "    | ";
+
+pub fn is_multiline(answer: &str) -> bool {
+    answer.trim_end_matches('\n').contains('\n')
+}
+
+// each line of the answer behind a gutter
+pub fn indented(answer: &str) -> String {
+    answer
+        .trim_end_matches('\n')
+        .lines()
+        .map(|line| format!("{}{}", GUTTER, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// "label: answer" for a single line, otherwise the label and the number of lines followed by the
+// answer, indented
+pub fn text(label: &str, answer: &str) -> String {
+    if !is_multiline(answer) {
+        return format!("{}: {}", label, answer);
+    }
+    let n_lines = answer.trim_end_matches('\n').lines().count();
+    format!("{}: ({} lines)\n{}", label, n_lines, indented(answer))
+}
+
+// a markdown table cell, where a multi-line answer keeps its lines as a code block broken by
+// <br> tags, since a cell cannot span lines of the table itself
+pub fn markdown_cell(answer: &str) -> String {
+    // note: cells cannot contain the column separator
+    let answer = answer.replace('|', "\\|");
+    if !is_multiline(&answer) {
+        return answer;
+    }
+    let lines = answer.trim_end_matches('\n').lines().collect::<Vec<_>>();
+    format!("<code>{}</code>", lines.join("<br>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_answers() {
+        assert_eq!(text("day 01 part 1", "1581"), "day 01 part 1: 1581");
+        assert_eq!(
+            text("day 13 part 2", "#..#\n####\n"),
+            "day 13 part 2: (2 lines)\n    | #..#\n    | ####"
+        );
+        assert_eq!(markdown_cell("a|b"), "a\\|b");
+        assert_eq!(markdown_cell("#..#\n####"), "<code>#..#<br>####</code>");
+    }
+}
@@ -3,17 +3,29 @@
 ** https://adventofcode.com/2021/day/1
 */
 
-use crate::types::{Puzzle, Result, Solution};
-use crate::utils::{self, PairWith};
+use crate::types::{FromLines, Puzzle, Result, Solution};
+use crate::utils::PairWith;
 
 pub struct Day1 {
     sonar_depths: Vec<u64>,
 }
 
 impl Day1 {
-    pub fn new(input: &'static str) -> Self {
-        let sonar_depths = utils::input_to_parsed_lines::<u64>(input).collect();
-        Self { sonar_depths }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+}
+
+impl FromLines for Day1 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let sonar_depths = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| line.parse())
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { sonar_depths })
     }
 }
 
@@ -3,17 +3,56 @@
 ** https://adventofcode.com/2021/day/10
 */
 
-use crate::types::{Puzzle, Result, Solution};
-use crate::utils;
+use crate::explain;
+use crate::types::{FromLines, Puzzle, Result, Solution};
+
+use std::fmt;
+
+// the outcome of checking the chunks of a line
+#[derive(Debug, PartialEq)]
+pub enum Diagnosis {
+    // the first illegal character, at its 1-based column, alongside the closer which was
+    // expected in its place, if any chunk was open
+    Corrupted {
+        column: usize,
+        found: char,
+        expected: Option<char>,
+    },
+    // the closers which complete the line, if any chunks are left open
+    Incomplete(String),
+    Complete,
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Corrupted {
+                column,
+                found,
+                expected: Some(expected),
+            } => write!(
+                f,
+                "column {}: expected {}, found {}",
+                column, expected, found
+            ),
+            Self::Corrupted {
+                column,
+                found,
+                expected: None,
+            } => write!(f, "column {}: unexpected {}", column, found),
+            Self::Incomplete(completion) => write!(f, "complete with {}", completion),
+            Self::Complete => write!(f, "complete"),
+        }
+    }
+}
 
 pub struct Day10 {
-    lines: Vec<&'static str>,
+    lines: Vec<String>,
 }
 
 impl Day10 {
-    pub fn new(input: &'static str) -> Self {
-        let lines = utils::input_to_lines(input).collect();
-        Self { lines }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
     fn is_opener(c: char) -> bool {
@@ -64,44 +103,61 @@ impl Day10 {
         }
     }
 
-    fn first_illegal_character(line: &str) -> Option<char> {
+    // checks the chunks of the line, finding either the first illegal character or the
+    // closers which complete it
+    pub fn diagnose(line: &str) -> Diagnosis {
         let mut stack = Vec::new();
 
-        for c in line.chars() {
+        for (i, c) in line.chars().enumerate() {
             if Self::is_opener(c) {
                 stack.push(c);
             } else if Self::is_closer(c) {
                 // ensure that the top of the stack matches
-                let top = stack.pop().unwrap();
-                if !Self::opener_matches_closer(top, c) {
-                    return Some(c);
+                match stack.pop() {
+                    Some(top) if Self::opener_matches_closer(top, c) => {}
+                    top => {
+                        return Diagnosis::Corrupted {
+                            column: i + 1,
+                            found: c,
+                            expected: top.map(Self::get_closer),
+                        }
+                    }
                 }
             }
         }
 
-        None
+        // match un-closed openers to complete the line
+        match stack.len() {
+            0 => Diagnosis::Complete,
+            _ => Diagnosis::Incomplete(stack.into_iter().rev().map(Self::get_closer).collect()),
+        }
     }
 
-    fn complete_with_score(line: &str) -> u64 {
-        let mut score = 0;
-        let mut stack = Vec::new();
-
-        for c in line.chars() {
-            if Self::is_opener(c) {
-                stack.push(c);
-            } else if Self::is_closer(c) {
-                let _ = stack.pop().unwrap();
-            }
-        }
+    // the diagnosis of each line, alongside its 1-based line number
+    pub fn diagnostics(&self) -> impl Iterator<Item = (usize, Diagnosis)> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| (i + 1, Self::diagnose(line)))
+    }
 
-        // match un-closed openers to complete the line
-        while !stack.is_empty() {
-            let opener = stack.pop().unwrap();
-            let closer = Self::get_closer(opener);
-            score = (score * 5) + Self::score(closer);
-        }
+    fn completion_score(completion: &str) -> u64 {
+        completion
+            .chars()
+            .fold(0, |score, closer| (score * 5) + Self::score(closer))
+    }
+}
 
-        score
+impl FromLines for Day10 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let lines = lines
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        Ok(Self { lines })
     }
 }
 
@@ -109,27 +165,79 @@ impl Puzzle for Day10 {
     // Find the first illegal character in each corrupted line of the navigation subsystem. What is
     // the total syntax error score for those errors?
     fn part_1(&self) -> Result<Solution> {
-        let syntax_err_score = self
-            .lines
-            .iter()
-            .map(|line| Self::first_illegal_character(line))
-            .flatten()
-            .map(Self::syntax_error_score)
-            .sum::<u64>();
+        let mut syntax_err_score = 0;
+        for (line, diagnosis) in self.diagnostics() {
+            if let Diagnosis::Corrupted { found, .. } = diagnosis {
+                explain::report("corrupted", format!("line {}, {}", line, diagnosis));
+                syntax_err_score += Self::syntax_error_score(found);
+            }
+        }
         Ok(syntax_err_score.into())
     }
 
     // Find the completion string for each incomplete line, score the completion strings, and sort
     // the scores. What is the middle score?
     fn part_2(&self) -> Result<Solution> {
-        let mut completion_scores = self
-            .lines
-            .iter()
-            .filter(|line| Self::first_illegal_character(line).is_none())
-            .map(|line| Self::complete_with_score(line))
-            .collect::<Vec<_>>();
+        let mut completion_scores = vec![];
+        for (line, diagnosis) in self.diagnostics() {
+            if let Diagnosis::Incomplete(completion) = &diagnosis {
+                explain::report("incomplete", format!("line {}, {}", line, diagnosis));
+                completion_scores.push(Self::completion_score(completion));
+            }
+        }
         completion_scores.sort_unstable();
         let score = completion_scores[completion_scores.len() / 2];
         Ok(score.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]";
+
+    #[test]
+    fn test_example() {
+        let day = Day10::new(TEST_INPUT).unwrap();
+        assert_eq!(day.part_1().unwrap().to_string(), "26397");
+        assert_eq!(day.part_2().unwrap().to_string(), "288957");
+    }
+
+    #[test]
+    fn test_diagnose() {
+        assert_eq!(
+            Day10::diagnose("{([(<{}[<>[]}>{[]{[(<()>"),
+            Diagnosis::Corrupted {
+                column: 13,
+                found: '}',
+                expected: Some(']'),
+            }
+        );
+        assert_eq!(
+            Day10::diagnose("[({(<(())[]>[[{[]{<()<>>"),
+            Diagnosis::Incomplete(String::from("}}]])})]"))
+        );
+        assert_eq!(Day10::completion_score("}}]])})]"), 288957);
+        assert_eq!(Day10::diagnose("<>"), Diagnosis::Complete);
+        assert_eq!(Day10::diagnose("<>)").to_string(), "column 3: unexpected )");
+
+        let day = Day10::new(TEST_INPUT).unwrap();
+        let corrupted = day
+            .diagnostics()
+            .filter(|(_, diagnosis)| matches!(diagnosis, Diagnosis::Corrupted { .. }))
+            .map(|(line, diagnosis)| format!("line {}, {}", line, diagnosis))
+            .collect::<Vec<_>>();
+        assert_eq!(corrupted[0], "line 3, column 13: expected ], found }");
+        assert_eq!(corrupted.len(), 5);
+    }
+}
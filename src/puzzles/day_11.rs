@@ -3,69 +3,153 @@
 ** https://adventofcode.com/2021/day/11
 */
 
-use crate::types::{Array2D, Puzzle, PuzzleError, Result, Solution};
+use crate::types::{Array2D, Coord, FromLines, Puzzle, Result, Simulation, Solution};
+use crate::viz::{Frame, Visualize};
 
-use std::cell::RefCell;
+use std::iter;
 
 const SIZE: usize = 10;
 
-pub struct Day11 {
-    input: &'static str,
-    // need RefCell for interior mutability
-    energy_levels: RefCell<Array2D<u8, SIZE, SIZE>>,
+struct Cavern {
+    energy_levels: Array2D<u8, SIZE, SIZE>,
+    // the number of flashes in the most recent step
+    flashes: u64,
+    total_flashes: u64,
 }
 
-impl Day11 {
-    fn load_energy_levels(s: &'static str) -> Array2D<u8, SIZE, SIZE> {
-        Array2D::from(s)
-    }
-
-    pub fn new(input: &'static str) -> Self {
-        let energy_levels = RefCell::new(Self::load_energy_levels(input));
+impl Cavern {
+    fn new(energy_levels: Array2D<u8, SIZE, SIZE>) -> Self {
         Self {
-            input,
             energy_levels,
+            flashes: 0,
+            total_flashes: 0,
         }
     }
 
-    // returns the number of flashes in the step
-    fn run_step(&self) -> u64 {
+    // did every octopus flash in the most recent step?
+    fn synchronized(&self) -> bool {
+        self.flashes == (SIZE * SIZE) as u64
+    }
+
+    // flashing octopi are drawn at full brightness
+    fn frame(&self) -> Frame {
+        Frame::from_fn(SIZE, SIZE, |i, j| {
+            match self.energy_levels.get(Coord::new(i, j)) {
+                0 => 255,
+                level => level * 16,
+            }
+        })
+    }
+}
+
+impl Simulation for Cavern {
+    fn step(&mut self) {
+        let grid = &mut self.energy_levels;
         let mut flashes = 0;
-        // copy out the energy level grid and replace it at the end to avoid borrowing concerns
-        let mut grid = self.energy_levels.take();
 
         // first increment all energy levels by 1
-        for row in 0..SIZE {
-            for col in 0..SIZE {
-                grid.increment(row, col);
-            }
+        for level in grid.iter_mut() {
+            *level += 1;
         }
 
-        // handle all flashes
-        while let Some((i, j)) = grid.find_index(|&x| x > 9) {
+        // octopi flash as soon as their energy level exceeds 9, at which point it is set to 0
+        // note: an octopus can only flash once per step, so those at 0 are not incremented
+        let mut flashing = vec![];
+        grid.for_each_mut(
+            |&x| x > 9,
+            |coord, level| {
+                *level = 0;
+                flashing.push(coord);
+            },
+        );
+
+        // handle all flashes, which can cascade into further flashes
+        while let Some(coord) = flashing.pop() {
             flashes += 1;
-            // set the energy level to 0
-            grid.set(i, j, 0);
             // increment the energy level of all neighboring octopi
-            for (ii, jj) in Array2D::<u8, SIZE, SIZE>::neighbors_with_diagonal(i, j)
-                .iter()
-                .flatten()
-            {
-                // note: do not increment if 0
-                if grid.get(*ii, *jj) != 0 {
-                    grid.increment(*ii, *jj);
+            for neighbor in Array2D::<u8, SIZE, SIZE>::neighbors_with_diagonal(coord) {
+                match grid.get(neighbor) {
+                    0 => {}
+                    9 => {
+                        grid.set(neighbor, 0);
+                        flashing.push(neighbor);
+                    }
+                    _ => grid.increment(neighbor),
                 }
             }
         }
 
-        // replace the grid and return
-        let _ = self.energy_levels.replace(grid);
-        flashes
+        self.flashes = flashes;
+        self.total_flashes += flashes;
     }
+}
 
-    // returns the sum of the number of flashes in each step
-    fn run_steps(&self, n: usize) -> u64 {
-        (0..n).map(|_| self.run_step()).sum()
+pub struct Day11 {
+    energy_levels: Array2D<u8, SIZE, SIZE>,
+}
+
+impl Day11 {
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+
+    fn cavern(&self) -> Cavern {
+        Cavern::new(self.energy_levels.clone())
+    }
+
+    // the number of flashes during each step, alongside the total number of flashes up to and
+    // including that step, without end
+    pub fn steps(&self) -> impl Iterator<Item = (u64, u64)> {
+        let mut cavern = self.cavern();
+        iter::from_fn(move || {
+            cavern.step();
+            Some((cavern.flashes, cavern.total_flashes))
+        })
+    }
+
+    // the total number of flashes after the given number of steps
+    pub fn total_flashes(&self, steps: usize) -> u64 {
+        match steps {
+            0 => 0,
+            _ => self.steps().nth(steps - 1).map_or(0, |(_, total)| total),
+        }
+    }
+
+    // the first n steps during which all octopuses flash, 1-indexed
+    // note: never returns if the octopuses never synchronize
+    pub fn synchronization_steps(&self, n: usize) -> Vec<usize> {
+        self.steps()
+            .enumerate()
+            .filter(|&(_, (flashes, _))| flashes == (SIZE * SIZE) as u64)
+            .map(|(i, _)| i + 1)
+            .take(n)
+            .collect()
+    }
+}
+
+impl Visualize for Day11 {
+    // the energy levels after each step, up to and including the first synchronized flash
+    fn frames(&self) -> impl Iterator<Item = Frame> {
+        let mut cavern = self.cavern();
+        let mut synchronized = false;
+        iter::once(cavern.frame()).chain(iter::from_fn(move || {
+            if synchronized {
+                return None;
+            }
+            cavern.step();
+            synchronized = cavern.synchronized();
+            Some(cavern.frame())
+        }))
+    }
+}
+
+impl FromLines for Day11 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let energy_levels = Array2D::parse_digits(&lines.collect::<Vec<_>>().join("\n"))?;
+        Ok(Self { energy_levels })
     }
 }
 
@@ -73,26 +157,13 @@ impl Puzzle for Day11 {
     // Given the starting energy levels of the dumbo octopuses in your cavern, simulate 100 steps.
     // How many total flashes are there after 100 steps?
     fn part_1(&self) -> Result<Solution> {
-        Ok(self.run_steps(100).into())
+        Ok(self.total_flashes(100).into())
     }
 
     // What is the first step during which all octopuses flash?
     fn part_2(&self) -> Result<Solution> {
-        // first reset the grid
-        let _ = self
-            .energy_levels
-            .replace(Self::load_energy_levels(self.input));
-
-        let all_flash = (SIZE * SIZE) as u64;
-        for step in 0..u64::MAX {
-            let n = self.run_step();
-            if n == all_flash {
-                // note: solution steps are 1-indexed
-                return Ok((step + 1).into());
-            }
-        }
-
-        Err(PuzzleError::NoSolution.into())
+        let step = self.synchronization_steps(1)[0];
+        Ok(step.into())
     }
 }
 
@@ -100,32 +171,53 @@ impl Puzzle for Day11 {
 mod tests {
     use super::*;
 
-    const TEST_INPUT: &'static str = "5483143223\n2745854711\n5264556173\n6141336146\n6357385478\n4167524645\n2176841721\n6882881134\n4846848554\n5283751526";
-
-    fn get_day() -> Day11 {
-        let energy_levels = RefCell::new(Day11::load_energy_levels(TEST_INPUT));
-        Day11 {
-            input: TEST_INPUT,
-            energy_levels,
-        }
-    }
+    const TEST_INPUT: &str = "5483143223\n2745854711\n5264556173\n6141336146\n6357385478\n4167524645\n2176841721\n6882881134\n4846848554\n5283751526";
 
     #[test]
     fn test_flashes() {
-        let day = get_day();
-        // print_grid(&day);
-        assert_eq!(day.run_step(), 0);
-        // print_grid(&day);
-        assert_eq!(day.run_step(), 35);
-        // print_grid(&day);
+        let mut cavern = Day11::new(TEST_INPUT).unwrap().cavern();
+        cavern.step();
+        assert_eq!(cavern.flashes, 0);
+        cavern.step();
+        assert_eq!(cavern.flashes, 35);
+        cavern.run(98);
+        assert_eq!(cavern.total_flashes, 1656);
     }
 
     #[test]
     fn test_flashes_synchronized() {
-        let day = get_day();
+        let mut cavern = Day11::new(TEST_INPUT).unwrap().cavern();
         // should synchronize on step 195
-        let _ = day.run_steps(194);
-        assert_eq!(day.run_step(), (SIZE * SIZE) as u64);
-        // print_grid(&day);
+        cavern.run(194);
+        assert!(!cavern.synchronized());
+        cavern.step();
+        assert!(cavern.synchronized());
+        assert_eq!(
+            Day11::new(TEST_INPUT)
+                .unwrap()
+                .part_2()
+                .unwrap()
+                .to_string(),
+            "195"
+        );
+    }
+
+    #[test]
+    fn test_synchronization_steps() {
+        let day = Day11::new(TEST_INPUT).unwrap();
+        assert_eq!(day.total_flashes(0), 0);
+        assert_eq!(day.total_flashes(10), 204);
+        assert_eq!(day.total_flashes(100), 1656);
+        // once synchronized, every octopus charges in lockstep and flashes again 10 steps later
+        assert_eq!(day.synchronization_steps(3), vec![195, 205, 215]);
+    }
+
+    #[test]
+    fn test_frames() {
+        let day = Day11::new(TEST_INPUT).unwrap();
+        // the initial grid, and then each step up to the synchronized flash on step 195
+        let frames = day.frames().collect::<Vec<_>>();
+        assert_eq!(frames.len(), 196);
+        assert!(frames[195].cells.iter().all(|&cell| cell == 255));
     }
 }
@@ -3,157 +3,176 @@
 ** https://adventofcode.com/2021/day/12
 */
 
-use crate::types::{Puzzle, Result, Solution};
-use crate::utils;
+use crate::types::{FromLines, Puzzle, Result, Solution};
 
-use std::collections::{HashMap, HashSet};
+use rayon::prelude::*;
 
-pub struct Day12 {
-    cave_connections: HashMap<&'static str, Vec<&'static str>>,
-}
+use std::collections::HashMap;
 
-impl Day12 {
-    pub fn new(input: &'static str) -> Self {
-        let mut cave_connections = HashMap::new();
+// the most caves which fit into the bitmask of visited caves
+const MAX_CAVES: usize = 64;
 
-        for line in utils::input_to_lines(input) {
-            match split!(line, "-") {
-                [from, to] => {
-                    // NOTE: cave connections are bi-directional!
-                    let entry_from = cave_connections.entry(*from).or_insert_with(Vec::new);
-                    entry_from.push(*to);
-                    let entry_to = cave_connections.entry(*to).or_insert_with(Vec::new);
-                    entry_to.push(*from);
-                }
-                _ => unreachable!(),
-            }
-        }
+// interns cave names as small integer IDs, in the order in which they are first seen, such that
+// the path search compares and tracks caves as integers rather than strings
+#[derive(Default)]
+struct CaveNames {
+    ids: HashMap<String, u8>,
+    // bitmask of the IDs of small caves, classified once when each name is interned
+    small: u64,
+}
 
-        Self { cave_connections }
+impl CaveNames {
+    fn intern(&mut self, name: &str) -> Result<u8> {
+        if let Some(&id) = self.ids.get(name) {
+            return Ok(id);
+        }
+        if self.ids.len() == MAX_CAVES {
+            return Err(format!("more than {} caves", MAX_CAVES).into());
+        }
+        let id = self.ids.len() as u8;
+        self.ids.insert(name.to_string(), id);
+        if name.chars().all(char::is_lowercase) {
+            self.small |= 1 << id;
+        }
+        Ok(id)
     }
 
-    fn is_start(cave: &str) -> bool {
-        cave == "start"
+    fn get(&self, name: &str) -> Result<u8> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("no {} cave", name).into())
     }
+}
 
-    fn is_end(cave: &str) -> bool {
-        cave == "end"
-    }
+pub struct Day12 {
+    // the caves connected to each cave, indexed by cave ID
+    cave_connections: Vec<Vec<u8>>,
+    small_caves: u64,
+    start: u8,
+    end: u8,
+}
 
-    fn is_small_cave(cave: &str) -> bool {
-        cave.chars().all(char::is_lowercase)
+impl Day12 {
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn find_paths_small_caves_once_rec(
-        &self,
-        from: &'static str,
-        mut visited: HashSet<&str>,
-    ) -> Vec<Vec<&str>> {
-        let mut paths = vec![];
-        // add the current cave to the visited caves if it is a small cave
-        if Self::is_small_cave(from) {
-            visited.insert(from);
-        }
+    fn is_small_cave(&self, cave: u8) -> bool {
+        self.small_caves & (1 << cave) != 0
+    }
 
-        // recurse on un-visited caves
-        if let Some(connected_caves) = self.cave_connections.get(from) {
-            for cave in connected_caves.iter() {
-                if !visited.contains(cave) {
-                    // base case: end
-                    if Self::is_end(cave) {
-                        paths.push(vec![*cave, from]);
-                    } else {
-                        let paths_rec = self.find_paths_small_caves_once_rec(cave, visited.clone());
-                        // add the current cave to the paths and continue
-                        for mut path in paths_rec.into_iter() {
-                            path.push(from);
-                            paths.push(path);
-                        }
-                    }
-                }
+    // counts the paths to the end which continue on to any of the given caves, after having passed
+    // through the visited small caves, other than a single revisit if one is allowed
+    fn count_paths(&self, caves: &[u8], visited: u64, revisit: bool) -> usize {
+        let mut paths = 0;
+        for &cave in caves.iter() {
+            let connected_caves = &self.cave_connections[cave as usize];
+            // base case: end
+            // note: the start cave can never be revisited
+            if cave == self.end {
+                paths += 1;
+            } else if cave == self.start {
+                continue;
+            } else if visited & (1 << cave) == 0 {
+                let visited = if self.is_small_cave(cave) {
+                    visited | (1 << cave)
+                } else {
+                    visited
+                };
+                paths += self.count_paths(connected_caves, visited, revisit);
+            } else if revisit {
+                // the small cave revisit adds a second branching point: skip the cave, as above,
+                // or visit it a second time and allow no further revisits
+                paths += self.count_paths(connected_caves, visited, false);
             }
         }
-
         paths
     }
 
-    fn find_paths_small_caves_once(&self) -> Vec<Vec<&str>> {
-        let visited = HashSet::new();
-        self.find_paths_small_caves_once_rec("start", visited)
+    // splits the search by the caves connected to the start, counting each branch in parallel
+    fn count_paths_from_start(&self, revisit: bool) -> usize {
+        let visited = 1 << self.start;
+        self.cave_connections[self.start as usize]
+            .par_chunks(1)
+            .map(|caves| self.count_paths(caves, visited, revisit))
+            .sum()
     }
 
-    fn find_paths_small_caves_once_or_twice_rec(
-        &self,
-        from: &'static str,
-        mut visited: HashSet<&str>,
-        twice_visited: bool,
-    ) -> Vec<Vec<&str>> {
-        let mut paths = vec![];
-        // add the current cave to the visited caves if it is a small cave
-        if Self::is_small_cave(from) {
-            visited.insert(from);
-        }
+    fn count_paths_small_caves_once(&self) -> usize {
+        self.count_paths_from_start(false)
+    }
 
-        // recurse on un-visited caves
-        if let Some(connected_caves) = self.cave_connections.get(from) {
-            for cave in connected_caves.iter() {
-                // the small cave revisit adds the option for a second branching point
-                // if we have already visited a small cave but have not visited any small cave
-                // twice, we can (a) skip the cave or (b) continuing on with the cave
-                // note: not true for the start cave
-                if visited.contains(cave) && !twice_visited && !Self::is_start(cave) {
-                    // base case: end
-                    if Self::is_end(cave) {
-                        paths.push(vec![*cave, from]);
-                    } else {
-                        let paths_rec = self.find_paths_small_caves_once_or_twice_rec(
-                            cave,
-                            visited.clone(),
-                            true,
-                        );
-                        // add the current cave to the paths and continue
-                        for mut path in paths_rec.into_iter() {
-                            path.push(from);
-                            paths.push(path);
-                        }
-                    }
-                } else if !visited.contains(cave) {
-                    // base case: end
-                    if Self::is_end(cave) {
-                        paths.push(vec![*cave, from]);
-                    } else {
-                        let paths_rec = self.find_paths_small_caves_once_or_twice_rec(
-                            cave,
-                            visited.clone(),
-                            twice_visited,
-                        );
-                        // add the current cave to the paths and continue
-                        for mut path in paths_rec.into_iter() {
-                            path.push(from);
-                            paths.push(path);
-                        }
+    fn count_paths_small_caves_once_or_twice(&self) -> usize {
+        self.count_paths_from_start(true)
+    }
+}
+
+impl FromLines for Day12 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut names = CaveNames::default();
+        let mut cave_connections: Vec<Vec<u8>> = vec![];
+
+        for line in lines.filter(|line| !line.is_empty()) {
+            match split!(line, "-") {
+                [from, to] => {
+                    let from = names.intern(from)?;
+                    let to = names.intern(to)?;
+                    let n_caves = from.max(to) as usize + 1;
+                    if cave_connections.len() < n_caves {
+                        cave_connections.resize_with(n_caves, Vec::new);
                     }
+                    // NOTE: cave connections are bi-directional!
+                    cave_connections[from as usize].push(to);
+                    cave_connections[to as usize].push(from);
                 }
+                _ => return Err(format!("invalid cave connection {:?}", line).into()),
             }
         }
-        paths
-    }
 
-    fn find_paths_small_caves_once_or_twice(&self) -> Vec<Vec<&str>> {
-        let visited = HashSet::new();
-        self.find_paths_small_caves_once_or_twice_rec("start", visited, false)
+        Ok(Self {
+            cave_connections,
+            small_caves: names.small,
+            start: names.get("start")?,
+            end: names.get("end")?,
+        })
     }
 }
 
 impl Puzzle for Day12 {
     // How many paths through this cave system are there that visit small caves at most once?
     fn part_1(&self) -> Result<Solution> {
-        Ok(self.find_paths_small_caves_once().len().into())
+        Ok(self.count_paths_small_caves_once().into())
     }
 
     // After reviewing the available paths, you realize you might have time to visit a single small
     // cave twice. Given these new rules, how many paths through this cave system are there?
     fn part_2(&self) -> Result<Solution> {
-        Ok(self.find_paths_small_caves_once_or_twice().len().into())
+        Ok(self.count_paths_small_caves_once_or_twice().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let day = Day12::new("start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end\n").unwrap();
+        assert_eq!(day.part_1().unwrap().to_string(), "10");
+        assert_eq!(day.part_2().unwrap().to_string(), "36");
+    }
+
+    #[test]
+    fn test_cave_names() {
+        let mut names = CaveNames::default();
+        assert_eq!(names.intern("start").unwrap(), 0);
+        assert_eq!(names.intern("HN").unwrap(), 1);
+        assert_eq!(names.intern("start").unwrap(), 0);
+        assert_eq!(names.small, 0b01);
+        assert!(names.get("end").is_err());
     }
 }
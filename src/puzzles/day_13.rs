@@ -3,10 +3,14 @@
 ** https://adventofcode.com/2021/day/13
 */
 
-use crate::types::{Point, Puzzle, Result, Solution};
+use crate::explain;
+use crate::types::{ocr, Context, FromLines, Point, Puzzle, Result, Solution};
+use crate::utils;
+use crate::viz::{Frame, Visualize};
 
 use std::cell::RefCell;
 use std::collections::HashSet;
+use std::iter;
 
 #[derive(Debug)]
 enum Fold {
@@ -23,14 +27,16 @@ impl Fold {
     }
 }
 
-impl From<&str> for Fold {
-    fn from(s: &str) -> Self {
-        let line = s.split(' ').last().unwrap();
-        split_into!(line, '=', axis, point);
-        match axis {
-            "x" => Fold::X(point.parse().unwrap()),
-            "y" => Fold::Y(point.parse().unwrap()),
-            _ => unreachable!(),
+impl Fold {
+    fn parse(s: &str) -> Result<Self> {
+        // format: fold along axis=n
+        let fold = s
+            .strip_prefix("fold along ")
+            .and_then(|s| s.split_once('='));
+        match fold {
+            Some(("x", x)) => Ok(Fold::X(x.parse().context("fold position")?)),
+            Some(("y", y)) => Ok(Fold::Y(y.parse().context("fold position")?)),
+            _ => Err(format!("invalid fold {:?}", s).into()),
         }
     }
 }
@@ -42,15 +48,8 @@ pub struct Day13 {
 }
 
 impl Day13 {
-    pub fn new(input: &'static str) -> Self {
-        match split!(input, "\n\n") {
-            [point_strings, fold_strings] => {
-                let points = RefCell::new(point_strings.split('\n').map(Point::from).collect());
-                let folds = fold_strings.split('\n').map(Fold::from).collect();
-                Self { points, folds }
-            }
-            _ => unreachable!(),
-        }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
     fn point_eligible_for_fold(point: &Point, fold: &Fold) -> bool {
@@ -60,10 +59,10 @@ impl Day13 {
         }
     }
 
-    fn perform_fold(&self, fold: &Fold) {
+    fn fold_points(points: &HashSet<Point>, fold: &Fold) -> HashSet<Point> {
         let mut new_points = HashSet::new();
 
-        for point in self.points.borrow().iter() {
+        for point in points.iter() {
             new_points.insert(if Self::point_eligible_for_fold(point, fold) {
                 fold.reflect_point(point)
             } else {
@@ -71,9 +70,30 @@ impl Day13 {
             });
         }
 
+        new_points
+    }
+
+    fn perform_fold(&self, fold: &Fold) {
+        let new_points = Self::fold_points(&self.points.borrow(), fold);
         let _ = self.points.replace(new_points);
     }
 
+    fn frame(points: &HashSet<Point>) -> Frame {
+        let width = points.iter().map(|p| p.x + 1).max().unwrap_or(0);
+        let height = points.iter().map(|p| p.y + 1).max().unwrap_or(0);
+        let mut frame = Frame::new(
+            utils::try_cast(width).unwrap(),
+            utils::try_cast(height).unwrap(),
+        );
+        for point in points.iter() {
+            // note: folding never moves a point past the origin
+            let row = utils::try_cast(point.y).unwrap();
+            let col = utils::try_cast(point.x).unwrap();
+            frame.set(row, col, 255);
+        }
+        frame
+    }
+
     fn print_grid(&self) -> String {
         let mut grid = vec![String::new()];
         let x_max = self.points.borrow().iter().map(|p| p.x).max().unwrap();
@@ -96,6 +116,49 @@ impl Day13 {
     }
 }
 
+impl Visualize for Day13 {
+    // the paper before folding and after each fold, without disturbing the puzzle state
+    fn frames(&self) -> impl Iterator<Item = Frame> {
+        let mut points = self.points.borrow().clone();
+        let folds = self.folds.iter().map(move |fold| {
+            points = Self::fold_points(&points, fold);
+            Self::frame(&points)
+        });
+        iter::once(Self::frame(&self.points.borrow())).chain(folds)
+    }
+}
+
+impl FromLines for Day13 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        match utils::sections(lines).as_slice() {
+            [point_lines, fold_lines] => {
+                let points = point_lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| Point::parse(line).with_context(|| format!("point {}", i + 1)))
+                    .collect::<Result<_>>()?;
+                let folds = fold_lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| Fold::parse(line).with_context(|| format!("fold {}", i + 1)))
+                    .collect::<Result<_>>()?;
+                Ok(Self {
+                    points: RefCell::new(points),
+                    folds,
+                })
+            }
+            sections => Err(format!(
+                "expected points and folds, found {} sections",
+                sections.len()
+            )
+            .into()),
+        }
+    }
+}
+
 impl Puzzle for Day13 {
     // How many dots are visible after completing just the first fold instruction on your
     // transparent paper?
@@ -111,6 +174,47 @@ impl Puzzle for Day13 {
         for fold in self.folds.iter().skip(1) {
             self.perform_fold(fold);
         }
-        Ok(self.print_grid().into())
+        let grid = self.print_grid();
+        explain::report("paper", grid.replace('\n', "\n    "));
+        let points = self
+            .points
+            .borrow()
+            .iter()
+            .map(|p| {
+                // note: folding never moves a point past the origin
+                (utils::try_cast(p.x).unwrap(), utils::try_cast(p.y).unwrap())
+            })
+            .collect::<Vec<_>>();
+        // note: falls back to the paper itself if the letters cannot be read
+        match ocr::read_points(points) {
+            Ok(code) => Ok(code.into()),
+            Err(err) => {
+                explain::report("unreadable", err);
+                Ok(grid.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day13::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(
+            err("x,y\n\nzz 3"),
+            "point 1: x: invalid digit found in string"
+        );
+        assert_eq!(err("6,10\n\nzz 3"), "fold 1: invalid fold \"zz 3\"");
+        assert_eq!(
+            err("6,10\n\nfold along z=7"),
+            "fold 1: invalid fold \"fold along z=7\""
+        );
+        assert_eq!(
+            err("6,10\n\nfold along y=-"),
+            "fold 1: fold position: invalid digit found in string"
+        );
     }
 }
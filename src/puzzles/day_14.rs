@@ -3,7 +3,10 @@
 ** https://adventofcode.com/2021/day/14
 */
 
-use crate::types::{Counter, Puzzle, Result, Solution};
+use crate::types::{
+    mat_pow, Context, Counter, FromLines, Matrix, Puzzle, Result, Simulation, Solution,
+};
+use crate::utils;
 
 use std::collections::HashMap;
 
@@ -16,12 +19,12 @@ impl Pair {
     }
 }
 
-impl From<&str> for Pair {
-    fn from(s: &str) -> Self {
-        if s.len() != 2 {
-            unreachable!()
+impl Pair {
+    fn parse(s: &str) -> Result<Self> {
+        match s.chars().collect::<Vec<_>>().as_slice() {
+            &[c1, c2] => Ok(Self(c1, c2)),
+            _ => Err(format!("invalid pair {:?}", s).into()),
         }
-        Self(s.chars().next().unwrap(), s.chars().nth(1).unwrap())
     }
 }
 
@@ -37,29 +40,13 @@ impl PairCounter {
     }
 }
 
-pub struct Day14 {
-    template: &'static str,
-    rules: HashMap<Pair, char>,
+// the polymer as counts of its adjacent pairs, as only the counts of each element matter
+struct Polymer<'a> {
+    rules: &'a HashMap<Pair, char>,
+    pairs: PairCounter,
 }
 
-impl Day14 {
-    pub fn new(input: &'static str) -> Self {
-        match split!(input, "\n\n") {
-            [template, rules_str] => {
-                let rules = rules_str.split('\n').map(Self::parse_rule).collect();
-                Self { template, rules }
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    fn parse_rule(s: &str) -> (Pair, char) {
-        match split!(s, " -> ") {
-            [pair, sub] => (Pair::from(*pair), sub.chars().next().unwrap()),
-            _ => unreachable!(),
-        }
-    }
-
+impl Polymer<'_> {
     fn matches_rule(&self, pair: &Pair) -> Option<(Pair, Pair)> {
         if let Some(&c) = self.rules.get(pair) {
             let pa = Pair::new(pair.0, c);
@@ -69,11 +56,14 @@ impl Day14 {
             None
         }
     }
+}
 
-    fn apply_pair_insertion(&self, input: PairCounter) -> PairCounter {
+impl Simulation for Polymer<'_> {
+    // applies a single step of pair insertion
+    fn step(&mut self) {
         let mut output = Counter::new();
 
-        for (pair, &count) in input.iter() {
+        for (pair, &count) in self.pairs.iter() {
             if let Some((new_pair_a, new_pair_b)) = self.matches_rule(pair) {
                 output.insert_n(new_pair_a, count);
                 output.insert_n(new_pair_b, count);
@@ -82,21 +72,151 @@ impl Day14 {
             }
         }
 
-        output
+        self.pairs = output;
+    }
+}
+
+pub struct Day14 {
+    template: String,
+    rules: HashMap<Pair, char>,
+}
+
+impl Day14 {
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+
+    fn parse_rule(s: &str) -> Result<(Pair, char)> {
+        match split!(s, " -> ") {
+            [pair, sub] => match sub.chars().collect::<Vec<_>>().as_slice() {
+                &[c] => Ok((Pair::parse(pair)?, c)),
+                _ => Err(format!("invalid element {:?}", sub).into()),
+            },
+            _ => Err(format!("invalid rule {:?}", s).into()),
+        }
+    }
+
+    fn polymer(&self) -> Polymer<'_> {
+        Polymer {
+            rules: &self.rules,
+            pairs: PairCounter::parse(&self.template),
+        }
     }
 
-    fn pair_counter_to_char_counter(pair_counts: PairCounter) -> Counter<char> {
+    // counts each element as the first of its pairs, plus the last element of the template,
+    // which is never the first of a pair and never moves
+    fn pair_counter_to_char_counter(&self, pair_counts: PairCounter) -> Counter<char> {
         let mut char_counts = Counter::new();
         for (pair, &count) in pair_counts.iter() {
             char_counts.insert_n(pair.0, count);
-            char_counts.insert_n(pair.1, count);
         }
+        if let Some(last) = self.template.chars().last() {
+            char_counts.insert(last);
+        }
+        char_counts
+    }
 
-        let mut output = Counter::new();
-        for (&c, &count) in char_counts.iter() {
-            output.insert_n(c, (count + 1) / 2);
+    // the difference between the quantities of the most and least common elements after the
+    // given number of steps of pair insertion
+    pub fn element_spread(&self, steps: usize) -> u64 {
+        let mut polymer = self.polymer();
+        polymer.run(steps);
+        let counts = self.pair_counter_to_char_counter(polymer.pairs);
+        let min = counts.min().unwrap_or(0);
+        let max = counts.max().unwrap_or(0);
+        (max - min) as u64
+    }
+
+    // every element in the template and rules, in order
+    fn elements(&self) -> Vec<char> {
+        let mut elements = self
+            .template
+            .chars()
+            .chain(self.rules.iter().flat_map(|(pair, &c)| [pair.0, pair.1, c]))
+            .collect::<Vec<_>>();
+        elements.sort_unstable();
+        elements.dedup();
+        elements
+    }
+
+    // the pair insertion rules as a linear operator, mapping the count of each pair onto the
+    // counts of the pairs which it becomes after a step, where the pair of the i-th and j-th
+    // elements is at index (i * n) + j
+    fn transition_matrix(&self, elements: &[char]) -> Matrix<u128> {
+        let n = elements.len();
+        let index = |c| elements.binary_search(&c).unwrap();
+        let mut matrix = Matrix::zero(n * n);
+        for (a, &c1) in elements.iter().enumerate() {
+            for (b, &c2) in elements.iter().enumerate() {
+                let pair = (a * n) + b;
+                match self.rules.get(&Pair::new(c1, c2)) {
+                    Some(&c) => {
+                        let c = index(c);
+                        matrix.set((a * n) + c, pair, matrix.get((a * n) + c, pair) + 1);
+                        matrix.set((c * n) + b, pair, matrix.get((c * n) + b, pair) + 1);
+                    }
+                    None => matrix.set(pair, pair, 1),
+                }
+            }
+        }
+        matrix
+    }
+
+    // the element spread after an arbitrary number of steps, or None if the quantities do not
+    // fit into a u128, by raising the transition matrix to the given power rather than
+    // simulating each step
+    // note: the polymer roughly doubles in length each step, so this overflows after ~120 steps
+    pub fn element_spread_after(&self, steps: u64) -> Option<u128> {
+        let elements = self.elements();
+        let n = elements.len();
+        let index = |c| elements.binary_search(&c).unwrap();
+        let chars = self.template.chars().map(index).collect::<Vec<_>>();
+        let mut pairs = vec![0; n * n];
+        for window in chars.windows(2) {
+            pairs[(window[0] * n) + window[1]] += 1;
+        }
+
+        let pairs =
+            mat_pow(&self.transition_matrix(&elements), steps)?.checked_mul_vector(&pairs)?;
+        let mut counts = vec![0u128; n];
+        for (pair, count) in pairs.into_iter().enumerate() {
+            counts[pair / n] = counts[pair / n].checked_add(count)?;
+        }
+        if let Some(&last) = chars.last() {
+            counts[last] += 1;
+        }
+        // note: elements which only appear in rules that never apply are absent from the polymer
+        let counts = counts.into_iter().filter(|&count| count > 0);
+        let (min, max) = counts.fold((u128::MAX, 0), |(min, max), count| {
+            (min.min(count), max.max(count))
+        });
+        Some(max.saturating_sub(min))
+    }
+}
+
+impl FromLines for Day14 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        match utils::sections(lines).as_slice() {
+            [template_lines, rule_lines] if template_lines.len() == 1 => {
+                let template = template_lines[0].to_string();
+                let rules = rule_lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| {
+                        Self::parse_rule(line).with_context(|| format!("rule {}", i + 1))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Self { template, rules })
+            }
+            sections => Err(format!(
+                "expected a template and insertion rules, found {} sections",
+                sections.len()
+            )
+            .into()),
         }
-        output
     }
 }
 
@@ -105,27 +225,65 @@ impl Puzzle for Day14 {
     // elements in the result. What do you get if you take the quantity of the most common element
     // and subtract the quantity of the least common element?
     fn part_1(&self) -> Result<Solution> {
-        let mut input = PairCounter::parse(self.template);
-        for _ in 0..10 {
-            input = self.apply_pair_insertion(input);
-        }
-        let counts = Self::pair_counter_to_char_counter(input);
-        let min = counts.min().unwrap();
-        let max = counts.max().unwrap();
-        Ok((max - min).into())
+        Ok(self.element_spread(10).into())
     }
 
     // Apply 40 steps of pair insertion to the polymer template and find the most and least common
     // elements in the result. What do you get if you take the quantity of the most common element
     // and subtract the quantity of the least common element?
     fn part_2(&self) -> Result<Solution> {
-        let mut input = PairCounter::parse(self.template);
-        for _ in 0..40 {
-            input = self.apply_pair_insertion(input);
-        }
-        let counts = Self::pair_counter_to_char_counter(input);
-        let min = counts.min().unwrap();
-        let max = counts.max().unwrap();
-        Ok((max - min).into())
+        Ok(self.element_spread(40).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C";
+
+    #[test]
+    fn test_element_spread() {
+        let day = Day14::new(TEST_INPUT).unwrap();
+        assert_eq!(day.element_spread(10), 1588);
+        assert_eq!(day.element_spread(40), 2188189693529);
+        assert_eq!(day.element_spread_after(10), Some(1588));
+        assert_eq!(day.element_spread_after(40), Some(2188189693529));
+        // the quantities far outgrow 128 bits, which is found without simulating each step
+        assert_eq!(day.element_spread_after(1_000_000), None);
+    }
+
+    #[test]
+    fn test_element_spread_same_ends() {
+        // the first and last elements are the same, and neither is in the middle of a pair
+        let day = Day14::new("NBN\n\nNB -> N\n").unwrap();
+        // NBN -> NNBN -> NNNBN
+        assert_eq!(day.element_spread(2), 3);
+        assert_eq!(day.element_spread_after(2), Some(3));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day14::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(err("x,y\n\nzz 3"), "rule 1: invalid rule \"zz 3\"");
+        assert_eq!(err("NN\n\nNNN -> C"), "rule 1: invalid pair \"NNN\"");
+        assert_eq!(err("NN\n\nCH -> B\nNN -> "), "rule 2: invalid element \"\"");
     }
 }
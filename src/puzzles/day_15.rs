@@ -3,55 +3,28 @@
 ** https://adventofcode.com/2021/day/15
 */
 
-use crate::types::{Array2D, Puzzle, Result, Solution};
+use crate::types::{
+    Array2D, Coord, FromLines, NeighborTable, PriorityMap, Puzzle, Result, Solution,
+};
+use crate::viz::{heatmap, Frame, Heatmap, Visualize};
 
-use std::cmp::Ordering;
+use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 
 const SIZE: usize = 100;
 const FULL_SIZE: usize = SIZE * 5;
 
-type Coord = (usize, usize);
-
-// tracks the path distances
-#[derive(Clone, Copy, Eq, PartialEq)]
-struct CoordDistance {
-    coord: Coord,
-    distance: u64,
-}
-
-impl CoordDistance {
-    fn new(coord: Coord, distance: u64) -> Self {
-        Self { coord, distance }
-    }
-}
-
-impl Ord for CoordDistance {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .distance
-            .cmp(&self.distance)
-            .then_with(|| self.coord.cmp(&other.coord))
-    }
-}
-
-impl PartialOrd for CoordDistance {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 pub struct Day15 {
     cave: Array2D<u8, SIZE, SIZE>,
-    cave_full: Array2D<u8, FULL_SIZE, FULL_SIZE>,
+    // note: boxed, as the full map is too large to move around on the stack
+    cave_full: Box<Array2D<u8, FULL_SIZE, FULL_SIZE>>,
+    neighbor_table: NeighborTable,
+    neighbor_table_full: NeighborTable,
 }
 
 impl Day15 {
-    pub fn new(input: &'static str) -> Self {
-        let cave = Array2D::from(input);
-        let mut cave_full = Array2D::new();
-        Self::build_full_cave(&cave, &mut cave_full);
-        Self { cave, cave_full }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
     fn build_full_cave(
@@ -59,18 +32,15 @@ impl Day15 {
         full_cave: &mut Array2D<u8, FULL_SIZE, FULL_SIZE>,
     ) {
         for row in 0..5 {
-            let row_offset = row * SIZE;
             for col in 0..5 {
-                let col_offset = col * SIZE;
-                for i in 0..SIZE {
-                    for j in 0..SIZE {
-                        let original = cave.get(i, j);
-                        let new = original + row as u8 + col as u8;
-                        if new > 9 {
-                            full_cave.set(row_offset + i, col_offset + j, new % 9);
-                        } else {
-                            full_cave.set(row_offset + i, col_offset + j, new);
-                        }
+                let offset = Coord::new(row * SIZE, col * SIZE);
+                for coord in cave.coords() {
+                    let original = cave.get(coord);
+                    let new = original + row as u8 + col as u8;
+                    if new > 9 {
+                        full_cave.set(offset + coord, new % 9);
+                    } else {
+                        full_cave.set(offset + coord, new);
                     }
                 }
             }
@@ -78,52 +48,162 @@ impl Day15 {
     }
 
     // implementation of Djikstra's algorithm to find the lowest-risk (i.e. shortest) path between
-    // the start and endpoint of the cave
-    fn lowest_risk_path<const N: usize>(&self, cave: &Array2D<u8, N, N>) -> u64 {
-        let size = N;
-        let total_size = size * size;
-
-        let origin = (0, 0);
-        let index = |(i, j)| (i * size) + j;
+    // the start and endpoint of the cave, as its total risk and the row-major index of each cell
+    // along it, from the start
+    // note: cells are visited by their row-major index, using a precomputed table of neighbors
+    fn lowest_risk_path<const N: usize>(
+        &self,
+        cave: &Array2D<u8, N, N>,
+        neighbor_table: &NeighborTable,
+    ) -> (u64, Vec<usize>) {
+        let total_size = N * N;
+        let risks = cave.iter().map(|&risk| risk as u64).collect::<Vec<_>>();
+
+        let origin = 0;
+        let end = total_size - 1;
 
         // assign distance 0 for the origin and infinity for all other nodes
-        let mut distances = (0..total_size).map(|_| u64::MAX).collect::<Vec<_>>();
+        let mut distances = vec![u64::MAX; total_size];
+        distances[origin] = 0;
+        // the cell from which each cell was reached by its shortest path
+        let mut previous = vec![origin; total_size];
+
+        // easily select the next node, where shortening a distance updates the queued node
+        let mut queue = PriorityMap::new();
+        queue.push(origin, 0);
+
+        while let Some((index, distance)) = queue.pop() {
+            // consider all neighbors
+            for &neighbor in neighbor_table.neighbors(index).iter() {
+                let neighbor = neighbor as usize;
+                let tmp_distance = distance + risks[neighbor];
+                if tmp_distance < distances[neighbor] {
+                    queue.push(neighbor, tmp_distance);
+                    distances[neighbor] = tmp_distance;
+                    previous[neighbor] = index;
+                }
+            }
+        }
+
+        let mut path = vec![end];
+        while let Some(&index) = path.last().filter(|&&index| index != origin) {
+            path.push(previous[index]);
+        }
+        path.reverse();
+        (distances[end], path)
+    }
+
+    // as lowest_risk_path, but queues cells on a plain binary heap, where shortening a distance
+    // queues the cell again and its outdated entries are skipped as they are popped
+    fn lowest_risk_lazy<const N: usize>(
+        &self,
+        cave: &Array2D<u8, N, N>,
+        neighbor_table: &NeighborTable,
+    ) -> u64 {
+        let total_size = N * N;
+        let risks = cave.iter().map(|&risk| risk as u64).collect::<Vec<_>>();
+
+        let mut distances = vec![u64::MAX; total_size];
         distances[0] = 0;
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, 0)));
 
-        // easily select the next node
-        let mut distance_heap = BinaryHeap::new();
-        distance_heap.push(CoordDistance::new(origin, 0));
-
-        while let Some(CoordDistance { coord, distance }) = distance_heap.pop() {
-            // skip if we have already found a shorter distance to this coordinate
-            if distance <= distances[index(coord)] {
-                // consider all neighbors
-                for neighbor in Array2D::<u8, N, N>::neighbors(coord.0, coord.1)
-                    .iter()
-                    .filter_map(|coord| *coord)
-                {
-                    let tmp_distance = distance + cave.get(neighbor.0, neighbor.1) as u64;
-                    if tmp_distance < distances[index(neighbor)] {
-                        distance_heap.push(CoordDistance::new(neighbor, tmp_distance));
-                        distances[index(neighbor)] = tmp_distance;
-                    }
+        while let Some(Reverse((distance, index))) = queue.pop() {
+            if distance > distances[index] {
+                continue;
+            }
+            for &neighbor in neighbor_table.neighbors(index).iter() {
+                let neighbor = neighbor as usize;
+                let tmp_distance = distance + risks[neighbor];
+                if tmp_distance < distances[neighbor] {
+                    queue.push(Reverse((tmp_distance, neighbor)));
+                    distances[neighbor] = tmp_distance;
                 }
             }
         }
-
         distances[total_size - 1]
     }
+
+    // the lowest total risk through the cave, or through the full map
+    pub fn lowest_risk(&self, full: bool) -> u64 {
+        if full {
+            self.lowest_risk_path(&self.cave_full, &self.neighbor_table_full)
+                .0
+        } else {
+            self.lowest_risk_path(&self.cave, &self.neighbor_table).0
+        }
+    }
+
+    // as lowest_risk, but with lowest_risk_lazy's binary heap in place of the priority map
+    pub fn lowest_risk_by_heap(&self, full: bool) -> u64 {
+        if full {
+            self.lowest_risk_lazy(&self.cave_full, &self.neighbor_table_full)
+        } else {
+            self.lowest_risk_lazy(&self.cave, &self.neighbor_table)
+        }
+    }
+
+    // the cave, where riskier cells are brighter, with the lowest-risk path drawn over it
+    fn frame<const N: usize>(
+        &self,
+        cave: &Array2D<u8, N, N>,
+        neighbor_table: &NeighborTable,
+    ) -> Frame {
+        let (_, path) = self.lowest_risk_path(cave, neighbor_table);
+        Frame::from_fn(N, N, |i, j| cave.get(Coord::new(i, j)) * 28).with_path(
+            path.into_iter()
+                .map(|index| Coord::new(index / N, index % N)),
+        )
+    }
+}
+
+impl Visualize for Day15 {
+    // the lowest-risk paths through the cave and then through the full map
+    fn frames(&self) -> impl Iterator<Item = Frame> {
+        [
+            self.frame(&self.cave, &self.neighbor_table),
+            self.frame(&self.cave_full, &self.neighbor_table_full),
+        ]
+        .into_iter()
+    }
+}
+
+impl Heatmap for Day15 {
+    // the risk level of each cell of the cave
+    fn heatmap(&self) -> Frame {
+        heatmap::from_array(&self.cave)
+    }
+}
+
+impl FromLines for Day15 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let cave = Array2D::parse_digits(&lines.collect::<Vec<_>>().join("\n"))?;
+        let mut cave_full = Box::new(Array2D::new());
+        time_block!(
+            "build_full_cave",
+            Self::build_full_cave(&cave, &mut cave_full)
+        );
+        Ok(Self {
+            cave,
+            cave_full,
+            neighbor_table: Array2D::<u8, SIZE, SIZE>::neighbor_table(),
+            neighbor_table_full: Array2D::<u8, FULL_SIZE, FULL_SIZE>::neighbor_table(),
+        })
+    }
 }
 
 impl Puzzle for Day15 {
     // What is the lowest total risk of any path from the top left to the bottom right?
     fn part_1(&self) -> Result<Solution> {
-        Ok(self.lowest_risk_path(&self.cave).into())
+        Ok(self.lowest_risk(false).into())
     }
 
     // Using the full map, what is the lowest total risk of any path from the top left to the
     // bottom right?
     fn part_2(&self) -> Result<Solution> {
-        Ok(self.lowest_risk_path(&self.cave_full).into())
+        Ok(self.lowest_risk(true).into())
     }
 }
@@ -3,7 +3,8 @@
 ** https://adventofcode.com/2021/day/17
 */
 
-use crate::types::{Puzzle, Result, Solution};
+use crate::types::{Context, FromLines, Puzzle, Result, Solution};
+use crate::utils::{self, Scan};
 
 use std::cmp;
 use std::ops::Range;
@@ -14,18 +15,29 @@ pub struct Day17 {
 }
 
 impl Day17 {
-    pub fn new(input: &'static str) -> Self {
-        split_into!(input, ": ", _x, ranges);
-        split_into!(ranges, ", ", x, y);
-        let x_range = Self::parse_range(x);
-        let y_range = Self::parse_range(y);
-        Self { x_range, y_range }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn parse_range(s: &str) -> Range<i64> {
-        let s = &s[2..s.len()];
-        split_into!(s, "..", start, end);
-        start.parse().unwrap()..(end.parse::<i64>().unwrap() + 1)
+    // parses an axis=start..end range, inclusive of its end
+    fn parse_range(s: &str, axis: &str) -> Result<Range<i64>> {
+        let range = s.strip_prefix(axis).and_then(|s| s.strip_prefix('='));
+        match range.and_then(|range| range.split_once("..")) {
+            Some((start, end)) => {
+                let start = start.parse().context("range minimum")?;
+                let end = end.parse::<i64>().context("range maximum")?;
+                Ok(start..(end + 1))
+            }
+            _ => Err(format!("invalid range {:?}", s).into()),
+        }
+    }
+
+    // the smallest vx which can reach the target area: x stops advancing after reaching the
+    // triangle number vx * (vx + 1) / 2, which only grows with vx
+    fn min_vx(&self) -> i64 {
+        utils::binary_search_by(1..self.x_range.end, |vx| {
+            vx * (vx + 1) / 2 >= self.x_range.start
+        })
     }
 
     // does the probe, when launched at the given velocity, land within the target area?
@@ -75,37 +87,87 @@ impl Day17 {
     }
 }
 
+impl FromLines for Day17 {
+    fn from_lines<'a, I>(mut lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let line = lines
+            .find(|line| !line.is_empty())
+            .ok_or("missing target area")?;
+        let ranges = line
+            .trim()
+            .strip_prefix("target area: ")
+            .ok_or_else(|| format!("invalid target area {:?}", line))?;
+        match split!(ranges, ", ") {
+            [x, y] => {
+                let x_range = Self::parse_range(x, "x").context("x range")?;
+                let y_range = Self::parse_range(y, "y").context("y range")?;
+                Ok(Self { x_range, y_range })
+            }
+            _ => Err(format!("invalid target area {:?}", line).into()),
+        }
+    }
+}
+
 impl Puzzle for Day17 {
     // Find the initial velocity that causes the probe to reach the highest y position and still
     // eventually be within the target area after any step. What is the highest y position it
     // reaches on this trajectory?
     fn part_1(&self) -> Result<Solution> {
-        // note: just brute-force it
+        // note: just brute-force it, searching each vy in parallel from the highest down, since
+        // the highest y reached only grows with vy
         // initial vx and vy must be positive
-        let mut y_max = 0;
-        for vx in 1..=self.x_range.end {
-            for vy in 1..=1000 {
-                if self.launch_probe(vx, vy) {
-                    y_max = cmp::max(y_max, self.max_y(vx, vy));
-                }
-            }
-        }
+        let y_max = utils::search_2d(
+            1..=1000,
+            self.min_vx()..=self.x_range.end,
+            Scan::Last,
+            true,
+            |vy, vx| self.launch_probe(vx, vy),
+        )
+        .into_iter()
+        .map(|(vy, vx)| self.max_y(vx, vy))
+        .max()
+        .unwrap_or(0);
         Ok(y_max.into())
     }
 
     // How many distinct initial velocity values cause the probe to be within the target area after
     // any step?
     fn part_2(&self) -> Result<Solution> {
-        // note: just brute-force it
+        // note: just brute-force it, searching each vx in parallel
         // initial vx must be positive
-        let mut count = 0;
-        for vx in 1..=self.x_range.end {
-            for vy in self.y_range.start..=1000 {
-                if self.launch_probe(vx, vy) {
-                    count += 1
-                }
-            }
-        }
+        let count = utils::search_2d(
+            self.min_vx()..=self.x_range.end,
+            self.y_range.start..=1000,
+            Scan::All,
+            true,
+            |vx, vy| self.launch_probe(vx, vy),
+        )
+        .len();
         Ok(count.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day17::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(err("x,y\n\nzz 3"), "invalid target area \"x,y\"");
+        assert_eq!(
+            err("target area: x=20..30"),
+            "invalid target area \"target area: x=20..30\""
+        );
+        assert_eq!(
+            err("target area: x=20..30, z=-10..-5"),
+            "y range: invalid range \"z=-10..-5\""
+        );
+        assert_eq!(
+            err("target area: x=20..3a, y=-10..-5"),
+            "x range: range maximum: invalid digit found in string"
+        );
+    }
+}
@@ -3,207 +3,121 @@
 ** https://adventofcode.com/2021/day/18
 */
 
-use crate::types::{Puzzle, Result, Solution, Tree};
-use crate::utils;
+use crate::types::{BinaryNode, BinaryTree, Context, FromLines, Puzzle, Result, Solution};
+
+use rayon::prelude::*;
 
-use std::cmp;
 use std::fmt;
 use std::ops::Add;
 
-#[derive(Clone, Debug, PartialEq)]
-enum NumberType {
-    Number(u8),
-    Nested,
-}
-
-impl NumberType {
-    fn number(&self) -> u8 {
-        match self {
-            Self::Number(n) => *n,
-            _ => unreachable!(),
-        }
-    }
-}
-
-impl From<u8> for NumberType {
-    fn from(n: u8) -> Self {
-        Self::Number(n)
-    }
-}
+// the numbers of a snailfish number are its leaves, and its pairs are the nodes above them
+type NumberTree = BinaryTree<u8>;
 
-impl From<u32> for NumberType {
-    fn from(n: u32) -> Self {
-        Self::Number(n as u8)
-    }
+// common interface over the tree and flat snailfish number representations
+trait Snailfish {
+    fn magnitude(&self) -> u64;
 }
 
-type NumberTree = Tree<NumberType>;
-
 struct SnailfishNumber {
     tree: NumberTree,
 }
 
 impl SnailfishNumber {
-    fn parse_number(tree: &mut NumberTree, s: &str, node_id: u64, pos: &mut usize) {
-        // skip the leading bracket
-        *pos += 1;
-
-        while *pos < s.len() {
-            let c = s.chars().nth(*pos).unwrap();
-            if c == ',' {
-                // continue, not relevant for parsing
-                *pos += 1;
-            } else if c == ']' {
-                // terminate
-                *pos += 1;
-                break;
-            } else if c.is_ascii_digit() {
-                // leaf node of the tree, insert and continue
-                tree.insert(c.to_digit(10).unwrap().into(), Some(node_id));
-                *pos += 1;
-            } else if c == '[' {
-                // add a branch point and recurse down another level
-                let new_node = tree.insert(NumberType::Nested, Some(node_id));
-                Self::parse_number(tree, s, new_node, pos);
-            }
+    // parses the pair or number starting at the given position, returning its node
+    fn parse_number(tree: &mut NumberTree, s: &str, pos: &mut usize) -> u64 {
+        if s.as_bytes()[*pos] == b'[' {
+            // skip the brackets and the comma between the halves of the pair
+            *pos += 1;
+            let left_id = Self::parse_number(tree, s, pos);
+            *pos += 1;
+            let right_id = Self::parse_number(tree, s, pos);
+            *pos += 1;
+            tree.insert_pair(left_id, right_id)
+        } else {
+            // accumulate all digits of the number
+            let len = s[*pos..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(s.len() - *pos);
+            let n = s[*pos..(*pos + len)].parse::<u8>().unwrap();
+            *pos += len;
+            tree.insert_leaf(n)
         }
     }
 
+    // finds the leftmost pair at the given depth or below, where the root is at depth 0
     fn find_nested_pair_rec(&self, depth: usize, node_id: u64) -> Option<u64> {
-        let node = self.tree.node(node_id).unwrap();
-        for child_id in node.children.iter() {
-            let child_node = self.tree.node(*child_id).unwrap();
-            if child_node.data == NumberType::Nested {
-                if depth == 4 {
-                    return Some(*child_id);
-                } else if let Some(id) = self.find_nested_pair_rec(depth + 1, *child_id) {
-                    return Some(id);
-                }
-            }
+        let (left_id, right_id) = self.tree.children(node_id)?;
+        if depth >= 4 {
+            Some(node_id)
+        } else {
+            self.find_nested_pair_rec(depth + 1, left_id)
+                .or_else(|| self.find_nested_pair_rec(depth + 1, right_id))
         }
-
-        None
     }
 
     fn magnitude_rec(&self, node_id: u64) -> u64 {
-        let node = self.tree.node(node_id).unwrap();
-        match node.data {
-            NumberType::Number(n) => n as u64,
-            NumberType::Nested => {
-                bind_vec_deref!(node.children, left_id, right_id);
+        match self.tree.node(node_id).unwrap() {
+            BinaryNode::Leaf(n) => *n as u64,
+            &BinaryNode::Pair(left_id, right_id) => {
                 (3 * self.magnitude_rec(left_id)) + (2 * self.magnitude_rec(right_id))
             }
         }
     }
 
     fn magnitude(&self) -> u64 {
-        match self.tree.root {
-            Some(root_id) => {
-                let node = self.tree.node(root_id).unwrap();
-                bind_vec_deref!(node.children, left_id, right_id);
-                (3 * self.magnitude_rec(left_id)) + (2 * self.magnitude_rec(right_id))
-            }
-            _ => unreachable!(),
-        }
+        self.magnitude_rec(self.tree.root.unwrap())
     }
 
     // finds the leftmost pair nested inside 4 pairs
     fn find_nested_pair(&self) -> Option<u64> {
-        if let Some(root) = self.tree.root {
-            self.find_nested_pair_rec(1, root)
-        } else {
-            None
-        }
+        self.find_nested_pair_rec(0, self.tree.root?)
     }
 
     fn explode(mut self, node_id: u64) -> Self {
-        let node = self.tree.node(node_id).unwrap();
-
         // grab the left and right elements of the nested pair
-        bind_vec_deref!(node.children, left_id, right_id);
-        let left = self.tree.node_data(left_id).unwrap().number();
-        let right = self.tree.node_data(right_id).unwrap().number();
-
-        // check for a left neighbor and add the left element to it, if found
-        if let Some(left_neighbor_id) = self.tree.left_neighbor_leaf(left_id) {
-            let mut node = self.tree.node_mut(left_neighbor_id).unwrap();
-            // note: assumes that this is a number and not a nested pair
-            node.data = (node.data.number() + left).into();
+        // note: assumes that both are numbers and not nested pairs
+        let (left_id, right_id) = self.tree.children(node_id).unwrap();
+        let left = *self.tree.leaf(left_id).unwrap();
+        let right = *self.tree.leaf(right_id).unwrap();
+
+        // add each element to its neighboring number, if there is one
+        if let Some(left_neighbor_id) = self.tree.left_neighbor_leaf(node_id) {
+            *self.tree.leaf_mut(left_neighbor_id).unwrap() += left;
         }
-        // check for a right neighbor and add the right element to it, if found
-        if let Some(right_neighbor_id) = self.tree.right_neighbor_leaf(right_id) {
-            let mut node = self.tree.node_mut(right_neighbor_id).unwrap();
-            // note: assumes that this is a number and not a nested pair
-            node.data = (node.data.number() + right).into();
+        if let Some(right_neighbor_id) = self.tree.right_neighbor_leaf(node_id) {
+            *self.tree.leaf_mut(right_neighbor_id).unwrap() += right;
         }
 
-        // first remove the children
-        self.tree.remove(left_id);
-        self.tree.remove(right_id);
         // then replace the nested pair with 0
-        // note: need to borrow mutably here separate from immutable borrows above
-        self.tree.node_mut(node_id).unwrap().data = 0u8.into();
+        self.tree.collapse(node_id, 0);
 
         self
     }
 
-    fn find_big_pair_rec(&self, node_id: u64) -> Option<u64> {
-        let node = self.tree.node(node_id).unwrap();
-        match node.data {
-            NumberType::Number(n) => {
-                if n > 9 {
-                    Some(node_id)
-                } else {
-                    None
-                }
-            }
-            NumberType::Nested => {
-                for child_id in node.children.iter() {
-                    if let Some(id) = self.find_big_pair_rec(*child_id) {
-                        return Some(id);
-                    }
-                }
-                None
-            }
-        }
-    }
-
-    // finds a number greater than or equal to 10
-    fn find_big_pair(&self) -> Option<u64> {
-        if let Some(root) = self.tree.root {
-            self.find_big_pair_rec(root)
-        } else {
-            None
-        }
+    // finds the leftmost number greater than or equal to 10
+    fn find_big_number(&self) -> Option<u64> {
+        self.tree
+            .leaves()
+            .find(|&id| *self.tree.leaf(id).unwrap() > 9)
     }
 
     fn split(mut self, node_id: u64) -> Self {
-        let mut node = self.tree.node_mut(node_id).unwrap();
-        let n = match node.data {
-            NumberType::Number(n) => n,
-            _ => unreachable!(),
-        };
-
-        node.data = NumberType::Nested;
-        self.tree.insert((n / 2).into(), Some(node_id));
-        self.tree.insert(((n + 1) / 2).into(), Some(node_id));
+        let n = *self.tree.leaf(node_id).unwrap();
+        self.tree.split(node_id, n / 2, n.div_ceil(2));
 
         self
     }
 
     fn reduce_number(mut self) -> Self {
-        let mut continue_reduction = true;
-        while continue_reduction {
-            continue_reduction = false;
-
-            // first check for explode then check for split
-            // either being found returns to the top of the loop
+        // first check for explode then check for split
+        // either being found returns to the top of the loop
+        loop {
             if let Some(node_id) = self.find_nested_pair() {
                 self = self.explode(node_id);
-                continue_reduction = true;
-            } else if let Some(node_id) = self.find_big_pair() {
+            } else if let Some(node_id) = self.find_big_number() {
                 self = self.split(node_id);
-                continue_reduction = true;
+            } else {
+                break;
             }
         }
 
@@ -211,31 +125,20 @@ impl SnailfishNumber {
     }
 
     fn to_string(&self, node_id: u64) -> String {
-        if let Some(node) = self.tree.node(node_id) {
-            match node.data {
-                NumberType::Number(n) => n.to_string(),
-                NumberType::Nested => {
-                    let children = node
-                        .children
-                        .iter()
-                        .map(|&child_id| self.to_string(child_id))
-                        .collect::<Vec<_>>();
-                    format!("[{}]", children.join(","))
-                }
+        match self.tree.node(node_id) {
+            Some(BinaryNode::Leaf(n)) => n.to_string(),
+            Some(&BinaryNode::Pair(left_id, right_id)) => {
+                format!("[{},{}]", self.to_string(left_id), self.to_string(right_id))
             }
-        } else {
-            String::new()
+            None => String::new(),
         }
     }
 }
 
 impl From<&str> for SnailfishNumber {
     fn from(s: &str) -> Self {
-        // build up a tree representation
-        let mut tree = Tree::new();
-        let node_id = tree.insert(NumberType::Nested, None);
-
-        Self::parse_number(&mut tree, s, node_id, &mut 0);
+        let mut tree = BinaryTree::new();
+        Self::parse_number(&mut tree, s, &mut 0);
         Self { tree }
     }
 }
@@ -244,7 +147,7 @@ impl Add<Self> for &SnailfishNumber {
     type Output = SnailfishNumber;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let tree = Tree::combine_trees(&self.tree, &rhs.tree, NumberType::Nested);
+        let tree = BinaryTree::combine(&self.tree, &rhs.tree);
         let output = SnailfishNumber { tree };
         output.reduce_number()
     }
@@ -260,16 +163,234 @@ impl fmt::Display for SnailfishNumber {
     }
 }
 
+impl Snailfish for SnailfishNumber {
+    fn magnitude(&self) -> u64 {
+        SnailfishNumber::magnitude(self)
+    }
+}
+
+// a snailfish number stored as its in-order leaves, each tagged with how many pairs it is nested
+// inside of; exploding and splitting only touch neighboring elements of the vector
+#[derive(Clone, Debug, PartialEq)]
+struct FlatSnailfishNumber {
+    elements: Vec<(u8, u8)>,
+}
+
+impl FlatSnailfishNumber {
+    // finds the index of the left element of the leftmost pair nested inside 4 pairs
+    fn find_nested_pair(&self) -> Option<usize> {
+        self.elements.iter().position(|&(_, depth)| depth > 4)
+    }
+
+    fn explode(&mut self, i: usize) {
+        let (left, depth) = self.elements[i];
+        let (right, _) = self.elements[i + 1];
+
+        // add the left and right elements to their neighbors, if present
+        if i > 0 {
+            self.elements[i - 1].0 += left;
+        }
+        if i + 2 < self.elements.len() {
+            self.elements[i + 2].0 += right;
+        }
+
+        // then replace the nested pair with 0
+        self.elements[i] = (0, depth - 1);
+        self.elements.remove(i + 1);
+    }
+
+    // finds the index of the leftmost number greater than or equal to 10
+    fn find_big_number(&self) -> Option<usize> {
+        self.elements.iter().position(|&(n, _)| n > 9)
+    }
+
+    fn split(&mut self, i: usize) {
+        let (n, depth) = self.elements[i];
+        self.elements[i] = (n / 2, depth + 1);
+        self.elements.insert(i + 1, (n.div_ceil(2), depth + 1));
+    }
+
+    fn reduce_number(&mut self) {
+        // first check for explode then check for split
+        // either being found returns to the top of the loop
+        loop {
+            if let Some(i) = self.find_nested_pair() {
+                self.explode(i);
+            } else if let Some(i) = self.find_big_number() {
+                self.split(i);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn magnitude(&self) -> u64 {
+        // collapse sibling leaves as they appear; two adjacent elements at the same depth on top
+        // of the stack must be the left and right halves of a single pair
+        let mut stack: Vec<(u64, u8)> = Vec::with_capacity(self.elements.len());
+        for &(n, depth) in self.elements.iter() {
+            stack.push((n as u64, depth));
+            while stack.len() >= 2 && stack[stack.len() - 1].1 == stack[stack.len() - 2].1 {
+                let (right, depth) = stack.pop().unwrap();
+                let (left, _) = stack.pop().unwrap();
+                stack.push(((3 * left) + (2 * right), depth - 1));
+            }
+        }
+        stack[0].0
+    }
+
+    fn fmt_rec(&self, f: &mut fmt::Formatter<'_>, depth: u8, pos: &mut usize) -> fmt::Result {
+        let (n, n_depth) = self.elements[*pos];
+        if n_depth == depth {
+            *pos += 1;
+            write!(f, "{}", n)
+        } else {
+            write!(f, "[")?;
+            self.fmt_rec(f, depth + 1, pos)?;
+            write!(f, ",")?;
+            self.fmt_rec(f, depth + 1, pos)?;
+            write!(f, "]")
+        }
+    }
+}
+
+// the deepest a number in the homework is nested, as the homework is already reduced
+const MAX_DEPTH: u8 = 4;
+// the most elements a sum holds while it is reduced, as none is nested inside more than
+// MAX_DEPTH + 1 pairs, such that its vector never needs to grow
+const MAX_ELEMENTS: usize = 2 << MAX_DEPTH;
+
+impl FlatSnailfishNumber {
+    fn parse(s: &str) -> Result<Self> {
+        if !s.starts_with('[') {
+            return Err(format!("expected a pair, found {:?}", s).into());
+        }
+        let mut elements = vec![];
+        let rest = Self::parse_element(s, 0, &mut elements)?;
+        if !rest.is_empty() {
+            return Err(format!("unexpected {:?} after the number", rest).into());
+        }
+        Ok(Self { elements })
+    }
+
+    // parses the pair or regular number at the start of the string, returning the rest of it
+    fn parse_element<'a>(s: &'a str, depth: u8, elements: &mut Vec<(u8, u8)>) -> Result<&'a str> {
+        match s.strip_prefix('[') {
+            Some(_) if depth == MAX_DEPTH => Err("pair nested too deeply".into()),
+            Some(rest) => {
+                let rest = Self::parse_element(rest, depth + 1, elements)?;
+                let rest = rest.strip_prefix(',').ok_or("expected ',' within a pair")?;
+                let rest = Self::parse_element(rest, depth + 1, elements)?;
+                Ok(rest
+                    .strip_prefix(']')
+                    .ok_or("expected ']' closing a pair")?)
+            }
+            None => {
+                let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                let n = s[..end]
+                    .parse()
+                    .with_context(|| format!("regular number {:?}", &s[..end]))?;
+                // note: as for nesting, larger numbers would have been split, and could overflow
+                // as their neighbors explode into them
+                if n > 9 {
+                    return Err(format!("regular number {} is not reduced", n).into());
+                }
+                elements.push((n, depth));
+                Ok(&s[end..])
+            }
+        }
+    }
+}
+
+impl Add<Self> for &FlatSnailfishNumber {
+    type Output = FlatSnailfishNumber;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        // both operands are nested one level deeper under the new pair
+        let mut elements = Vec::with_capacity(MAX_ELEMENTS);
+        elements.extend(
+            self.elements
+                .iter()
+                .chain(rhs.elements.iter())
+                .map(|&(n, depth)| (n, depth + 1)),
+        );
+        let mut output = FlatSnailfishNumber { elements };
+        output.reduce_number();
+        output
+    }
+}
+
+impl fmt::Display for FlatSnailfishNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.elements.is_empty() {
+            write!(f, "")
+        } else {
+            self.fmt_rec(f, 0, &mut 0)
+        }
+    }
+}
+
+impl Snailfish for FlatSnailfishNumber {
+    fn magnitude(&self) -> u64 {
+        FlatSnailfishNumber::magnitude(self)
+    }
+}
+
 pub struct Day18 {
-    numbers: Vec<SnailfishNumber>,
+    numbers: Vec<FlatSnailfishNumber>,
 }
 
 impl Day18 {
-    pub fn new(input: &'static str) -> Self {
-        let numbers = utils::input_to_lines(input)
-            .map(SnailfishNumber::from)
-            .collect();
-        Self { numbers }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+
+    fn final_sum_magnitude<N>(numbers: &[N]) -> u64
+    where
+        N: Snailfish,
+        for<'a> &'a N: Add<&'a N, Output = N>,
+    {
+        let mut sum = &numbers[0] + &numbers[1];
+        for number in numbers.iter().skip(2) {
+            sum = &sum + number;
+        }
+        sum.magnitude()
+    }
+
+    fn max_pair_magnitude<N>(numbers: &[N]) -> u64
+    where
+        N: Snailfish + Sync,
+        for<'a> &'a N: Add<&'a N, Output = N>,
+    {
+        // addition is not commutative, so check every ordered pair
+        let n = numbers.len();
+        (0..(n * n))
+            .into_par_iter()
+            .map(|k| (k / n, k % n))
+            .filter(|(i, j)| i != j)
+            .map(|(i, j)| (&numbers[i] + &numbers[j]).magnitude())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl FromLines for Day18 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let numbers = lines
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                FlatSnailfishNumber::parse(line).with_context(|| format!("number {}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // note: both parts add numbers in pairs
+        if numbers.len() < 2 {
+            return Err(format!("expected at least 2 numbers, found {}", numbers.len()).into());
+        }
+        Ok(Self { numbers })
     }
 }
 
@@ -277,34 +398,20 @@ impl Puzzle for Day18 {
     // Add up all of the snailfish numbers from the homework assignment in the order they appear.
     // What is the magnitude of the final sum?
     fn part_1(&self) -> Result<Solution> {
-        let mut sum = &self.numbers[0] + &self.numbers[1];
-        for number in self.numbers.iter().skip(2) {
-            sum = &sum + number;
-        }
-        Ok(sum.magnitude().into())
+        Ok(Self::final_sum_magnitude(&self.numbers).into())
     }
 
     // What is the largest magnitude of any sum of two different snailfish numbers from the
     // homework assignment?
     fn part_2(&self) -> Result<Solution> {
-        let mut max_magnitude = 0;
-        for i in 0..(self.numbers.len() - 1) {
-            for j in (i + 1)..self.numbers.len() {
-                let a = &self.numbers[i];
-                let b = &self.numbers[j];
-                let c = a + b;
-                let d = b + a;
-                max_magnitude = cmp::max(max_magnitude, cmp::max(c.magnitude(), d.magnitude()));
-            }
-        }
-
-        Ok(max_magnitude.into())
+        Ok(Self::max_pair_magnitude(&self.numbers).into())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils;
 
     #[test]
     fn test_parse_snailfish_number_simple() {
@@ -313,12 +420,9 @@ mod tests {
         let root = number.tree.root;
         assert!(root.is_some());
 
-        let root_node = number.tree.node(root.unwrap()).unwrap();
-        assert_eq!(root_node.children.len(), 2);
-        for (node_id, exp) in root_node.children.iter().zip([1, 2]) {
-            let node = number.tree.node(*node_id).unwrap();
-            assert_eq!(node.data, NumberType::Number(exp));
-            assert_eq!(node.children.len(), 0);
+        let (left_id, right_id) = number.tree.children(root.unwrap()).unwrap();
+        for (node_id, exp) in [left_id, right_id].into_iter().zip([1, 2]) {
+            assert_eq!(number.tree.node(node_id), Some(&BinaryNode::Leaf(exp)));
         }
     }
 
@@ -328,24 +432,20 @@ mod tests {
 
         let root = number.tree.root;
         assert!(root.is_some());
-        let root_node = number.tree.node(root.unwrap()).unwrap();
-        assert_eq!(root_node.children.len(), 2);
-
-        let left_id = root_node.children[0];
-        let left_node = number.tree.node(left_id).unwrap();
-        assert_eq!(left_node.data, NumberType::Nested);
-        assert_eq!(left_node.children.len(), 2);
-
-        let right_id = root_node.children[1];
-        let right_node = number.tree.node(right_id).unwrap();
-        assert_eq!(right_node.data, 4u8.into());
-        assert_eq!(right_node.children.len(), 0);
+        let (left_id, right_id) = number.tree.children(root.unwrap()).unwrap();
+        assert!(number.tree.children(left_id).is_some());
+        assert_eq!(number.tree.leaf(right_id), Some(&4));
     }
 
     #[test]
     fn test_snailfish_number_nested_pair() {
         let number = SnailfishNumber::from("[[[[[9,8],1],2],3],4]");
-        assert_eq!(number.find_nested_pair(), Some(4));
+        let node_id = number.find_nested_pair().unwrap();
+        assert_eq!(number.to_string(node_id), "[9,8]");
+        assert_eq!(
+            SnailfishNumber::from("[[[[1,2],3],4],5]").find_nested_pair(),
+            None
+        );
     }
 
     #[test]
@@ -368,4 +468,140 @@ mod tests {
         let c = SnailfishNumber::from("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]");
         assert_eq!(c.magnitude(), 3488);
     }
+
+    #[test]
+    fn test_parse_snailfish_number_multi_digit() {
+        let number = SnailfishNumber::from("[15,[0,13]]");
+        let left_id = number.tree.left(number.tree.root.unwrap()).unwrap();
+        assert_eq!(number.tree.leaf(left_id), Some(&15));
+        assert_eq!(format!("{}", number), "[15,[0,13]]");
+    }
+
+    #[test]
+    fn test_snailfish_number_round_trip() {
+        // reduce partway by hand so that the intermediate contains multi-digit literals
+        let number = SnailfishNumber::from("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+        let node_id = number.find_nested_pair().unwrap();
+        let number = number.explode(node_id);
+        let node_id = number.find_nested_pair().unwrap();
+        let number = number.explode(node_id);
+        let intermediate = format!("{}", number);
+        assert_eq!(intermediate, "[[[[0,7],4],[15,[0,13]]],[1,1]]");
+
+        let reparsed = SnailfishNumber::from(intermediate.as_str());
+        assert_eq!(format!("{}", reparsed), intermediate);
+        let reduced = reparsed.reduce_number();
+        assert_eq!(format!("{}", reduced), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+    }
+
+    const TEST_HOMEWORK: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]";
+
+    fn parse_homework<N>(parse: fn(&str) -> N) -> Vec<N> {
+        utils::input_to_lines(TEST_HOMEWORK).map(parse).collect()
+    }
+
+    fn parse_flat(s: &str) -> FlatSnailfishNumber {
+        FlatSnailfishNumber::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_flat_snailfish_number_round_trip() {
+        let s = "[[[[0,7],4],[7,[8,4]]],[1,1]]";
+        let number = parse_flat(s);
+        assert_eq!(format!("{}", number), s);
+
+        // numbers part way through reduction may hold larger regular numbers
+        let number = FlatSnailfishNumber {
+            elements: vec![(15, 1), (0, 2), (13, 2)],
+        };
+        assert_eq!(format!("{}", number), "[15,[0,13]]");
+    }
+
+    #[test]
+    fn test_add_flat_snailfish_numbers() {
+        let a = parse_flat("[[[[4,3],4],4],[7,[[8,4],9]]]");
+        let b = parse_flat("[1,1]");
+        let c = &a + &b;
+        assert_eq!(format!("{}", c), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+        assert_eq!(
+            c.magnitude(),
+            SnailfishNumber::from(c.to_string().as_str()).magnitude()
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day18::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(
+            err("x,y\n\nzz 3"),
+            "number 1: expected a pair, found \"x,y\""
+        );
+        assert_eq!(err("[1,2]\n[1,2"), "number 2: expected ']' closing a pair");
+        assert_eq!(err("[1,2]\n[1;2]"), "number 2: expected ',' within a pair");
+        assert_eq!(
+            err("[1,2]\n[1,a]"),
+            "number 2: regular number \"\": cannot parse integer from empty string"
+        );
+        assert_eq!(
+            err("[1,2]\n[1,2]]"),
+            "number 2: unexpected \"]\" after the number"
+        );
+        assert_eq!(
+            err("[1,2]\n[[[[[1,2],3],4],5],6]"),
+            "number 2: pair nested too deeply"
+        );
+        assert_eq!(
+            err("[1,2]\n[1,[255,9]]"),
+            "number 2: regular number 255 is not reduced"
+        );
+        assert_eq!(err("[1,2]"), "expected at least 2 numbers, found 1");
+    }
+
+    #[test]
+    fn test_homework_representations_agree() {
+        let tree = parse_homework(|s| SnailfishNumber::from(s));
+        let flat = parse_homework(parse_flat);
+        assert_eq!(Day18::final_sum_magnitude(&tree), 4140);
+        assert_eq!(Day18::final_sum_magnitude(&flat), 4140);
+        assert_eq!(Day18::max_pair_magnitude(&tree), 3993);
+        assert_eq!(Day18::max_pair_magnitude(&flat), 3993);
+    }
+
+    // run with: cargo test --release bench_ -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_snailfish_representations() {
+        use std::time::Instant;
+
+        let iterations = 20;
+        let tree = parse_homework(|s| SnailfishNumber::from(s));
+        let start = Instant::now();
+        for _ in 0..iterations {
+            assert_eq!(Day18::max_pair_magnitude(&tree), 3993);
+        }
+        let tree_time = start.elapsed() / iterations;
+
+        let flat = parse_homework(parse_flat);
+        let start = Instant::now();
+        for _ in 0..iterations {
+            assert_eq!(Day18::max_pair_magnitude(&flat), 3993);
+        }
+        let flat_time = start.elapsed() / iterations;
+
+        println!(
+            "tree: {:?} flat: {:?} ({:.1}x)",
+            tree_time,
+            flat_time,
+            tree_time.as_secs_f64() / flat_time.as_secs_f64()
+        );
+    }
 }
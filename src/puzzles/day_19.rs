@@ -3,468 +3,607 @@
 ** https://adventofcode.com/2021/day/19
 */
 
-use crate::types::{Puzzle, PuzzleError, Result, Solution};
+use crate::explain;
+use crate::types::{Context, Counter, FromLines, Puzzle, PuzzleError, Result, Solution};
+use crate::utils;
 
 use itertools::Itertools;
-use nalgebra::{Rotation3, Vector3};
-
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
-
-#[derive(Debug, Clone, Copy)]
-enum Rotation {
-    Identity,
-    RotateX,
-    RotateY,
-    RotateZ,
-    RotateXY,
-    RotateXZ,
-    RotateYZ,
-    RotateXYZ,
-}
+use nalgebra::{Matrix3, Vector3};
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
 
-impl Rotation {
-    fn has_x_rotation(&self) -> bool {
-        matches!(
-            self,
-            Self::RotateX | Self::RotateXY | Self::RotateXZ | Self::RotateXYZ
-        )
-    }
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+use std::fmt;
 
-    fn has_y_rotation(&self) -> bool {
-        matches!(
-            self,
-            Self::RotateY | Self::RotateXY | Self::RotateYZ | Self::RotateXYZ
-        )
-    }
+pub type Beacon = Vector3<i64>;
+pub type Orientation = Matrix3<i64>;
 
-    fn has_z_rotation(&self) -> bool {
-        matches!(
-            self,
-            Self::RotateZ | Self::RotateXZ | Self::RotateYZ | Self::RotateXYZ
-        )
-    }
+// scanners must share at least this many beacons to be considered overlapping
+const MIN_OVERLAP: usize = 12;
 
-    fn rotate_inner(&self, v: &Vector3<i64>, angle: f64) -> Vector3<i64> {
-        // note: need to convert to floating point first
-        let mut u = v.map(|n| n as f64);
+fn determinant(m: &Orientation) -> i64 {
+    m[(0, 0)] * (m[(1, 1)] * m[(2, 2)] - m[(1, 2)] * m[(2, 1)])
+        - m[(0, 1)] * (m[(1, 0)] * m[(2, 2)] - m[(1, 2)] * m[(2, 0)])
+        + m[(0, 2)] * (m[(1, 0)] * m[(2, 1)] - m[(1, 1)] * m[(2, 0)])
+}
 
-        if self.has_x_rotation() {
-            let rot = Rotation3::from_axis_angle(&Vector3::x_axis(), angle);
-            u = rot * u;
-        }
-        if self.has_y_rotation() {
-            let rot = Rotation3::from_axis_angle(&Vector3::y_axis(), angle);
-            u = rot * u;
+// the 24 proper rotations are the signed permutation matrices with a determinant of 1; the other
+// 24 signed permutations include a reflection, which a scanner cannot be oriented with
+fn orientations() -> Vec<Orientation> {
+    let mut orientations = Vec::with_capacity(24);
+    for axes in (0..3).permutations(3) {
+        for signs in 0..8 {
+            let mut m = Orientation::zeros();
+            for (row, &col) in axes.iter().enumerate() {
+                m[(row, col)] = if signs & (1 << row) == 0 { 1 } else { -1 };
+            }
+            if determinant(&m) == 1 {
+                orientations.push(m);
+            }
         }
-        if self.has_z_rotation() {
-            let rot = Rotation3::from_axis_angle(&Vector3::z_axis(), angle);
-            u = rot * u;
+    }
+    orientations
+}
+
+// the position and orientation of a scanner within the frame of a reference scanner, which maps
+// coordinates from the scanner's frame into the reference's: rotate, then translate
+// note: the solution of scanner B relative to scanner A, composed with that of scanner A relative
+// to scanner 0, is the solution of scanner B relative to scanner 0
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScannerSolution {
+    // the scanner's origin within the reference frame
+    pub position: Beacon,
+    // rotates the scanner's axes onto those of the reference frame
+    pub orientation: Orientation,
+}
+
+impl ScannerSolution {
+    // the solution of the reference scanner itself
+    fn identity() -> Self {
+        Self {
+            position: Beacon::zeros(),
+            orientation: Orientation::identity(),
         }
+    }
 
-        // convert back to integers
-        u.map(|n| n as i64)
+    // maps a beacon from the scanner's frame into the reference frame
+    pub fn transform_to_reference(&self, beacon: &Beacon) -> Beacon {
+        (self.orientation * beacon) + self.position
     }
 
-    fn rotate(&self, v: &Vector3<i64>) -> Vector3<i64> {
-        let angle = std::f64::consts::FRAC_PI_2;
-        self.rotate_inner(v, angle)
+    // the solution of the reference scanner relative to this one
+    // note: rotations are orthogonal, so the inverse rotation is the transpose
+    fn inverse(&self) -> Self {
+        let orientation = self.orientation.transpose();
+        Self {
+            position: -(orientation * self.position),
+            orientation,
+        }
     }
 
-    fn unrotate(&self, v: &Vector3<i64>) -> Vector3<i64> {
-        let angle = std::f64::consts::PI + std::f64::consts::FRAC_PI_2;
-        self.rotate_inner(v, angle)
+    // the solution of a scanner whose solution relative to this scanner is other, relative to
+    // this scanner's reference
+    fn compose(&self, other: &Self) -> Self {
+        Self {
+            position: self.transform_to_reference(&other.position),
+            orientation: self.orientation * other.orientation,
+        }
     }
 }
 
-const ROTATIONS: [Rotation; 8] = [
-    Rotation::Identity,
-    Rotation::RotateX,
-    Rotation::RotateY,
-    Rotation::RotateZ,
-    Rotation::RotateXY,
-    Rotation::RotateXZ,
-    Rotation::RotateYZ,
-    Rotation::RotateXYZ,
-];
-
-#[derive(Debug, Clone, Copy)]
-enum Reflection {
-    Identity,
-    ReflectX,
-    ReflectY,
-    ReflectZ,
-    ReflectXY,
-    ReflectXZ,
-    ReflectYZ,
-    ReflectXYZ,
+// a solved scanner, for checking the solver against the positions documented in the example
+pub struct ScannerReport {
+    pub scanner: usize,
+    pub solution: ScannerSolution,
+    // the other scanners which see any of the same beacons, with the number of beacons shared
+    pub overlaps: Vec<(usize, usize)>,
 }
 
-impl Reflection {
-    fn combine(a: Self, b: Self) -> Self {
-        let a: u8 = a.into();
-        let b: u8 = b.into();
-        Self::from(a | b)
+impl fmt::Display for ScannerReport {
+    // e.g. "scanner 1 at 68,-1246,-43 facing -x,+y,-z, overlapping 0 (12), 3 (12)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = self.solution.position;
+        // each axis of the reference frame, as the signed axis of the scanner's frame it lies along
+        let axes = self
+            .solution
+            .orientation
+            .row_iter()
+            .map(|row| {
+                let col = row.iter().position(|&x| x != 0).unwrap();
+                let sign = if row[col] > 0 { '+' } else { '-' };
+                format!("{}{}", sign, ['x', 'y', 'z'][col])
+            })
+            .join(",");
+        let overlaps = self
+            .overlaps
+            .iter()
+            .map(|(scanner, n_beacons)| format!("{} ({})", scanner, n_beacons))
+            .join(", ");
+        write!(
+            f,
+            "scanner {} at {},{},{} facing {}, overlapping {}",
+            self.scanner, position.x, position.y, position.z, axes, overlaps
+        )
     }
+}
+
+fn square_distance(va: &Beacon, vb: &Beacon) -> i64 {
+    va.zip_map(vb, |x, y| (x - y).pow(2)).fold(0, |x, y| x + y)
+}
 
-    fn from_parameters(x: bool, y: bool, z: bool) -> Self {
-        let mut reflection = Self::Identity;
+// squared distances between every pair of beacons seen by a scanner; distances do not depend on
+// the scanner's position or orientation, so matching beacons can be found by hashing
+// note: the fixed hasher visits the distances in the same order on every run, such that the
+// candidate correspondences, and so the alignment of each pair of scanners, are reproducible
+struct Fingerprint {
+    pairs: FxHashMap<i64, Vec<(usize, usize)>>,
+}
 
-        if x {
-            reflection = Self::combine(reflection, Self::ReflectX);
-        }
-        if y {
-            reflection = Self::combine(reflection, Self::ReflectY);
+impl Fingerprint {
+    fn new(beacons: &[Beacon]) -> Self {
+        let mut pairs: FxHashMap<i64, Vec<(usize, usize)>> = FxHashMap::default();
+        for ((i, a), (j, b)) in beacons.iter().enumerate().tuple_combinations() {
+            pairs.entry(square_distance(a, b)).or_default().push((i, j));
         }
-        if z {
-            reflection = Self::combine(reflection, Self::ReflectZ);
-        }
-
-        reflection
+        Self { pairs }
     }
 
-    fn solve_for_reflection(a: &Vector3<i64>, b: &Vector3<i64>) -> Option<Self> {
-        // compare the absolute values, they must be equal
-        let a_abs = a.map(|n| n.abs());
-        let b_abs = b.map(|n| n.abs());
+    // the number of pairwise distances both scanners see, counted with multiplicity
+    fn common_distances(&self, other: &Self) -> usize {
+        self.pairs
+            .iter()
+            .filter_map(|(d, pairs)| other.pairs.get(d).map(|other| pairs.len().min(other.len())))
+            .sum()
+    }
 
-        if a_abs == b_abs {
-            let reflect_x = a.x != b.x;
-            let reflect_y = a.y != b.y;
-            let reflect_z = a.z != b.z;
-            Some(Self::from_parameters(reflect_x, reflect_y, reflect_z))
-        } else {
-            None
+    // candidate (self, other) beacon correspondences: a beacon seen by both scanners shares its
+    // distances to the other 11 overlapping beacons, so it collects at least 11 votes
+    fn correspondences(&self, other: &Self) -> Vec<(usize, usize)> {
+        let mut votes = Counter::new();
+        for (d, pairs) in self.pairs.iter() {
+            if let Some(other_pairs) = other.pairs.get(d) {
+                // note: the endpoints of a pair can match up either way around
+                for (&(i1, i2), &(j1, j2)) in pairs.iter().cartesian_product(other_pairs.iter()) {
+                    votes.insert((i1, j1));
+                    votes.insert((i1, j2));
+                    votes.insert((i2, j1));
+                    votes.insert((i2, j2));
+                }
+            }
         }
+        votes
+            .iter()
+            .filter(|(_, &n)| n >= MIN_OVERLAP - 1)
+            .map(|(&pair, _)| pair)
+            .collect()
     }
+}
 
-    fn has_x_reflection(&self) -> bool {
-        matches!(
-            self,
-            Self::ReflectX | Self::ReflectXY | Self::ReflectXZ | Self::ReflectXYZ
-        )
+pub struct Day19 {
+    scanner_reports: Vec<Vec<Beacon>>,
+    // the solution of each scanner relative to scanner 0
+    // note: solved on first use and shared by both parts
+    scanner_solutions: OnceCell<Vec<ScannerSolution>>,
+}
+
+impl Day19 {
+    fn parse_vector(input: &str) -> Result<Beacon> {
+        match split!(input, ',') {
+            [x, y, z] => Ok(Vector3::new(
+                x.parse().context("x")?,
+                y.parse().context("y")?,
+                z.parse().context("z")?,
+            )),
+            _ => Err(format!("invalid beacon {:?}", input).into()),
+        }
     }
 
-    fn has_y_reflection(&self) -> bool {
-        matches!(
-            self,
-            Self::ReflectY | Self::ReflectXY | Self::ReflectYZ | Self::ReflectXYZ
-        )
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn has_z_reflection(&self) -> bool {
-        matches!(
-            self,
-            Self::ReflectZ | Self::ReflectXZ | Self::ReflectYZ | Self::ReflectXYZ
-        )
+    fn manhattan_distance(va: &Beacon, vb: &Beacon) -> i64 {
+        va.zip_map(vb, |x, y| (x - y).abs()).fold(0, |x, y| x + y)
     }
 
-    fn reflect(&self, v: &Vector3<i64>) -> Vector3<i64> {
-        let mut x = v.x;
-        let mut y = v.y;
-        let mut z = v.z;
+    // lists, for each scanner, the scanners which likely see the same beacons
+    fn overlap_graph(fingerprints: &[Fingerprint]) -> Vec<Vec<usize>> {
+        // treat the beacons as a complete graph so need n * (n - 1) / 2 overlaps
+        // for n=12 this is 66
+        let n_common = MIN_OVERLAP * (MIN_OVERLAP - 1) / 2;
+        let mut graph = vec![vec![]; fingerprints.len()];
+        for ((i, fp_a), (j, fp_b)) in fingerprints.iter().enumerate().tuple_combinations() {
+            if fp_a.common_distances(fp_b) >= n_common {
+                graph[i].push(j);
+                graph[j].push(i);
+            }
+        }
+        graph
+    }
 
-        if self.has_x_reflection() {
-            x = -x;
+    // finds the solution of scanner B relative to scanner A, if at least 12 of the candidate beacon
+    // correspondences line up under one of the orientations
+    fn align(
+        beacons_a: &[Beacon],
+        beacons_b: &[Beacon],
+        correspondences: &[(usize, usize)],
+        orientations: &[Orientation],
+    ) -> Option<ScannerSolution> {
+        if correspondences.len() < MIN_OVERLAP {
+            return None;
         }
-        if self.has_y_reflection() {
-            y = -y;
+        for &orientation in orientations.iter() {
+            // each correspondence votes for the scanner position which would make its beacons
+            // coincide
+            let positions = Counter::from(
+                correspondences
+                    .iter()
+                    .map(|&(i, j)| beacons_a[i] - (orientation * beacons_b[j])),
+            );
+            let aligned = positions.iter().find(|(_, &n)| n >= MIN_OVERLAP);
+            if let Some((&position, _)) = aligned {
+                return Some(ScannerSolution {
+                    position,
+                    orientation,
+                });
+            }
         }
-        if self.has_z_reflection() {
-            z = -z;
+        None
+    }
+
+    fn solve_scanners(&self) -> Result<Vec<ScannerSolution>> {
+        let n_scanners = self.scanner_reports.len();
+        let orientations = orientations();
+        let fingerprints = time_block!(
+            "fingerprint",
+            self.scanner_reports
+                .par_iter()
+                .map(|beacons| Fingerprint::new(beacons))
+                .collect::<Vec<_>>()
+        );
+        let graph = time_block!("overlap_graph", Self::overlap_graph(&fingerprints));
+
+        // each overlap check is independent, so align every candidate pair up-front in parallel;
+        // the results are collected rather than shared so no locking is needed
+        let reports = &self.scanner_reports;
+        let relative_solutions = time_block!(
+            "align",
+            graph
+                .iter()
+                .enumerate()
+                .flat_map(|(a, neighbors)| {
+                    neighbors
+                        .iter()
+                        .filter(move |&&b| a < b)
+                        .map(move |&b| (a, b))
+                })
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .filter_map(|(a, b)| {
+                    let correspondences = fingerprints[a].correspondences(&fingerprints[b]);
+                    Self::align(&reports[a], &reports[b], &correspondences, &orientations)
+                        .map(|relative| ((a, b), relative))
+                })
+                .collect::<FxHashMap<_, _>>()
+        );
+
+        // use the first scanner as the base reference and walk outwards through the overlaps,
+        // composing each relative solution with that of the scanner it was solved from
+        let mut solutions = vec![None; n_scanners];
+        solutions[0] = Some(ScannerSolution::identity());
+        let mut queue = VecDeque::from([0]);
+        while let Some(a) = queue.pop_front() {
+            let solution_a = solutions[a].unwrap();
+            for &b in graph[a].iter() {
+                if solutions[b].is_some() {
+                    continue;
+                }
+                // note: pairs were aligned in one direction only, invert for the other
+                let relative = if a < b {
+                    relative_solutions.get(&(a, b)).copied()
+                } else {
+                    relative_solutions.get(&(b, a)).map(|s| s.inverse())
+                };
+                if let Some(relative) = relative {
+                    solutions[b] = Some(solution_a.compose(&relative));
+                    queue.push_back(b);
+                }
+            }
         }
 
-        Vector3::new(x, y, z)
+        let solutions = solutions
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(PuzzleError::NoSolution)?;
+        Ok(solutions)
     }
-}
 
-#[allow(clippy::from_over_into)]
-impl Into<u8> for Reflection {
-    fn into(self) -> u8 {
-        match self {
-            Self::Identity => 0x0,
-            Self::ReflectX => 0x1,
-            Self::ReflectY => 0x2,
-            Self::ReflectZ => 0x4,
-            Self::ReflectXY => 0x3,
-            Self::ReflectXZ => 0x5,
-            Self::ReflectYZ => 0x6,
-            Self::ReflectXYZ => 0x7,
+    // the position and orientation of every scanner relative to scanner 0
+    pub fn scanner_solutions(&self) -> Result<&[ScannerSolution]> {
+        if let Some(solutions) = self.scanner_solutions.get() {
+            return Ok(solutions);
         }
+        let solutions = self.solve_scanners()?;
+        Ok(self.scanner_solutions.get_or_init(|| solutions))
     }
-}
 
-impl From<u8> for Reflection {
-    fn from(n: u8) -> Self {
-        match n {
-            0x0 => Self::Identity,
-            0x1 => Self::ReflectX,
-            0x2 => Self::ReflectY,
-            0x4 => Self::ReflectZ,
-            0x3 => Self::ReflectXY,
-            0x5 => Self::ReflectXZ,
-            0x6 => Self::ReflectYZ,
-            0x7 => Self::ReflectXYZ,
-            _ => unreachable!(),
-        }
+    // each scanner's beacons within the frame of scanner 0
+    fn reference_beacons(&self) -> Result<Vec<FxHashSet<Beacon>>> {
+        let solutions = self.scanner_solutions()?;
+        let beacons = self
+            .scanner_reports
+            .iter()
+            .zip(solutions.iter())
+            .map(|(beacons, solution)| {
+                beacons
+                    .iter()
+                    .map(|beacon| solution.transform_to_reference(beacon))
+                    .collect()
+            })
+            .collect();
+        Ok(beacons)
     }
-}
 
-pub struct Day19 {
-    scanner_reports: Vec<Vec<Vector3<i64>>>,
-    // note: need RefCell for interior mutability
-    scanner_positions: RefCell<HashMap<usize, Vector3<i64>>>,
-    scanner_rotations: RefCell<HashMap<usize, Rotation>>,
-    scanner_reflections: RefCell<HashMap<usize, Reflection>>,
-}
+    // the position and orientation of every scanner, with the beacons it shares with the others
+    pub fn scanner_reports(&self) -> Result<Vec<ScannerReport>> {
+        let solutions = self.scanner_solutions()?;
+        let beacons = self.reference_beacons()?;
+        let reports = solutions
+            .iter()
+            .enumerate()
+            .map(|(scanner, &solution)| {
+                let overlaps = beacons
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != scanner)
+                    .map(|(other, other_beacons)| {
+                        (other, beacons[scanner].intersection(other_beacons).count())
+                    })
+                    .filter(|&(_, n_beacons)| n_beacons > 0)
+                    .collect();
+                ScannerReport {
+                    scanner,
+                    solution,
+                    overlaps,
+                }
+            })
+            .collect();
+        Ok(reports)
+    }
 
-impl Day19 {
-    fn parse_vector(input: &'static str) -> Vector3<i64> {
-        Vector3::from_iterator(input.split(',').map(|n| n.parse().unwrap()))
+    fn combine_beacons(&self) -> Result<FxHashSet<Beacon>> {
+        Ok(self.reference_beacons()?.into_iter().flatten().collect())
     }
+}
 
-    pub fn new(input: &'static str) -> Self {
-        let scanner_reports = input
-            .split("\n\n")
-            .map(|scanner| {
+impl FromLines for Day19 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let scanner_reports = utils::sections(lines)
+            .into_iter()
+            .enumerate()
+            .map(|(i, scanner)| {
+                // note: the first line of each report only names the scanner
                 scanner
-                    .split('\n')
+                    .into_iter()
                     .skip(1)
                     .map(Self::parse_vector)
-                    .collect::<Vec<_>>()
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| format!("scanner {}", i))
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>>>()?;
 
-        Self {
+        Ok(Self {
             scanner_reports,
-            scanner_positions: RefCell::new(HashMap::new()),
-            scanner_rotations: RefCell::new(HashMap::new()),
-            scanner_reflections: RefCell::new(HashMap::new()),
-        }
+            scanner_solutions: OnceCell::new(),
+        })
     }
+}
 
-    fn square_distance(va: &Vector3<i64>, vb: &Vector3<i64>) -> i64 {
-        va.zip_map(vb, |x, y| (x - y).pow(2)).fold(0, |x, y| x + y)
+impl Puzzle for Day19 {
+    // Assemble the full map of beacons. How many beacons are there?
+    fn part_1(&self) -> Result<Solution> {
+        let beacons = self.combine_beacons()?;
+        if explain::is_enabled() {
+            for report in self.scanner_reports()? {
+                explain::report("solved", report);
+            }
+        }
+        Ok(beacons.len().into())
     }
 
-    fn manhattan_distance(va: &Vector3<i64>, vb: &Vector3<i64>) -> i64 {
-        va.zip_map(vb, |x, y| (x - y).abs()).fold(0, |x, y| x + y)
+    // What is the largest Manhattan distance between any two scanners?
+    fn part_2(&self) -> Result<Solution> {
+        let largest = self
+            .scanner_solutions()?
+            .iter()
+            .map(|solution| solution.position)
+            .tuple_combinations()
+            .map(|(pos_a, pos_b)| Self::manhattan_distance(&pos_a, &pos_b))
+            .max()
+            // note: a lone scanner is no distance from itself
+            .unwrap_or(0);
+        Ok(largest.into())
     }
 
-    fn square_distances(set: &[Vector3<i64>]) -> HashSet<i64> {
-        set.iter()
-            .tuple_combinations()
-            .map(|(ba, bb)| Self::square_distance(ba, bb))
-            .collect()
+    fn dump(&self) -> Option<String> {
+        let scanners = self
+            .scanner_reports
+            .iter()
+            .enumerate()
+            .map(|(i, beacons)| {
+                let lines = beacons
+                    .iter()
+                    .map(|beacon| format!("{},{},{}", beacon.x, beacon.y, beacon.z))
+                    .join("\n");
+                format!("scanner {} ({} beacons):\n{}", i, beacons.len(), lines)
+            })
+            .collect::<Vec<_>>();
+        Some(scanners.join("\n\n"))
     }
+}
 
-    fn set_scanner_position(&self, scanner: usize, position: Vector3<i64>) {
-        self.scanner_positions
-            .borrow_mut()
-            .insert(scanner, position);
-    }
-
-    fn set_scanner_rotation(&self, scanner: usize, rotation: Rotation) {
-        self.scanner_rotations
-            .borrow_mut()
-            .insert(scanner, rotation);
-    }
-
-    fn set_scanner_reflection(&self, scanner: usize, reflection: Reflection) {
-        self.scanner_reflections
-            .borrow_mut()
-            .insert(scanner, reflection);
-    }
-
-    #[allow(clippy::type_complexity)]
-    fn find_matching_beacon_pairs(
-        &self,
-        scanner_a: usize,
-        scanner_b: usize,
-    ) -> Result<(
-        (&Vector3<i64>, &Vector3<i64>),
-        (&Vector3<i64>, &Vector3<i64>),
-    )> {
-        // find a pair of beacons in each scanner with matching distances
-        let mut beacon_a1 = None;
-        let mut beacon_a2 = None;
-        let mut beacon_b1 = None;
-        let mut beacon_b2 = None;
-
-        'outer: for (ba1, ba2) in self.scanner_reports[scanner_a].iter().tuple_combinations() {
-            let dist_a = Self::square_distance(ba1, ba2);
-            for (bb1, bb2) in self.scanner_reports[scanner_b].iter().tuple_combinations() {
-                let dist_b = Self::square_distance(bb1, bb2);
-                if dist_a == dist_b {
-                    beacon_a1 = Some(ba1);
-                    beacon_a2 = Some(ba2);
-                    beacon_b1 = Some(bb1);
-                    beacon_b2 = Some(bb2);
-                    break 'outer;
-                }
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../../input/19.dbg.txt");
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day19::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(
+            err("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\nx,y"),
+            "scanner 1: invalid beacon \"x,y\""
+        );
+        assert_eq!(
+            err("--- scanner 0 ---\n1,2,a"),
+            "scanner 0: z: invalid digit found in string"
+        );
+    }
 
-        let beacon_a1 = beacon_a1.ok_or(PuzzleError::NoSolution)?;
-        let beacon_a2 = beacon_a2.ok_or(PuzzleError::NoSolution)?;
-        let beacon_b1 = beacon_b1.ok_or(PuzzleError::NoSolution)?;
-        let beacon_b2 = beacon_b2.ok_or(PuzzleError::NoSolution)?;
-        Ok(((beacon_a1, beacon_a2), (beacon_b1, beacon_b2)))
-    }
-
-    fn solve_scanners_for_rotation(
-        beacon_a1: &Vector3<i64>,
-        beacon_a2: &Vector3<i64>,
-        beacon_b1: &Vector3<i64>,
-        beacon_b2: &Vector3<i64>,
-    ) -> Result<Option<(Vector3<i64>, Reflection)>> {
-        // check if the rotation is correct but a reflection is needed
-        let a = beacon_a2 - beacon_a1;
-        let b = beacon_b2 - beacon_b1;
-        if let Some(reflection) = Reflection::solve_for_reflection(&a, &b) {
-            // apply the reflection to the B beacons
-            let beacon_b1 = reflection.reflect(beacon_b1);
-            let beacon_b2 = reflection.reflect(beacon_b2);
-
-            // compare differences between the points, accounting for different endpoints
-            let loc_a = beacon_a1 - beacon_b1;
-            let loc_b = beacon_a2 - beacon_b2;
-            let loc_c = beacon_a1 - beacon_b2;
-            let loc_d = beacon_a2 - beacon_b1;
-            if loc_a == loc_b {
-                Ok(Some((loc_a, reflection)))
-            } else if loc_c == loc_d {
-                Ok(Some((loc_c, reflection)))
-            } else {
-                // nothing found, incorrect rotation
-                // NOTE: this should probably never be hit...
-                Ok(None)
-            }
-        } else {
-            // incorrect rotation
-            Ok(None)
+    #[test]
+    fn test_orientations() {
+        let orientations = orientations();
+        assert_eq!(orientations.len(), 24);
+        assert_eq!(orientations.iter().unique().count(), 24);
+        // the rotations form a group, so composing any two yields another
+        for (a, b) in orientations.iter().cartesian_product(orientations.iter()) {
+            assert!(orientations.contains(&(a * b)));
         }
     }
 
-    fn solve_scanners(&self, scanner_a: usize, scanner_b: usize) -> Result<()> {
-        // figure out which scanner has already been solved
-        // sa is solved, sb is unknown
-        let (sa, sb) = if self.scanner_positions.borrow().contains_key(&scanner_a) {
-            (scanner_a, scanner_b)
-        } else {
-            (scanner_b, scanner_a)
+    #[test]
+    fn test_scanner_solution_inverse() {
+        let solution = ScannerSolution {
+            position: Beacon::new(68, -1246, -43),
+            orientation: orientations()[5],
         };
-        println!("DEBUG: solving scanner {} using scanner {}", sb, sa);
-        // grab the position/rotation/reflection of the already-solved scanner
-        let a_pos = self.scanner_positions.borrow()[&sa];
-        let a_rot = self.scanner_rotations.borrow()[&sa];
-        let a_rfl = self.scanner_reflections.borrow()[&sa];
-        println!("DEBUG: scanner {} position is {:?}", sa, a_pos);
-        println!("DEBUG: scanner {} rotation is {:?}", sa, a_rot);
-        println!("DEBUG: scanner {} reflection is {:?}", sa, a_rfl);
-
-        // find a pair of beacons in each scanner with matching distances
-        let ((ba1, ba2), (bb1, bb2)) = self.find_matching_beacon_pairs(sa, sb)?;
-
-        // try all rotations to find a working orientation
-        for rot in ROTATIONS.iter() {
-            println!("DEBUG: using rotation {:?}", rot);
-            let rot_bb1 = rot.rotate(bb1);
-            let rot_bb2 = rot.rotate(bb2);
-            if let Some((pos, rfl)) =
-                Self::solve_scanners_for_rotation(ba1, ba2, &rot_bb1, &rot_bb2)?
-            {
-                println!(
-                    "DEBUG: initial solve at {:?} using rotation {:?} and reflection {:?}",
-                    pos, rot, rfl
-                );
-                // apply the position/rotation/reflection from the previously-solved scanner
-                // NOTE: need to UN-rotate relative to the solved scanner
-                let pos = a_pos + a_rfl.reflect(&a_rot.unrotate(&pos));
-                println!(
-                    "DEBUG: solved scanner {} at {:?} with rotation {:?} and reflection {:?}",
-                    sb, pos, rot, rfl
-                );
-                self.set_scanner_position(sb, pos);
-                // TODO: might need to do some composition of rotations
-                self.set_scanner_rotation(sb, *rot);
-                self.set_scanner_reflection(sb, rfl);
-                break;
-            }
-        }
-
-        Ok(())
+        let v = Beacon::new(-618, -824, -621);
+        let there_and_back = solution
+            .inverse()
+            .transform_to_reference(&solution.transform_to_reference(&v));
+        assert_eq!(there_and_back, v);
+        assert_eq!(
+            solution.compose(&solution.inverse()),
+            ScannerSolution::identity()
+        );
     }
 
-    fn combine_beacons(&self) -> HashSet<Vector3<i64>> {
-        let mut beacons = HashSet::new();
-
-        for (i, scanner_beacons) in self.scanner_reports.iter().enumerate() {
-            let pos = self.scanner_positions.borrow()[&i];
-            let rot = self.scanner_rotations.borrow()[&i];
-            let rfl = self.scanner_reflections.borrow()[&i];
+    #[test]
+    fn test_scanner_solution_compose() {
+        let orientations = orientations();
+        let a = ScannerSolution {
+            position: Beacon::new(1, 2, 3),
+            orientation: orientations[7],
+        };
+        let b = ScannerSolution {
+            position: Beacon::new(-40, 50, 60),
+            orientation: orientations[19],
+        };
+        // composing is the same as mapping through B's frame and then A's
+        let v = Beacon::new(5, -6, 7);
+        assert_eq!(
+            a.compose(&b).transform_to_reference(&v),
+            a.transform_to_reference(&b.transform_to_reference(&v))
+        );
+    }
 
-            for beacon in scanner_beacons.iter() {
-                let b_pos = beacon + rfl.reflect(&rot.unrotate(&pos));
-                beacons.insert(b_pos);
-            }
-        }
+    #[test]
+    fn test_fingerprint_correspondences() {
+        let day = Day19::new(TEST_INPUT).unwrap();
+        let fp_0 = Fingerprint::new(&day.scanner_reports[0]);
+        let fp_1 = Fingerprint::new(&day.scanner_reports[1]);
+        assert!(fp_0.common_distances(&fp_1) >= 66);
 
-        beacons
+        // the example documents that scanner 0's 404,-588,-901 is scanner 1's -336,658,858
+        let i = day.scanner_reports[0]
+            .iter()
+            .position(|b| *b == Beacon::new(404, -588, -901))
+            .unwrap();
+        let j = day.scanner_reports[1]
+            .iter()
+            .position(|b| *b == Beacon::new(-336, 658, 858))
+            .unwrap();
+        let correspondences = fp_0.correspondences(&fp_1);
+        assert!(correspondences.len() >= MIN_OVERLAP);
+        assert!(correspondences.contains(&(i, j)));
     }
-}
 
-impl Puzzle for Day19 {
-    // Assemble the full map of beacons. How many beacons are there?
-    fn part_1(&self) -> Result<Solution> {
-        // get the squared distances between all beacons for each scanner report
-        let square_distances = self
-            .scanner_reports
+    #[test]
+    fn test_scanner_positions() {
+        let day = Day19::new(TEST_INPUT).unwrap();
+        let solutions = day.scanner_solutions().unwrap();
+        let positions = solutions
             .iter()
-            .map(|scanner| Self::square_distances(scanner.as_slice()))
+            .map(|solution| solution.position)
             .collect::<Vec<_>>();
+        let expected = [
+            Beacon::new(0, 0, 0),
+            Beacon::new(68, -1246, -43),
+            Beacon::new(1105, -1205, 1229),
+            Beacon::new(-92, -2380, -20),
+            Beacon::new(-20, -1133, 1061),
+        ];
+        assert_eq!(positions, expected);
+
+        // the example documents that scanner 1's -336,658,858 is scanner 0's 404,-588,-901
+        assert_eq!(
+            solutions[1].transform_to_reference(&Beacon::new(-336, 658, 858)),
+            Beacon::new(404, -588, -901)
+        );
+    }
 
-        // find scanners that can see the same beacons
-        // treat the beacons as a complete graph so need n * (n - 1) / 2 overlaps
-        // for n=12 this is 66
-        let n_common = 66;
-        let overlaps = square_distances
+    #[test]
+    fn test_scanner_reports() {
+        let day = Day19::new(TEST_INPUT).unwrap();
+        let reports = day.scanner_reports().unwrap();
+        // the example documents the overlapping pairs, each of which shares 12 beacons
+        let overlaps = reports
             .iter()
-            .enumerate()
-            .tuple_combinations()
-            .filter(|((_, dists_a), (_, dists_b))| {
-                dists_a.intersection(dists_b).count() >= n_common
+            .flat_map(|report| {
+                report
+                    .overlaps
+                    .iter()
+                    .filter(|&&(_, n_beacons)| n_beacons >= MIN_OVERLAP)
+                    .map(move |&(other, _)| (report.scanner, other))
             })
-            .map(|((i, _), (j, _))| (i, j))
+            .filter(|(a, b)| a < b)
             .collect::<Vec<_>>();
+        assert_eq!(overlaps, vec![(0, 1), (1, 3), (1, 4), (2, 4)]);
+        assert_eq!(
+            reports[1].to_string(),
+            "scanner 1 at 68,-1246,-43 facing -x,+y,-z, overlapping 0 (12), 2 (6), 3 (12), 4 (12)"
+        );
+    }
 
-        // use the first scanner as the base reference
-        self.set_scanner_position(0, Vector3::from_element(0));
-        self.set_scanner_rotation(0, Rotation::Identity);
-        self.set_scanner_reflection(0, Reflection::Identity);
+    #[test]
+    fn test_example() {
+        let day = Day19::new(TEST_INPUT).unwrap();
+        assert_eq!(day.part_1().unwrap().to_string(), "79");
+        assert_eq!(day.part_2().unwrap().to_string(), "3621");
+    }
 
-        // solve remaining scanners
-        for (scanner_a, scanner_b) in overlaps.iter() {
-            self.solve_scanners(*scanner_a, *scanner_b)?;
-        }
+    #[test]
+    fn test_part_2_alone() {
+        let day = Day19::new(TEST_INPUT).unwrap();
+        assert_eq!(day.part_2().unwrap().to_string(), "3621");
+    }
 
-        // combine the beacons using the scanner solutions
-        let beacons = self.combine_beacons();
-        // FIXME: currently broken...
-        Ok(beacons.len().into())
+    #[test]
+    fn test_single_scanner() {
+        let day = Day19::new("--- scanner 0 ---\n404,-588,-901\n528,-643,409").unwrap();
+        assert_eq!(day.part_1().unwrap().to_string(), "2");
+        assert_eq!(day.part_2().unwrap().to_string(), "0");
     }
 
-    // What is the largest Manhattan distance between any two scanners?
-    fn part_2(&self) -> Result<Solution> {
-        let largest = self
-            .scanner_positions
-            .borrow()
-            .iter()
-            .enumerate()
-            .tuple_combinations()
-            .map(|((_, (_, pos_a)), (_, (_, pos_b)))| Self::manhattan_distance(pos_a, pos_b))
-            .max()
-            .unwrap();
-        Ok(largest.into())
+    #[test]
+    fn test_dump() {
+        let dump = Day19::new(TEST_INPUT).unwrap().dump().unwrap();
+        assert!(dump.starts_with("scanner 0 (25 beacons):\n404,-588,-901\n"));
+        assert_eq!(dump.matches("scanner").count(), 5);
     }
 }
@@ -3,8 +3,7 @@
 ** https://adventofcode.com/2021/day/2
 */
 
-use crate::types::{Puzzle, Result, Solution};
-use crate::utils;
+use crate::types::{Context, FromLines, Puzzle, Result, Solution};
 
 enum Direction {
     Forward,
@@ -12,13 +11,13 @@ enum Direction {
     Down,
 }
 
-impl From<&str> for Direction {
-    fn from(s: &str) -> Self {
+impl Direction {
+    fn parse(s: &str) -> Result<Self> {
         match s {
-            "forward" => Self::Forward,
-            "up" => Self::Up,
-            "down" => Self::Down,
-            _ => panic!("invalid direction: {}", s),
+            "forward" => Ok(Self::Forward),
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            _ => Err(format!("invalid direction {:?}", s).into()),
         }
     }
 }
@@ -28,15 +27,15 @@ struct Command {
     unit: u64,
 }
 
-impl From<&str> for Command {
-    fn from(s: &str) -> Self {
+impl Command {
+    fn parse(s: &str) -> Result<Self> {
         match split!(s, ' ') {
             [dir_str, unit_str] => {
-                let direction = Direction::from(*dir_str);
-                let unit = unit_str.parse().unwrap();
-                Self { direction, unit }
+                let direction = Direction::parse(dir_str)?;
+                let unit = unit_str.parse().context("units")?;
+                Ok(Self { direction, unit })
             }
-            _ => panic!("invalid command: {}", s),
+            _ => Err(format!("invalid command {:?}", s).into()),
         }
     }
 }
@@ -81,10 +80,22 @@ pub struct Day2 {
 }
 
 impl Day2 {
-    pub fn new(input: &'static str) -> Self {
-        let commands = utils::input_to_lines(input).map(Command::from).collect();
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+}
 
-        Self { commands }
+impl FromLines for Day2 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let commands = lines
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(i, line)| Command::parse(line).with_context(|| format!("command {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(Self { commands })
     }
 }
 
@@ -115,3 +126,22 @@ impl Puzzle for Day2 {
         Ok((navigator.position * navigator.depth).into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day2::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(
+            err("forward 5\nsideways 3"),
+            "command 2: invalid direction \"sideways\""
+        );
+        assert_eq!(
+            err("up x"),
+            "command 1: units: invalid digit found in string"
+        );
+        assert_eq!(err("x,y\n\nzz 3"), "command 1: invalid command \"x,y\"");
+    }
+}
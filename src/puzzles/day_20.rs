@@ -3,10 +3,15 @@
 ** https://adventofcode.com/2021/day/20
 */
 
-use crate::types::{Puzzle, Result, Solution};
+use crate::types::{
+    grid, Context, Coord, FromLines, GridError, Puzzle, Result, Simulation, Solution,
+};
+use crate::utils;
+use crate::viz::{Frame, Visualize};
+
+use std::iter;
 
 const IMG_ENH_ALG_SIZE: usize = 512;
-const INPUT_SIZE: usize = 100;
 
 #[derive(Debug, Clone, Copy)]
 enum Pixel {
@@ -14,12 +19,12 @@ enum Pixel {
     Light,
 }
 
-impl From<char> for Pixel {
-    fn from(c: char) -> Self {
+impl Pixel {
+    fn parse(c: char) -> Option<Self> {
         match c {
-            '.' => Self::Dark,
-            '#' => Self::Light,
-            _ => unreachable!(),
+            '.' => Some(Self::Dark),
+            '#' => Some(Self::Light),
+            _ => None,
         }
     }
 }
@@ -32,49 +37,77 @@ impl Algorithm {
     fn get(&self, n: u16) -> Pixel {
         self.string[n as usize]
     }
+
+    // splits the algorithm into a 2-input function of the two lowest window bits for each value
+    // of the 7 highest window bits; each function is encoded as its 4-entry truth table
+    fn low_bit_functions(&self) -> [u8; 128] {
+        let mut functions = [0; 128];
+        for (prefix, function) in functions.iter_mut().enumerate() {
+            for low_bits in 0..4 {
+                if let Pixel::Light = self.string[(prefix << 2) | low_bits] {
+                    *function |= 1 << low_bits;
+                }
+            }
+        }
+        functions
+    }
 }
 
-impl From<&'static str> for Algorithm {
-    fn from(s: &'static str) -> Self {
+impl Algorithm {
+    fn parse(s: &str) -> Result<Self> {
+        let n_chars = s.chars().count();
+        if n_chars != IMG_ENH_ALG_SIZE {
+            return Err(format!("expected {} pixels, found {}", IMG_ENH_ALG_SIZE, n_chars).into());
+        }
         let mut string = [Pixel::Dark; IMG_ENH_ALG_SIZE];
         for (i, c) in s.chars().enumerate() {
-            string[i] = Pixel::from(c);
+            string[i] = Pixel::parse(c).ok_or_else(|| format!("invalid pixel {:?}", c))?;
         }
 
-        Self { string }
+        Ok(Self { string })
     }
 }
 
 #[derive(Clone)]
 struct Image {
     pixels: Vec<Vec<Pixel>>,
-    size: usize,
+    width: usize,
+    height: usize,
+    // the pixel which fills the rest of the infinite image
+    background: Pixel,
 }
 
 impl Image {
-    fn blank(size: usize) -> Self {
-        let pixels = vec![vec![Pixel::Dark; size]; size];
-        Self { pixels, size }
+    fn blank(width: usize, height: usize, background: Pixel) -> Self {
+        let pixels = vec![vec![background; width]; height];
+        Self {
+            pixels,
+            width,
+            height,
+            background,
+        }
     }
 
-    fn from_string(s: &'static str, size: usize) -> Self {
-        let mut pixels = Vec::with_capacity(size);
-
-        for row in s.split_whitespace() {
-            let mut pixel_row = Vec::with_capacity(size);
-            for c in row.chars() {
-                pixel_row.push(Pixel::from(c));
-            }
-            pixels.push(pixel_row);
-        }
+    fn from_string(s: &str) -> std::result::Result<Self, GridError> {
+        let (width, height, cells) = grid::parse_chars(s, Pixel::parse)?;
+        let pixels = cells.chunks(width).map(|row| row.to_vec()).collect();
 
-        Self { pixels, size }
+        Ok(Self {
+            pixels,
+            width,
+            height,
+            background: Pixel::Dark,
+        })
     }
 
     fn pad(&self, padding: usize) -> Self {
-        let mut output = Self::blank(self.size + (padding * 2));
-        for (i, j) in itertools::iproduct!(0..self.size, 0..self.size) {
-            output.pixels[i + padding][j + padding] = self.pixels[i][j];
+        let mut output = Self::blank(
+            self.width + (padding * 2),
+            self.height + (padding * 2),
+            self.background,
+        );
+        for Coord { row, col } in Coord::iter_rect(self.width, self.height) {
+            output.pixels[row + padding][col + padding] = self.pixels[row][col];
         }
         output
     }
@@ -83,32 +116,45 @@ impl Image {
         self.pixels[i][j] = pixel;
     }
 
-    fn lit_pixels(&self) -> usize {
-        self.pixels
-            .iter()
-            .map(|row| row.iter().filter(|p| matches!(p, Pixel::Light)).count())
-            .sum()
+    // note: a lit background means infinitely many lit pixels
+    fn lit_pixels(&self) -> Option<usize> {
+        match self.background {
+            Pixel::Dark => Some(
+                self.pixels
+                    .iter()
+                    .map(|row| row.iter().filter(|p| matches!(p, Pixel::Light)).count())
+                    .sum(),
+            ),
+            Pixel::Light => None,
+        }
     }
 
-    fn get_or(&self, i: usize, j: usize, di: i64, dj: i64, or: Pixel) -> Pixel {
+    fn get(&self, i: usize, j: usize, di: i64, dj: i64) -> Pixel {
         // passed as usize for better interface
         let i = i as i64;
         let j = j as i64;
 
-        let i_in_range = i + di >= 0 && i + di < self.size as i64;
-        let j_in_range = j + dj >= 0 && j + dj < self.size as i64;
+        let i_in_range = i + di >= 0 && i + di < self.height as i64;
+        let j_in_range = j + dj >= 0 && j + dj < self.width as i64;
 
         if i_in_range && j_in_range {
             self.pixels[(i + di) as usize][(j + dj) as usize]
         } else {
-            or
+            self.background
         }
     }
 
-    fn window(&self, i: usize, j: usize, default_pixel: Pixel) -> u16 {
+    fn frame(&self) -> Frame {
+        Frame::from_fn(self.width, self.height, |i, j| match self.pixels[i][j] {
+            Pixel::Dark => 0,
+            Pixel::Light => 255,
+        })
+    }
+
+    fn window(&self, i: usize, j: usize) -> u16 {
         let mut n = 0;
         for (offset, (di, dj)) in itertools::enumerate(itertools::iproduct!(-1..=1, -1..=1)) {
-            if let Pixel::Light = self.get_or(i, j, di, dj, default_pixel) {
+            if let Pixel::Light = self.get(i, j, di, dj) {
                 n |= 1 << (8 - offset);
             };
         }
@@ -116,35 +162,158 @@ impl Image {
     }
 }
 
+// an image with each row packed into 64-bit words, such that the enhancement algorithm can be
+// applied to 64 pixels at once
+#[derive(Clone)]
+struct BitImage {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    // note: bits past the width of a row hold the background, so shifting them in is correct
+    words: Vec<u64>,
+    background: bool,
+}
+
+impl BitImage {
+    fn fill(background: bool) -> u64 {
+        if background {
+            u64::MAX
+        } else {
+            0
+        }
+    }
+
+    fn row(&self, i: usize) -> &[u64] {
+        &self.words[(i * self.words_per_row)..((i + 1) * self.words_per_row)]
+    }
+
+    // sets the bits past the width of each row to the background
+    fn fill_margin(&mut self) {
+        let used_bits = self.width % 64;
+        if used_bits == 0 {
+            return;
+        }
+        let margin = u64::MAX << used_bits;
+        for i in 0..self.height {
+            let word = &mut self.words[((i + 1) * self.words_per_row) - 1];
+            *word = (*word & !margin) | (Self::fill(self.background) & margin);
+        }
+    }
+
+    fn lit_pixels(&self) -> Option<usize> {
+        if self.background {
+            return None;
+        }
+        // note: the margin holds the dark background, so it does not contribute
+        Some(self.words.iter().map(|w| w.count_ones() as usize).sum())
+    }
+
+    // the row shifted such that each bit holds its left neighbor, itself, and its right neighbor
+    fn neighbors(&self, row: &[u64], w: usize) -> [u64; 3] {
+        let fill = Self::fill(self.background);
+        let prev = if w > 0 { row[w - 1] } else { fill };
+        let next = if w + 1 < row.len() { row[w + 1] } else { fill };
+        [
+            (row[w] << 1) | (prev >> 63),
+            row[w],
+            (row[w] >> 1) | (next << 63),
+        ]
+    }
+}
+
+impl From<&Image> for BitImage {
+    fn from(image: &Image) -> Self {
+        let words_per_row = image.width.div_ceil(64).max(1);
+        let background = matches!(image.background, Pixel::Light);
+        let mut words = vec![0; words_per_row * image.height];
+        for (i, row) in image.pixels.iter().enumerate() {
+            for (j, pixel) in row.iter().enumerate() {
+                if let Pixel::Light = pixel {
+                    words[(i * words_per_row) + (j / 64)] |= 1 << (j % 64);
+                }
+            }
+        }
+
+        let mut bit_image = Self {
+            width: image.width,
+            height: image.height,
+            words_per_row,
+            words,
+            background,
+        };
+        bit_image.fill_margin();
+        bit_image
+    }
+}
+
+// the bitwise enhancement of an image, one round per step
+struct Enhancement<'a> {
+    algorithm: &'a Algorithm,
+    functions: [u8; 128],
+    image: BitImage,
+}
+
+impl Simulation for Enhancement<'_> {
+    // applies a single round of the enhancement algorithm
+    fn step(&mut self) {
+        let image = &self.image;
+        let background = match image.background {
+            false => self.algorithm.get(0),
+            true => self.algorithm.get(0x1FF),
+        };
+        let mut output = image.clone();
+        output.background = matches!(background, Pixel::Light);
+
+        let fill_row = vec![BitImage::fill(image.background); image.words_per_row];
+        for i in 0..image.height {
+            let above = if i > 0 { image.row(i - 1) } else { &fill_row };
+            let below = if i + 1 < image.height {
+                image.row(i + 1)
+            } else {
+                &fill_row
+            };
+            let rows = [above, image.row(i), below];
+
+            for w in 0..image.words_per_row {
+                // window bits run from the top-left (bit 8) to the bottom-right (bit 0)
+                let mut planes = [0; 9];
+                for (r, row) in rows.iter().enumerate() {
+                    for (c, plane) in image.neighbors(row, w).into_iter().enumerate() {
+                        planes[8 - ((r * 3) + c)] = plane;
+                    }
+                }
+                output.words[(i * image.words_per_row) + w] =
+                    Day20::apply_bitwise(&self.functions, &planes);
+            }
+        }
+
+        output.fill_margin();
+        self.image = output;
+    }
+}
+
 pub struct Day20 {
     algorithm: Algorithm,
     image: Image,
 }
 
 impl Day20 {
-    pub fn new(input: &'static str) -> Self {
-        split_into!(input, "\n\n", alg_str, img_str);
-
-        let algorithm = Algorithm::from(alg_str);
-        let image = Image::from_string(img_str, INPUT_SIZE);
-
-        Self { algorithm, image }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn process_image_single_round(&self, image: Image, round: usize) -> Image {
-        let mut output = Image::blank(image.size);
-        // from observing the algorithm, an all-dark window results in a light
-        // pixel and an all-light window results in a dark pixel, so alternate
-        // between the two for the "infinite" region
-        let default_pixel = if round % 2 == 0 {
-            Pixel::Dark
-        } else {
-            Pixel::Light
+    fn process_image_single_round(&self, image: Image) -> Image {
+        // every pixel of the infinite background sees a window of all-background pixels, i.e.
+        // index 0 when dark and index 511 when light
+        let background = match image.background {
+            Pixel::Dark => self.algorithm.get(0),
+            Pixel::Light => self.algorithm.get(0x1FF),
         };
+        let mut output = Image::blank(image.width, image.height, background);
 
-        for (i, j) in itertools::iproduct!(0..image.size, 0..image.size) {
-            let index = image.window(i, j, default_pixel);
-            output.set(i, j, self.algorithm.get(index));
+        for Coord { row, col } in Coord::iter_rect(image.width, image.height) {
+            let index = image.window(row, col);
+            output.set(row, col, self.algorithm.get(index));
         }
 
         output
@@ -152,11 +321,99 @@ impl Day20 {
 
     fn process_image(&self, image: Image, n_rounds: usize) -> Image {
         let mut output = image;
-        for round in 0..n_rounds {
-            output = self.process_image_single_round(output, round);
+        for _ in 0..n_rounds {
+            output = self.process_image_single_round(output);
         }
         output
     }
+
+    // evaluates the algorithm for 64 windows at once, given the window bits as bit planes such
+    // that bit k of planes[n] is bit n of the window of pixel k
+    fn apply_bitwise(low_bit_functions: &[u8; 128], planes: &[u64; 9]) -> u64 {
+        let (b0, b1) = (planes[0], planes[1]);
+        // every 2-input function of the lowest window bits, indexed by its truth table
+        let minterms = [!b1 & !b0, !b1 & b0, b1 & !b0, b1 & b0];
+        let mut functions = [0; 16];
+        for table in 1..16usize {
+            let lowest = table.trailing_zeros() as usize;
+            functions[table] = functions[table & (table - 1)] | minterms[lowest];
+        }
+
+        // then select between them on each of the higher window bits in turn
+        let mut values = [0; 128];
+        for (value, &function) in values.iter_mut().zip(low_bit_functions.iter()) {
+            *value = functions[function as usize];
+        }
+        let mut len = values.len();
+        for plane in planes.iter().skip(2) {
+            len /= 2;
+            for i in 0..len {
+                values[i] = (plane & values[(2 * i) + 1]) | (!plane & values[2 * i]);
+            }
+        }
+        values[0]
+    }
+
+    // applies the enhancement algorithm n_rounds times and counts the lit pixels
+    pub fn enhanced(&self, n_rounds: usize) -> Result<usize> {
+        // each round can only spread the image by a single pixel into the infinite background,
+        // which is tracked separately, so one pixel of padding per round is sufficient
+        let mut enhancement = Enhancement {
+            algorithm: &self.algorithm,
+            functions: self.algorithm.low_bit_functions(),
+            image: BitImage::from(&self.image.pad(n_rounds)),
+        };
+        enhancement.run(n_rounds);
+        let lit = enhancement
+            .image
+            .lit_pixels()
+            .ok_or("infinitely many pixels are lit")?;
+        Ok(lit)
+    }
+
+    // the reference implementation of enhanced, one pixel at a time
+    pub fn enhanced_scalar(&self, n_rounds: usize) -> Result<usize> {
+        let input = self.image.pad(n_rounds);
+        let output = self.process_image(input, n_rounds);
+        let lit = output
+            .lit_pixels()
+            .ok_or("infinitely many pixels are lit")?;
+        Ok(lit)
+    }
+}
+
+impl Visualize for Day20 {
+    // the image after each of the 50 enhancement rounds, padded up front so that every frame
+    // has the same size
+    fn frames(&self) -> impl Iterator<Item = Frame> {
+        let n_rounds = 50;
+        let image = self.image.pad(n_rounds);
+        iter::successors(Some(image), |image| {
+            Some(self.process_image_single_round(image.clone()))
+        })
+        .take(n_rounds + 1)
+        .map(|image| image.frame())
+    }
+}
+
+impl FromLines for Day20 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        match utils::sections(lines).as_slice() {
+            [alg_lines, img_lines] => {
+                let algorithm = Algorithm::parse(&alg_lines.concat()).context("algorithm")?;
+                let image = Image::from_string(&img_lines.join("\n"))?;
+                Ok(Self { algorithm, image })
+            }
+            sections => Err(format!(
+                "expected an algorithm and an image, found {} sections",
+                sections.len()
+            )
+            .into()),
+        }
+    }
 }
 
 impl Puzzle for Day20 {
@@ -164,21 +421,108 @@ impl Puzzle for Day20 {
     // algorithm twice, being careful to account for the infinite size of the
     // images. How many pixels are lit in the resulting image?
     fn part_1(&self) -> Result<Solution> {
-        let n_rounds = 2;
-        // add sufficient padding to simulate the "infinite" image
-        let input = self.image.pad(n_rounds * 2);
-        let output = self.process_image(input, n_rounds);
-        Ok(output.lit_pixels().into())
+        Ok(self.enhanced(2)?.into())
     }
 
     // Start again with the original input image and apply the image
     // enhancement algorithm 50 times. How many pixels are lit in the
     // resulting image?
     fn part_2(&self) -> Result<Solution> {
-        let n_rounds = 50;
-        // add sufficient padding to simulate the "infinite" image
-        let input = self.image.pad(n_rounds * 2);
-        let output = self.process_image(input, n_rounds);
-        Ok(output.lit_pixels().into())
+        Ok(self.enhanced(50)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../../input/20.dbg.txt");
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day20::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(
+            err("x,y\n\nzz 3"),
+            "algorithm: expected 512 pixels, found 3"
+        );
+        let algorithm = ".".repeat(IMG_ENH_ALG_SIZE - 1);
+        assert_eq!(
+            err(&format!("{}x\n\n#.\n.#", algorithm)),
+            "algorithm: invalid pixel 'x'"
+        );
+        assert!(Day20::from_lines(format!("{}#\n\n#.\nzz", algorithm).lines()).is_err());
+    }
+
+    #[test]
+    fn test_image_size() {
+        let day = Day20::new(TEST_INPUT).unwrap();
+        assert_eq!(day.image.width, 5);
+        assert_eq!(day.image.height, 5);
+    }
+
+    #[test]
+    fn test_example() {
+        // the example algorithm maps an all-dark window to a dark pixel, so the background never
+        // lights up
+        let day = Day20::new(TEST_INPUT).unwrap();
+        assert!(matches!(day.algorithm.get(0), Pixel::Dark));
+        assert_eq!(day.part_1().unwrap().to_string(), "35");
+        assert_eq!(day.part_2().unwrap().to_string(), "3351");
+    }
+
+    #[test]
+    fn test_enhanced() {
+        let day = Day20::new(TEST_INPUT).unwrap();
+        assert_eq!(day.enhanced(0).unwrap(), 10);
+        assert_eq!(day.enhanced(1).unwrap(), 24);
+        assert_eq!(day.enhanced(2).unwrap(), 35);
+    }
+
+    #[test]
+    fn test_enhanced_bitwise() {
+        // an algorithm which lights the background on odd rounds exercises the margin handling
+        let mut algorithm = TEST_INPUT.split("\n\n").next().unwrap().to_string();
+        algorithm.replace_range(0..1, "#");
+        algorithm.replace_range(511..512, ".");
+        let image = TEST_INPUT.split("\n\n").nth(1).unwrap();
+        let flipping = Box::leak(format!("{}\n\n{}", algorithm, image).into_boxed_str());
+
+        for input in [TEST_INPUT, flipping] {
+            let day = Day20::new(input).unwrap();
+            for n_rounds in [0, 1, 2, 5, 50, 70] {
+                assert_eq!(
+                    day.enhanced(n_rounds).ok(),
+                    day.enhanced_scalar(n_rounds).ok()
+                );
+            }
+        }
+    }
+
+    // run with: cargo test --release bench_ -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_enhancement_implementations() {
+        use std::time::Instant;
+
+        let iterations = 10;
+        let day = Day20::new(TEST_INPUT).unwrap();
+        let start = Instant::now();
+        for _ in 0..iterations {
+            assert_eq!(day.enhanced_scalar(50).unwrap(), 3351);
+        }
+        let scalar_time = start.elapsed() / iterations;
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            assert_eq!(day.enhanced(50).unwrap(), 3351);
+        }
+        let bitwise_time = start.elapsed() / iterations;
+
+        println!(
+            "scalar: {:?} bitwise: {:?} ({:.1}x)",
+            scalar_time,
+            bitwise_time,
+            scalar_time.as_secs_f64() / bitwise_time.as_secs_f64()
+        );
     }
 }
@@ -3,10 +3,13 @@
 ** https://adventofcode.com/2021/day/21
 */
 
-use crate::types::{Puzzle, Result, Solution};
+use crate::explain;
+use crate::types::{
+    Context, FromLines, Memo, Puzzle, Replacement, Result, Solution, TranspositionTable,
+};
 
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone)]
 enum Player {
@@ -23,15 +26,70 @@ impl Player {
     }
 }
 
+// parameters describing a variant of the dice game
+#[derive(Debug, Clone, Copy)]
+pub struct GameRules {
+    // number of spaces around the circular track
+    pub board_size: u32,
+    pub die_faces: u32,
+    pub rolls_per_turn: u32,
+    pub win_score: u32,
+}
+
+impl GameRules {
+    // the practice game, played with a deterministic 100-sided die
+    pub const PRACTICE: Self = Self {
+        board_size: 10,
+        die_faces: 100,
+        rolls_per_turn: 3,
+        win_score: 1000,
+    };
+
+    // the quantum game, played with the 3-sided Dirac die
+    pub const DIRAC: Self = Self {
+        board_size: 10,
+        die_faces: 3,
+        rolls_per_turn: 3,
+        win_score: 21,
+    };
+
+    // maps each possible total of a turn's rolls to the number of roll sequences producing it;
+    // reduces branching by combining dice roll permutations whose sums are equal
+    // note: ordered, such that the universes are always explored in the same order
+    fn moveset(&self) -> BTreeMap<u32, u64> {
+        let mut moveset = BTreeMap::from([(0, 1)]);
+        for _ in 0..self.rolls_per_turn {
+            let mut next = BTreeMap::new();
+            for (total, n) in moveset.iter() {
+                for face in 1..=self.die_faces {
+                    *next.entry(total + face).or_insert(0) += n;
+                }
+            }
+            moveset = next;
+        }
+        moveset
+    }
+}
+
+// slots in the transposition table of the memoized quantum game, around a third of the states of
+// the standard game, beyond which memory stays bounded at the cost of solving some states again
+const TABLE_SIZE: usize = 1 << 14;
+
+// a state of the quantum game between turns: the position and score of the player about to move,
+// then the position and score of the other player
+pub type GameState = (u32, u32, u32, u32);
+
 #[derive(Debug)]
 struct DeterministicDice {
+    faces: u32,
     counter: u32,
     rolls: u32,
 }
 
 impl DeterministicDice {
-    fn new() -> Self {
+    fn new(faces: u32) -> Self {
         Self {
+            faces,
             counter: 1,
             rolls: 0,
         }
@@ -39,18 +97,17 @@ impl DeterministicDice {
 
     fn roll_single(&mut self) -> u32 {
         let output = self.counter;
-        // advance the counter, rolling over to 1 at 100
+        // advance the counter, rolling over to 1 after the last face
         self.counter += 1;
-        if self.counter > 100 {
+        if self.counter > self.faces {
             self.counter = 1;
         }
         self.rolls += 1;
         output
     }
 
-    fn roll(&mut self) -> u32 {
-        // roll the dice 3 times
-        self.roll_single() + self.roll_single() + self.roll_single()
+    fn roll(&mut self, n_rolls: u32) -> u32 {
+        (0..n_rolls).map(|_| self.roll_single()).sum()
     }
 }
 
@@ -60,18 +117,26 @@ struct DiracDiceGame {
     p2_pos: u32,
     p1_score: u32,
     p2_score: u32,
+    board_size: u32,
     win_score: u32,
     curr_player: Player,
 }
 
 impl DiracDiceGame {
-    fn new(p1_pos: u32, p2_pos: u32, win_score: u32) -> Self {
+    fn new(p1_pos: u32, p2_pos: u32, rules: &GameRules) -> Self {
+        Self::from_state((p1_pos, 0, p2_pos, 0), rules)
+    }
+
+    // note: the player about to move is player 1
+    fn from_state(state: GameState, rules: &GameRules) -> Self {
+        let (p1_pos, p1_score, p2_pos, p2_score) = state;
         Self {
             p1_pos,
             p2_pos,
-            p1_score: 0,
-            p2_score: 0,
-            win_score,
+            p1_score,
+            p2_score,
+            board_size: rules.board_size,
+            win_score: rules.win_score,
             curr_player: Player::Player1,
         }
     }
@@ -90,29 +155,20 @@ impl DiracDiceGame {
         }
     }
 
+    // advances a position around the track, whose spaces are numbered from 1
+    fn advance_position(&self, pos: u32, n: u32) -> u32 {
+        ((pos - 1 + n) % self.board_size) + 1
+    }
+
     fn advance_player(&mut self, n: u32) {
         match self.curr_player {
             Player::Player1 => {
-                // advance the position, rolling over at 10
-                self.p1_pos += n;
-                if self.p1_pos > 10 {
-                    self.p1_pos %= 10;
-                    if self.p1_pos == 0 {
-                        self.p1_pos = 10;
-                    }
-                }
+                self.p1_pos = self.advance_position(self.p1_pos, n);
                 // add the new position to the score
                 self.p1_score += self.p1_pos;
             }
             Player::Player2 => {
-                // advance the position, rolling over at 10
-                self.p2_pos += n;
-                if self.p2_pos > 10 {
-                    self.p2_pos %= 10;
-                    if self.p2_pos == 0 {
-                        self.p2_pos = 10;
-                    }
-                }
+                self.p2_pos = self.advance_position(self.p2_pos, n);
                 // add the new position to the score
                 self.p2_score += self.p2_pos;
             }
@@ -128,50 +184,42 @@ impl DiracDiceGame {
 pub struct Day21 {
     p1_start_pos: u32,
     p2_start_pos: u32,
-    dirac_moveset: HashMap<u32, u64>,
 }
 
 impl Day21 {
-    fn parse_start_position(line: &'static str) -> u32 {
-        // just grab the last character in each line
-        line.chars().rev().next().unwrap().to_digit(10).unwrap()
-    }
-
-    pub fn new(input: &'static str) -> Self {
-        let p1_start_line = input.split('\n').next().unwrap();
-        let p1_start_pos = Self::parse_start_position(p1_start_line);
-
-        let p2_start_line = input.split('\n').nth(1).unwrap();
-        let p2_start_pos = Self::parse_start_position(p2_start_line);
-
-        // generate the moveset for part 2; reduces branching by combining
-        // dice roll permutations whose sums are equal
-        let mut dirac_moveset = HashMap::new();
-        for (i, j, k) in itertools::iproduct!(1..=3, 1..=3, 1..=3) {
-            let entry = dirac_moveset.entry(i + j + k).or_insert(0);
-            *entry += 1;
+    fn parse_start_position(line: &str, player: u32) -> Result<u32> {
+        let prefix = format!("Player {} starting position: ", player);
+        let pos = line
+            .strip_prefix(&prefix)
+            .ok_or_else(|| format!("invalid starting position {:?}", line))?
+            .parse()
+            .context("starting position")?;
+        // note: both sets of rules play on a board of the same size
+        if !(1..=GameRules::PRACTICE.board_size).contains(&pos) {
+            return Err(format!("starting position {} is off the board", pos).into());
         }
+        Ok(pos)
+    }
 
-        Self {
-            p1_start_pos,
-            p2_start_pos,
-            dirac_moveset,
-        }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn play_game_deterministic(&self) -> u64 {
-        let mut game = DiracDiceGame::new(self.p1_start_pos, self.p2_start_pos, 1000);
-        let mut dice = DeterministicDice::new();
+    // plays with a deterministic die; returns the losing player's score multiplied by the number
+    // of times the die was rolled
+    pub fn play_game_deterministic(&self, rules: &GameRules) -> u64 {
+        let mut game = DiracDiceGame::new(self.p1_start_pos, self.p2_start_pos, rules);
+        let mut dice = DeterministicDice::new(rules.die_faces);
 
         while game.check_for_winner().is_none() {
-            game.play_round(dice.roll());
+            game.play_round(dice.roll(rules.rolls_per_turn));
         }
 
         game.losing_player_score() as u64 * dice.rolls as u64
     }
 
     fn play_game_dirac_rec(
-        &self,
+        moveset: &BTreeMap<u32, u64>,
         p1_wins: &mut u64,
         p2_wins: &mut u64,
         mut game: DiracDiceGame,
@@ -187,38 +235,261 @@ impl Day21 {
                 Player::Player2 => *p2_wins += n_games,
             };
         } else {
-            for (roll, n) in self.dirac_moveset.iter() {
-                self.play_game_dirac_rec(p1_wins, p2_wins, game.clone(), *roll, n_games * n);
+            for (roll, n) in moveset.iter() {
+                Self::play_game_dirac_rec(
+                    moveset,
+                    p1_wins,
+                    p2_wins,
+                    game.clone(),
+                    *roll,
+                    n_games * n,
+                );
             }
         }
     }
 
-    fn play_game_dirac(&self) -> u64 {
-        let game = DiracDiceGame::new(self.p1_start_pos, self.p2_start_pos, 21);
+    // the number of universes won by the player about to move and by the other player, from the
+    // given state, by playing out every universe
+    pub fn dirac_wins_from(rules: &GameRules, state: GameState) -> (u64, u64) {
+        let game = DiracDiceGame::from_state(state, rules);
+        let moveset = rules.moveset();
         let mut p1_wins = 0;
         let mut p2_wins = 0;
 
         // recurse on each possible die roll
-        for (roll, n_games) in self.dirac_moveset.iter() {
-            self.play_game_dirac_rec(&mut p1_wins, &mut p2_wins, game.clone(), *roll, *n_games);
+        for (roll, n_games) in moveset.iter() {
+            Self::play_game_dirac_rec(
+                &moveset,
+                &mut p1_wins,
+                &mut p2_wins,
+                game.clone(),
+                *roll,
+                *n_games,
+            );
+        }
+        (p1_wins, p2_wins)
+    }
+
+    fn start_state(&self) -> GameState {
+        (self.p1_start_pos, 0, self.p2_start_pos, 0)
+    }
+
+    // the number of universes won by each player, with a die which splits the universe on every
+    // roll
+    pub fn dirac_wins(&self, rules: &GameRules) -> (u64, u64) {
+        Self::dirac_wins_from(rules, self.start_state())
+    }
+
+    // plays with a die which splits the universe on every roll; returns the number of universes
+    // in which the player who wins more often wins
+    pub fn play_game_dirac(&self, rules: &GameRules) -> u64 {
+        let (p1_wins, p2_wins) = self.dirac_wins(rules);
+        explain::report("player 1 wins", p1_wins);
+        explain::report("player 2 wins", p2_wins);
+        cmp::max(p1_wins, p2_wins)
+    }
+
+    // the number of universes won by the player about to move and by the other player, from the
+    // given positions and scores
+    fn count_wins<M>(
+        rules: &GameRules,
+        moveset: &BTreeMap<u32, u64>,
+        memo: &mut M,
+        state: GameState,
+    ) -> (u64, u64)
+    where
+        M: Memo<GameState, (u64, u64)>,
+    {
+        if let Some(wins) = memo.recall(&state) {
+            return wins;
         }
+        let (pos, score, other_pos, other_score) = state;
+        let mut wins = (0, 0);
+        for (roll, n_games) in moveset.iter() {
+            let pos = ((pos - 1 + roll) % rules.board_size) + 1;
+            let score = score + pos;
+            if score >= rules.win_score {
+                wins.0 += n_games;
+            } else {
+                // the other player moves next, so their wins are the first of the pair
+                let state = (other_pos, other_score, pos, score);
+                let (other_wins, own_wins) = Self::count_wins(rules, moveset, memo, state);
+                wins.0 += n_games * own_wins;
+                wins.1 += n_games * other_wins;
+            }
+        }
+        // note: states further from the winning score have more of the game left to play out, so
+        // take more work to solve again
+        let depth = (2 * rules.win_score).saturating_sub(score + other_score);
+        memo.remember(state, wins, depth);
+        wins
+    }
+
+    // the wins from every state which the game can reach from the starting positions, as for
+    // dirac_wins_from, such that each state can be checked against playing out its universes
+    pub fn dirac_win_table(&self, rules: &GameRules) -> HashMap<GameState, (u64, u64)> {
+        let mut memo = HashMap::new();
+        Self::count_wins(rules, &rules.moveset(), &mut memo, self.start_state());
+        memo
+    }
 
+    // as dirac_wins, but counts the wins from each distinct game state only once, so long as it
+    // is still held by the given transposition table
+    pub fn dirac_wins_memoized(
+        &self,
+        rules: &GameRules,
+        table: &mut TranspositionTable<GameState, (u64, u64)>,
+    ) -> (u64, u64) {
+        Self::count_wins(rules, &rules.moveset(), table, self.start_state())
+    }
+
+    // as play_game_dirac, but counts the wins from each distinct game state only once
+    pub fn play_game_dirac_memoized(&self, rules: &GameRules) -> u64 {
+        let mut table = TranspositionTable::new(TABLE_SIZE, Replacement::Deeper);
+        let (p1_wins, p2_wins) = self.dirac_wins_memoized(rules, &mut table);
+        explain::report("player 1 wins", p1_wins);
+        explain::report("player 2 wins", p2_wins);
+        let (hits, misses, evictions) = table.stats();
+        explain::report("table states", table.len());
+        explain::report("table hits", hits);
+        explain::report("table misses", misses);
+        explain::report("table evictions", evictions);
         cmp::max(p1_wins, p2_wins)
     }
 }
 
+impl FromLines for Day21 {
+    fn from_lines<'a, I>(mut lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let p1_start_line = lines.next().ok_or("missing player 1 starting position")?;
+        let p1_start_pos = Self::parse_start_position(p1_start_line, 1)?;
+
+        let p2_start_line = lines.next().ok_or("missing player 2 starting position")?;
+        let p2_start_pos = Self::parse_start_position(p2_start_line, 2)?;
+
+        Ok(Self {
+            p1_start_pos,
+            p2_start_pos,
+        })
+    }
+}
+
 impl Puzzle for Day21 {
     // Play a practice game using the deterministic 100-sided die. The moment
     // either player wins, what do you get if you multiply the score of the
     // losing player by the number of times the die was rolled during the game?
     fn part_1(&self) -> Result<Solution> {
-        Ok(self.play_game_deterministic().into())
+        Ok(self.play_game_deterministic(&GameRules::PRACTICE).into())
     }
 
     // Using your given starting positions, determine every possible outcome.
     // Find the player that wins in more universes; in how many universes does
     // that player win?
     fn part_2(&self) -> Result<Solution> {
-        Ok(self.play_game_dirac().into())
+        Ok(self.play_game_dirac_memoized(&GameRules::DIRAC).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../../input/21.dbg.txt");
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day21::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(err("x,y\n\nzz 3"), "invalid starting position \"x,y\"");
+        assert_eq!(
+            err("Player 1 starting position: 4"),
+            "missing player 2 starting position"
+        );
+        assert_eq!(
+            err("Player 1 starting position: 4\nPlayer 2 starting position: 0"),
+            "starting position 0 is off the board"
+        );
+        assert_eq!(
+            err("Player 1 starting position: x\nPlayer 2 starting position: 8"),
+            "starting position: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_moveset() {
+        let moveset = GameRules::DIRAC.moveset();
+        assert_eq!(moveset.len(), 7);
+        assert_eq!(moveset[&3], 1);
+        assert_eq!(moveset[&6], 7);
+        assert_eq!(moveset.values().sum::<u64>(), 27);
+    }
+
+    #[test]
+    fn test_play_game_deterministic() {
+        let day = Day21::new(TEST_INPUT).unwrap();
+        assert_eq!(day.play_game_deterministic(&GameRules::PRACTICE), 739785);
+    }
+
+    #[test]
+    fn test_play_game_dirac_memoized() {
+        let day = Day21::new(TEST_INPUT).unwrap();
+        assert_eq!(
+            day.play_game_dirac_memoized(&GameRules::DIRAC),
+            444356092776315
+        );
+        assert_eq!(day.part_2().unwrap().to_string(), "444356092776315");
+    }
+
+    #[test]
+    fn test_dirac_win_table() {
+        let day = Day21::new(TEST_INPUT).unwrap();
+        // note: a shortened game keeps playing out every universe quick
+        let rules = GameRules {
+            win_score: 10,
+            ..GameRules::DIRAC
+        };
+        let table = day.dirac_win_table(&rules);
+        assert_eq!(table[&(4, 0, 8, 0)], day.dirac_wins(&rules));
+        for (&state, &wins) in table.iter() {
+            assert_eq!(wins, Day21::dirac_wins_from(&rules, state), "{:?}", state);
+        }
+    }
+
+    #[test]
+    fn test_play_game_variants() {
+        let day = Day21::new(TEST_INPUT).unwrap();
+        // a single roll of a 6-sided die per turn: player 2 visits 10, 4, 10, 2, 6 to win with 32
+        // on the 10th roll while player 1 has visited 5, 8, 3, 4, 7 for 27
+        let rules = GameRules {
+            board_size: 10,
+            die_faces: 6,
+            rolls_per_turn: 1,
+            win_score: 30,
+        };
+        assert_eq!(day.play_game_deterministic(&rules), 27 * 10);
+
+        // with a 1-sided die there is only a single universe, which player 1 wins
+        let rules = GameRules {
+            die_faces: 1,
+            ..GameRules::DIRAC
+        };
+        assert_eq!(day.play_game_dirac(&rules), 1);
+        assert_eq!(day.play_game_dirac_memoized(&rules), 1);
+    }
+
+    #[test]
+    fn test_bounded_table() {
+        let day = Day21::new(TEST_INPUT).unwrap();
+        let wins = day.dirac_win_table(&GameRules::DIRAC)[&(4, 0, 8, 0)];
+        // however few slots the table has, evicted states are solved again to the same answer
+        for (capacity, replacement) in [
+            (1 << 14, Replacement::Deeper),
+            (1 << 12, Replacement::Always),
+        ] {
+            let mut table = TranspositionTable::new(capacity, replacement);
+            assert_eq!(day.dirac_wins_memoized(&GameRules::DIRAC, &mut table), wins);
+            assert!(table.len() <= capacity);
+        }
     }
 }
@@ -3,232 +3,176 @@
 ** https://adventofcode.com/2021/day/22
 */
 
-use crate::types::{Puzzle, Result, Solution};
-use crate::utils;
+use crate::explain;
+use crate::types::{Context, FromLines, Interval, Puzzle, Result, Solution};
 
-use itertools::Itertools;
-
-use std::cmp;
-use std::collections::HashSet;
 use std::ops::RangeInclusive;
 
-type Cube = (i64, i64, i64);
-
 #[derive(Debug)]
 enum Instruction {
     On,
     Off,
 }
 
-impl From<&'static str> for Instruction {
-    fn from(s: &'static str) -> Self {
+impl Instruction {
+    fn parse(s: &str) -> Result<Self> {
         match s {
-            "on" => Self::On,
-            "off" => Self::Off,
-            _ => unreachable!(),
+            "on" => Ok(Self::On),
+            "off" => Ok(Self::Off),
+            _ => Err(format!("invalid instruction {:?}", s).into()),
         }
     }
 }
 
-#[derive(Debug)]
-struct Range {
-    min: i64,
-    max: i64,
-}
-
-impl Range {
-    fn size(&self) -> i64 {
-        self.max - self.min
-    }
-
-    fn iter(&self) -> RangeInclusive<i64> {
-        self.min..=self.max
-    }
-
-    fn intersection(&self, other: &Self) -> Option<Self> {
-        let max_of_mins = cmp::max(self.min, other.min);
-        let min_of_maxs = cmp::min(self.max, other.max);
-
-        if max_of_mins > min_of_maxs {
-            None
-        } else {
-            Some(Self {
-                min: max_of_mins,
-                max: min_of_maxs,
-            })
-        }
-    }
-
-    fn fully_contains(&self, other: &Self) -> bool {
-        other.min >= self.min && other.max <= self.max
-    }
-}
-
-impl From<RangeInclusive<i64>> for Range {
-    fn from(range: RangeInclusive<i64>) -> Self {
-        let (min, max) = range.into_inner();
-        Self { min, max }
-    }
-}
-
-#[derive(Debug)]
-struct Region {
-    x: Range,
-    y: Range,
-    z: Range,
+#[derive(Clone, Debug, PartialEq)]
+struct Cuboid {
+    x: Interval,
+    y: Interval,
+    z: Interval,
 }
 
-impl Region {
+impl Cuboid {
     fn new(x: RangeInclusive<i64>, y: RangeInclusive<i64>, z: RangeInclusive<i64>) -> Self {
         Self {
-            x: Range::from(x),
-            y: Range::from(y),
-            z: Range::from(z),
+            x: Interval::from(x),
+            y: Interval::from(y),
+            z: Interval::from(z),
         }
     }
 
-    fn size(&self) -> i64 {
+    fn volume(&self) -> i64 {
         self.x.size() * self.y.size() * self.z.size()
     }
 
     fn intersection(&self, other: &Self) -> Option<Self> {
-        if let Some(x) = self.x.intersection(&other.x) {
-            if let Some(y) = self.y.intersection(&other.y) {
-                if let Some(z) = self.z.intersection(&other.z) {
-                    return Some(Self { x, y, z });
-                }
-            }
-        }
-        None
+        let x = self.x.intersection(&other.x)?;
+        let y = self.y.intersection(&other.y)?;
+        let z = self.z.intersection(&other.z)?;
+        Some(Self { x, y, z })
     }
 
-    fn fully_contains(&self, other: &Self) -> bool {
-        self.x.fully_contains(&other.x)
-            && self.y.fully_contains(&other.y)
-            && self.z.fully_contains(&other.z)
+    // the parts of this cuboid which are not in the other, as at most 6 disjoint cuboids
+    fn subtract(&self, other: &Self) -> Vec<Self> {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None => return vec![self.clone()],
+        };
+        // slabs on either side of the overlap along x span the whole cuboid, those along y are
+        // within the overlap's x range, and those along z are within its x and y ranges
+        let mut parts = vec![];
+        for x in self.x.subtract(&overlap.x) {
+            parts.push(Self { x, ..*self });
+        }
+        for y in self.y.subtract(&overlap.y) {
+            parts.push(Self {
+                x: overlap.x,
+                y,
+                z: self.z,
+            });
+        }
+        for z in self.z.subtract(&overlap.z) {
+            parts.push(Self {
+                x: overlap.x,
+                y: overlap.y,
+                z,
+            });
+        }
+        parts
     }
 }
 
-struct PoweredRegion<'a> {
-    region: &'a Region,
-    deductions: Vec<Region>,
-}
+// the initialization region, x=-50..50,y=-50..50,z=-50..50
+const REGION: i64 = 50;
+const REGION_SIDE: usize = (2 * REGION + 1) as usize;
 
-impl<'a> PoweredRegion<'a> {
-    fn prune_overlaps(regions: Vec<Region>) -> Vec<Region> {
-        let fully_contained_regions = regions
-            .iter()
-            .enumerate()
-            .tuple_combinations()
-            .filter_map(|((a, region_a), (b, region_b))| {
-                if region_a.fully_contains(region_b) {
-                    Some(b)
-                } else if region_b.fully_contains(region_a) {
-                    Some(a)
-                } else {
-                    None
-                }
-            })
-            .collect::<HashSet<_>>();
+// the cubes of the initialization region as a dense array of bits, ~128KB
+// note: x varies fastest, such that each row of cubes along x is a contiguous run of bits
+struct BitCube {
+    words: Vec<u64>,
+}
 
-        regions
-            .into_iter()
-            .enumerate()
-            .filter_map(|(i, region)| {
-                if fully_contained_regions.contains(&i) {
-                    None
-                } else {
-                    Some(region)
-                }
-            })
-            .collect()
+impl BitCube {
+    fn new() -> Self {
+        Self {
+            words: vec![0; REGION_SIDE.pow(3).div_ceil(64)],
+        }
     }
 
-    fn overlaps(regions: &[Region]) -> Vec<Region> {
-        let overlaps = regions
-            .iter()
-            .tuple_combinations()
-            .map(|(a, b)| a.intersection(b))
-            .flatten()
-            .collect();
-
-        // remove overlaps which are fully subsets of other overlaps
-        Self::prune_overlaps(overlaps)
+    fn index(x: i64, y: i64, z: i64) -> usize {
+        let (x, y, z) = (
+            (x + REGION) as usize,
+            (y + REGION) as usize,
+            (z + REGION) as usize,
+        );
+        (((z * REGION_SIDE) + y) * REGION_SIDE) + x
     }
 
-    fn deduction_overlap_size(regions: &[Region]) -> i64 {
-        if regions.is_empty() {
-            0
-        } else if regions.len() == 1 {
-            regions[0].size()
-        } else {
-            // find the overlaps of the regions
-            let overlaps = Self::overlaps(regions);
-            // calculate the size of the region
-            let size = overlaps.iter().map(|region| region.size()).sum::<i64>();
-            // then exclude the overlaps of the overlaps
-            let overlap_size = Self::deduction_overlap_size(&overlaps);
-
-            size - overlap_size
+    // sets the bits from start to end, inclusive, to the given state
+    fn fill(&mut self, start: usize, end: usize, on: bool) {
+        // all ones to turn cubes on, or all zeros to turn them off
+        let bits = (on as u64).wrapping_neg();
+        let (first, last) = (start / 64, end / 64);
+        for i in first..=last {
+            let lo = if i == first { start % 64 } else { 0 };
+            let hi = if i == last { end % 64 } else { 63 };
+            let mask = (u64::MAX << lo) & (u64::MAX >> (63 - hi));
+            self.words[i] = (self.words[i] & !mask) | (bits & mask);
         }
     }
 
-    fn size(&self) -> i64 {
-        // start with the total size of the region
-        let full_size = self.region.size();
-        // subtract the sizes of the deductions
-        let deduction_size = self.deductions.iter().map(Region::size).sum::<i64>();
-        // but then account for overlaps in the deductions
-        let deduction_overlaps = Self::deduction_overlap_size(&self.deductions);
-
-        full_size - deduction_size + deduction_overlaps
-    }
-
-    fn deduct(&mut self, region: &Region) {
-        if let Some(overlap) = self.region.intersection(region) {
-            self.deductions.push(overlap);
+    // note: the cuboid must lie within the initialization region
+    fn fill_cuboid(&mut self, cuboid: &Cuboid, on: bool) {
+        for z in cuboid.z.min..=cuboid.z.max {
+            for y in cuboid.y.min..=cuboid.y.max {
+                let start = Self::index(cuboid.x.min, y, z);
+                let end = Self::index(cuboid.x.max, y, z);
+                self.fill(start, end, on);
+            }
         }
     }
-}
 
-impl<'a> From<&'a Region> for PoweredRegion<'a> {
-    fn from(region: &'a Region) -> Self {
-        Self {
-            region,
-            deductions: vec![],
-        }
+    fn count(&self) -> i64 {
+        self.words.iter().map(|word| word.count_ones() as i64).sum()
     }
 }
 
 #[derive(Debug)]
 struct Step {
     instr: Instruction,
-    region: Region,
+    cuboid: Cuboid,
 }
 
 impl Step {
-    fn parse_range(s: &'static str) -> (i64, i64) {
-        split_into!(&s[2..s.len()], "..", min_str, max_str);
-        let min = min_str.parse().unwrap();
-        let max = max_str.parse().unwrap();
-        (min, max)
+    // format: a=min..max
+    fn parse_range(s: &str, axis: char) -> Result<RangeInclusive<i64>> {
+        let range_str = s
+            .strip_prefix(axis)
+            .and_then(|s| s.strip_prefix('='))
+            .with_context(|| format!("{} axis", axis))?;
+        let (min_str, max_str) = range_str.split_once("..").context("range separator")?;
+        let min = min_str.parse().context("range minimum")?;
+        let max = max_str.parse().context("range maximum")?;
+        Ok(min..=max)
     }
-}
-
-impl From<&'static str> for Step {
-    fn from(s: &'static str) -> Self {
-        split_into!(s, ' ', instr_str, ranges_str);
-        split_into!(ranges_str, ',', x_str, y_str, z_str);
-
-        let instr = Instruction::from(instr_str);
-        let (x_min, x_max) = Self::parse_range(x_str);
-        let (y_min, y_max) = Self::parse_range(y_str);
-        let (z_min, z_max) = Self::parse_range(z_str);
 
-        Self {
+    // format: on x=10..12,y=10..12,z=10..12
+    fn parse(s: &str) -> Result<Self> {
+        let (instr_str, ranges_str) = s.split_once(' ').context("cuboid")?;
+        let instr = Instruction::parse(instr_str)?;
+
+        let mut ranges = ranges_str.split(',');
+        let mut next_range = |axis| {
+            let range_str = ranges.next().with_context(|| format!("{} range", axis))?;
+            Self::parse_range(range_str, axis).with_context(|| format!("{} range", axis))
+        };
+        let x = next_range('x')?;
+        let y = next_range('y')?;
+        let z = next_range('z')?;
+
+        Ok(Self {
             instr,
-            region: Region::new(x_min..=x_max, y_min..=y_max, z_min..=z_max),
-        }
+            cuboid: Cuboid::new(x, y, z),
+        })
     }
 }
 
@@ -237,81 +181,133 @@ pub struct Day22 {
 }
 
 impl Day22 {
-    pub fn new(input: &'static str) -> Self {
-        let procedure = utils::input_to_lines(input).map(Step::from).collect();
-        Self { procedure }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn power_on_cubes_with_boundary(cubes: &mut HashSet<Cube>, region: &Region, boundary: &Region) {
-        if let Some(overlap) = region.intersection(boundary) {
-            for x in overlap.x.iter() {
-                for y in overlap.y.iter() {
-                    for z in overlap.z.iter() {
-                        cubes.insert((x, y, z));
-                    }
-                }
+    // the number of powered cubes after each step
+    fn signed_volume_trace<'a, I>(steps: I) -> Vec<i64>
+    where
+        I: Iterator<Item = (&'a Instruction, Cuboid)>,
+    {
+        // track cuboids with a sign, such that the signed sum of their volumes is the number of
+        // powered cubes: each step cancels out its intersection with every tracked cuboid, so
+        // that space is counted exactly once (or not at all), then powered cuboids are added
+        let mut signed_cuboids: Vec<(Cuboid, i64)> = vec![];
+        let mut volume = 0;
+        let mut trace = vec![];
+        for (instr, step_cuboid) in steps {
+            let cancellations = signed_cuboids
+                .iter()
+                .filter_map(|(cuboid, sign)| {
+                    step_cuboid
+                        .intersection(cuboid)
+                        .map(|overlap| (overlap, -sign))
+                })
+                .collect::<Vec<_>>();
+            let start = signed_cuboids.len();
+            signed_cuboids.extend(cancellations);
+            if let Instruction::On = instr {
+                signed_cuboids.push((step_cuboid, 1));
             }
+            volume += signed_cuboids[start..]
+                .iter()
+                .map(|(cuboid, sign)| cuboid.volume() * sign)
+                .sum::<i64>();
+            trace.push(volume);
         }
+        trace
     }
 
-    fn power_off_cubes_with_boundary(
-        cubes: &mut HashSet<Cube>,
-        region: &Region,
-        boundary: &Region,
-    ) {
-        if let Some(overlap) = region.intersection(boundary) {
-            for x in overlap.x.iter() {
-                for y in overlap.y.iter() {
-                    for z in overlap.z.iter() {
-                        cubes.remove(&(x, y, z));
-                    }
-                }
-            }
-        }
+    fn signed_volume<'a, I>(steps: I) -> i64
+    where
+        I: Iterator<Item = (&'a Instruction, Cuboid)>,
+    {
+        Self::signed_volume_trace(steps)
+            .last()
+            .copied()
+            .unwrap_or(0)
     }
 
-    fn execute_procedure_with_boundary(&self, boundary: Region) -> usize {
-        let mut cubes = HashSet::new();
+    fn execute_procedure_with_boundary(&self, boundary: Cuboid) -> i64 {
+        // clip each step to the boundary, dropping those which fall entirely outside of it
+        let steps = self.procedure.iter().filter_map(|step| {
+            step.cuboid
+                .intersection(&boundary)
+                .map(|cuboid| (&step.instr, cuboid))
+        });
+        Self::signed_volume(steps)
+    }
 
+    pub fn execute_initialization_by_volume(&self) -> i64 {
+        self.execute_procedure_with_boundary(Self::initialization_region())
+    }
+
+    // as execute_initialization_by_volume, but sets each cube of the region directly, as there are
+    // only about a million of them
+    pub fn execute_initialization(&self) -> i64 {
+        let region = Self::initialization_region();
+        let mut cubes = BitCube::new();
         for step in self.procedure.iter() {
-            match step.instr {
-                Instruction::On => {
-                    Self::power_on_cubes_with_boundary(&mut cubes, &step.region, &boundary)
-                }
-                Instruction::Off => {
-                    Self::power_off_cubes_with_boundary(&mut cubes, &step.region, &boundary)
-                }
+            if let Some(cuboid) = step.cuboid.intersection(&region) {
+                cubes.fill_cuboid(&cuboid, matches!(step.instr, Instruction::On));
             }
         }
+        cubes.count()
+    }
 
-        cubes.len()
+    fn initialization_region() -> Cuboid {
+        Cuboid::new(-REGION..=REGION, -REGION..=REGION, -REGION..=REGION)
     }
 
-    fn execute_procedure(&self) -> i64 {
-        // first add all powered on cubes
-        let mut powered_regions = self
+    // the number of powered cubes after each step of the procedure, to find the step at which a
+    // total first goes wrong
+    pub fn execute_procedure_trace(&self) -> Vec<i64> {
+        let steps = self
             .procedure
             .iter()
-            .filter(|step| matches!(step.instr, Instruction::On))
-            .map(|step| PoweredRegion::from(&step.region))
-            .collect::<Vec<_>>();
+            .map(|step| (&step.instr, step.cuboid.clone()));
+        Self::signed_volume_trace(steps)
+    }
 
-        // now deduct the powered off regions from the powered on cubes
-        for step in self
+    pub fn execute_procedure(&self) -> i64 {
+        let steps = self
             .procedure
             .iter()
-            .filter(|step| matches!(step.instr, Instruction::Off))
-        {
-            for powered_region in powered_regions.iter_mut() {
-                powered_region.deduct(&step.region);
+            .map(|step| (&step.instr, step.cuboid.clone()));
+        Self::signed_volume(steps)
+    }
+
+    // as execute_procedure, but tracks the powered cubes as disjoint cuboids, splitting each
+    // around every later step which overlaps it
+    pub fn execute_procedure_by_splitting(&self) -> i64 {
+        let mut powered: Vec<Cuboid> = vec![];
+        for step in self.procedure.iter() {
+            powered = powered
+                .iter()
+                .flat_map(|cuboid| cuboid.subtract(&step.cuboid))
+                .collect();
+            if let Instruction::On = step.instr {
+                powered.push(step.cuboid.clone());
             }
         }
+        powered.iter().map(|cuboid| cuboid.volume()).sum()
+    }
+}
 
-        // now sum the sizes of the remaining powered on regions
-        powered_regions
-            .iter()
-            .map(|region| region.size())
-            .sum::<i64>()
+impl FromLines for Day22 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let procedure = lines
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                Step::parse(line).with_context(|| format!("parsing day 22 step {}", i + 1))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { procedure })
     }
 }
 
@@ -319,15 +315,105 @@ impl Puzzle for Day22 {
     // Execute the reboot steps. Afterward, considering only cubes in the region
     // x=-50..50,y=-50..50,z=-50..50, how many cubes are on?
     fn part_1(&self) -> Result<Solution> {
-        let boundary = Region::new(-50..=50, -50..=50, -50..=50);
-        let n_cubes = self.execute_procedure_with_boundary(boundary);
+        let n_cubes = self.execute_initialization();
         Ok(n_cubes.into())
     }
 
     // Starting again with all cubes off, execute all reboot steps. Afterward,
     // considering all cubes, how many cubes are on?
     fn part_2(&self) -> Result<Solution> {
+        if explain::is_enabled() {
+            for (i, (step, n_cubes)) in self
+                .procedure
+                .iter()
+                .zip(self.execute_procedure_trace())
+                .enumerate()
+            {
+                explain::report("step", format!("{} ({:?}): {}", i + 1, step.instr, n_cubes));
+            }
+        }
         let n_cubes = self.execute_procedure();
         Ok(n_cubes.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../../input/22.dbg.txt");
+
+    #[test]
+    fn test_cuboid_volume() {
+        // note: bounds are inclusive
+        let cuboid = Cuboid::new(10..=12, 10..=12, 10..=12);
+        assert_eq!(cuboid.volume(), 27);
+
+        let other = Cuboid::new(11..=13, 11..=13, 11..=13);
+        let overlap = cuboid.intersection(&other).unwrap();
+        assert_eq!(overlap, Cuboid::new(11..=12, 11..=12, 11..=12));
+        assert_eq!(overlap.volume(), 8);
+    }
+
+    #[test]
+    fn test_execute_procedure_small() {
+        let day = Day22::new(
+            "on x=10..12,y=10..12,z=10..12\n\
+             on x=11..13,y=11..13,z=11..13\n\
+             off x=9..11,y=9..11,z=9..11\n\
+             on x=10..10,y=10..10,z=10..10",
+        )
+        .unwrap();
+        assert_eq!(day.execute_procedure(), 39);
+        assert_eq!(day.execute_procedure_by_splitting(), 39);
+        // the totals after each step given in the puzzle description
+        assert_eq!(day.execute_procedure_trace(), vec![27, 46, 38, 39]);
+    }
+
+    #[test]
+    fn test_execute_procedure_with_boundary() {
+        let day = Day22::new(
+            "on x=-60..-40,y=0..0,z=0..0\n\
+             on x=100..200,y=0..0,z=0..0\n\
+             off x=-45..-45,y=-100..100,z=-100..100",
+        )
+        .unwrap();
+        // only x=-50..-40 lies inside of the boundary, less the single cube turned off
+        let boundary = Cuboid::new(-50..=50, -50..=50, -50..=50);
+        assert_eq!(day.execute_procedure_with_boundary(boundary), 10);
+        assert_eq!(day.execute_initialization(), 10);
+    }
+
+    #[test]
+    fn test_bit_cube_fill() {
+        let mut cubes = BitCube::new();
+        // a run of bits which spans several words
+        cubes.fill(60, 200, true);
+        assert_eq!(cubes.count(), 141);
+        cubes.fill(64, 127, false);
+        assert_eq!(cubes.count(), 77);
+        cubes.fill(63, 63, false);
+        assert_eq!(cubes.count(), 76);
+        // the corners of the region
+        cubes.fill_cuboid(&Day22::initialization_region(), true);
+        assert_eq!(cubes.count(), 101 * 101 * 101);
+    }
+
+    #[test]
+    fn test_parse_step_error() {
+        let err = Step::parse("on x=10..12,y=10..1a,z=10..12").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "y range: range maximum: invalid digit found in string"
+        );
+        let err = Step::parse("on x=10..12,y=10..12").unwrap_err();
+        assert_eq!(err.to_string(), "missing z range");
+    }
+
+    #[test]
+    fn test_example() {
+        let day = Day22::new(TEST_INPUT).unwrap();
+        assert_eq!(day.part_1().unwrap().to_string(), "474140");
+        assert_eq!(day.part_2().unwrap().to_string(), "2758514936282235");
+    }
+}
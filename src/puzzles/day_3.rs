@@ -3,90 +3,86 @@
 ** https://adventofcode.com/2021/day/3
 */
 
-use crate::types::{Puzzle, Result, Solution};
-use crate::utils;
+use crate::explain;
+use crate::types::{FromLines, Puzzle, PuzzleError, Result, Solution};
 
-const N_BITS: usize = 12;
-
-#[derive(Clone)]
-struct Binary {
-    digits: [u8; N_BITS],
+pub struct Day3 {
+    numbers: Vec<u32>,
+    // the number of bits in each number, which is the length of each line
+    width: usize,
 }
 
-impl Binary {
-    fn bit(&self, i: usize) -> u8 {
-        self.digits[N_BITS - i - 1]
+impl Day3 {
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
-}
 
-impl From<&str> for Binary {
-    fn from(s: &str) -> Self {
-        let mut digits = [0; N_BITS];
-        for (i, c) in s.chars().enumerate() {
-            digits[i] = c.to_digit(10).unwrap() as u8;
-        }
-        Self { digits }
+    // the most common value of the given bit among the numbers, where ties go to 1
+    fn most_common(numbers: &[u32], bit: usize) -> u32 {
+        let ones = numbers.iter().filter(|&&n| (n >> bit) & 1 == 1).count();
+        ((ones * 2) >= numbers.len()) as u32
     }
-}
 
-#[allow(clippy::from_over_into)]
-impl Into<u32> for &Binary {
-    fn into(self) -> u32 {
-        let mut n = 0;
-        for (i, &x) in self.digits.iter().rev().enumerate() {
-            n |= (x as u32) << i;
-        }
-        n
+    fn mask(&self) -> u32 {
+        u32::MAX >> (u32::BITS as usize - self.width)
     }
-}
 
-pub struct Day3 {
-    numbers: Vec<Binary>,
-    bit_counts: [u64; N_BITS],
-}
+    // the most common value of each bit
+    pub fn gamma(&self) -> u32 {
+        (0..self.width).fold(0, |gamma, bit| {
+            gamma | (Self::most_common(&self.numbers, bit) << bit)
+        })
+    }
 
-impl Day3 {
-    fn count_bits(numbers: &[Binary]) -> [u64; N_BITS] {
-        let mut bit_count = [0; N_BITS];
-
-        for number in numbers.iter() {
-            for (i, &bit) in number.digits.iter().enumerate() {
-                if bit == 1 {
-                    bit_count[i] += 1;
-                }
+    // the least common value of each bit
+    pub fn epsilon(&self) -> u32 {
+        !self.gamma() & self.mask()
+    }
+
+    // filters the numbers bit by bit from the most significant, keeping those with the most (or
+    // least) common value of the bit among those remaining, until one number is left
+    fn rating(&self, most_common: bool) -> Result<u32> {
+        let mut numbers = self.numbers.clone();
+        for bit in (0..self.width).rev() {
+            if numbers.len() <= 1 {
+                break;
             }
+            let keep = Self::most_common(&numbers, bit) ^ (!most_common as u32);
+            numbers.retain(|&n| (n >> bit) & 1 == keep);
+        }
+        match numbers.as_slice() {
+            &[n] => Ok(n),
+            _ => Err(PuzzleError::NoSolution.into()),
         }
-
-        bit_count
     }
 
-    pub fn new(input: &'static str) -> Self {
-        let numbers = utils::input_to_lines(input)
-            .map(Binary::from)
-            .collect::<Vec<_>>();
-        let bit_counts = Self::count_bits(&numbers);
-        Self {
-            numbers,
-            bit_counts,
-        }
+    pub fn oxygen_rating(&self) -> Result<u32> {
+        self.rating(true)
     }
 
-    fn most_common(bit_counts: &[u64; N_BITS], n_numbers: usize, bit: usize) -> u8 {
-        let pos = N_BITS - bit - 1;
-        if bit_counts[pos] >= n_numbers as u64 / 2 {
-            1
-        } else {
-            0
-        }
+    pub fn co2_rating(&self) -> Result<u32> {
+        self.rating(false)
     }
+}
 
-    fn least_common(bit_counts: &[u64; N_BITS], n_numbers: usize, bit: usize) -> u8 {
-        let pos = N_BITS - bit - 1;
-        if bit_counts[pos] >= n_numbers as u64 / 2 {
-            0
-        } else {
-            1
+impl FromLines for Day3 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let lines = lines.filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        let width = lines.first().map_or(0, |line| line.len());
+        if width == 0 || width > u32::BITS as usize {
+            return Err(format!("expected binary numbers of 1 to 32 bits, found {}", width).into());
         }
+        let numbers = lines
+            .iter()
+            .map(|line| match line.len() {
+                len if len == width => Ok(u32::from_str_radix(line, 2)?),
+                len => Err(format!("expected {}-bit numbers, found {} bits", width, len).into()),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { numbers, width })
     }
 }
 
@@ -95,56 +91,50 @@ impl Puzzle for Day3 {
     // rate and epsilon rate, then multiply them together. What is the power
     // consumption of the submarine?
     fn part_1(&self) -> Result<Solution> {
-        let mut gamma = 0;
-        let mut epsilon = 0;
-
-        for i in 0..N_BITS {
-            match Self::most_common(&self.bit_counts, self.numbers.len(), i) {
-                0 => epsilon |= 1 << i,
-                1 => gamma |= 1 << i,
-                _ => unreachable!(),
-            };
-        }
-
-        Ok((gamma * epsilon).into())
+        let gamma = self.gamma();
+        let epsilon = self.epsilon();
+        let width = self.width;
+        explain::report("gamma", format!("{:0width$b} ({})", gamma, gamma));
+        explain::report("epsilon", format!("{:0width$b} ({})", epsilon, epsilon));
+        Ok((gamma as u64 * epsilon as u64).into())
     }
 
     // Use the binary numbers in your diagnostic report to calculate the oxygen
     // generator rating and CO2 scrubber rating, then multiply them together.
     // What is the life support rating of the submarine?
     fn part_2(&self) -> Result<Solution> {
-        // determine oxygen generator rating
-        let mut oxygen_numbers = self.numbers.clone();
-        for i in (0..N_BITS).rev() {
-            let bit_counts = Self::count_bits(&oxygen_numbers);
-            let bit = Self::most_common(&bit_counts, oxygen_numbers.len(), i);
-            oxygen_numbers = oxygen_numbers
-                .iter()
-                .filter(|n| n.bit(i) == bit)
-                .cloned()
-                .collect();
-            if oxygen_numbers.len() == 1 {
-                break;
-            }
-        }
-        let oxygen_rating: u32 = (&oxygen_numbers[0]).into();
-
-        // determine CO2 scrubber rating
-        let mut co2_numbers = self.numbers.clone();
-        for i in (0..N_BITS).rev() {
-            let bit_counts = Self::count_bits(&co2_numbers);
-            let bit = Self::least_common(&bit_counts, co2_numbers.len(), i);
-            co2_numbers = co2_numbers
-                .iter()
-                .filter(|n| n.bit(i) == bit)
-                .cloned()
-                .collect();
-            if co2_numbers.len() == 1 {
-                break;
-            }
-        }
-        let co2_rating: u32 = (&co2_numbers[0]).into();
+        let oxygen_rating = self.oxygen_rating()?;
+        let co2_rating = self.co2_rating()?;
+        explain::report("oxygen generator rating", oxygen_rating);
+        explain::report("CO2 scrubber rating", co2_rating);
+        Ok((oxygen_rating as u64 * co2_rating as u64).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str =
+        "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010";
+
+    #[test]
+    fn test_ratings() {
+        let day = Day3::new(TEST_INPUT).unwrap();
+        assert_eq!(day.gamma(), 22);
+        assert_eq!(day.epsilon(), 9);
+        assert_eq!(day.oxygen_rating().unwrap(), 23);
+        assert_eq!(day.co2_rating().unwrap(), 10);
+        assert_eq!(day.part_1().unwrap().to_string(), "198");
+        assert_eq!(day.part_2().unwrap().to_string(), "230");
+    }
 
-        Ok((oxygen_rating * co2_rating).into())
+    #[test]
+    fn test_most_common_odd() {
+        // 3 of 7 numbers have the bit set, which is not the most common value
+        let numbers = [1, 1, 1, 0, 0, 0, 0];
+        assert_eq!(Day3::most_common(&numbers, 0), 0);
+        // and ties go to 1
+        assert_eq!(Day3::most_common(&numbers[..6], 0), 1);
     }
 }
@@ -3,24 +3,65 @@
 ** https://adventofcode.com/2021/day/4
 */
 
-use crate::types::{Puzzle, PuzzleError, Result, Solution};
+use crate::types::{FromLines, Puzzle, PuzzleError, Result, Solution};
 
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use crate::utils;
+
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
 
-const BINGO_SIZE: usize = 5;
+use std::cell::RefCell;
+use std::collections::HashSet;
 
+// note: the fixed hasher visits the marked positions in the same order on every run
 #[derive(Debug)]
 struct BingoBoard {
+    width: usize,
+    height: usize,
     // stores the numbers on the card
-    numbers: HashSet<u8>,
-    // stores the positions of the numbers
-    positions: HashMap<u8, usize>,
+    numbers: FxHashSet<u8>,
+    // stores the positions of the numbers, row by row
+    positions: FxHashMap<u8, usize>,
     // stores marked number positions
-    marked: HashSet<usize>,
+    marked: FxHashSet<usize>,
 }
 
 impl BingoBoard {
+    // parses the rows of a board, whose dimensions are those of the rows given
+    fn parse(rows: &[&str]) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.split_whitespace().count());
+        let mut numbers = FxHashSet::default();
+        let mut positions = FxHashMap::default();
+        for (i, row) in rows.iter().enumerate() {
+            let row = row
+                .split_whitespace()
+                .map(|n| n.parse())
+                .collect::<std::result::Result<Vec<u8>, _>>()?;
+            if row.len() != width {
+                return Err(format!(
+                    "bingo board row {} has {} numbers, expected {}",
+                    i + 1,
+                    row.len(),
+                    width
+                )
+                .into());
+            }
+            for (j, num) in row.into_iter().enumerate() {
+                numbers.insert(num);
+                positions.insert(num, (i * width) + j);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            numbers,
+            positions,
+            marked: FxHashSet::default(),
+        })
+    }
+
     fn reset(&mut self) {
         self.marked.clear();
     }
@@ -31,19 +72,16 @@ impl BingoBoard {
         }
     }
 
+    // is the row containing the position fully marked?
     fn contains_row(&self, pos: usize) -> bool {
-        pos % 5 == 0
-            && self.marked.contains(&(pos + 1))
-            && self.marked.contains(&(pos + 2))
-            && self.marked.contains(&(pos + 3))
-            && self.marked.contains(&(pos + 4))
+        let row_start = pos - (pos % self.width);
+        (row_start..row_start + self.width).all(|p| self.marked.contains(&p))
     }
 
+    // is the column containing the position fully marked?
     fn contains_col(&self, pos: usize) -> bool {
-        self.marked.contains(&(pos + BINGO_SIZE))
-            && self.marked.contains(&(pos + (BINGO_SIZE * 2)))
-            && self.marked.contains(&(pos + (BINGO_SIZE * 3)))
-            && self.marked.contains(&(pos + (BINGO_SIZE * 4)))
+        let col = pos % self.width;
+        (0..self.height).all(|row| self.marked.contains(&((row * self.width) + col)))
     }
 
     fn is_complete(&self) -> bool {
@@ -66,23 +104,17 @@ impl BingoBoard {
         }
         sum * final_number as u64
     }
-}
-
-impl From<&str> for BingoBoard {
-    fn from(s: &str) -> Self {
-        let mut numbers = HashSet::new();
-        let mut positions = HashMap::new();
-        for (pos, num_str) in s.split_whitespace().filter(|ss| !ss.is_empty()).enumerate() {
-            let num = num_str.parse().unwrap();
-            numbers.insert(num);
-            positions.insert(num, pos);
-        }
 
-        Self {
-            numbers,
-            positions,
-            marked: HashSet::new(),
+    // renders the numbers in their positions on the card, one row per line
+    fn render(&self) -> String {
+        let mut cells = vec![0; self.width * self.height];
+        for (&number, &pos) in self.positions.iter() {
+            cells[pos] = number;
         }
+        cells
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(|n| format!("{:>2}", n)).join(" "))
+            .join("\n")
     }
 }
 
@@ -93,18 +125,8 @@ pub struct Day4 {
 }
 
 impl Day4 {
-    pub fn new(input: &'static str) -> Self {
-        let parts = input.split("\n\n").collect::<Vec<_>>();
-        let numbers = parts[0].split(',').map(|n| n.parse().unwrap()).collect();
-        let bingo_boards = parts
-            .iter()
-            .skip(1)
-            .map(|&s| RefCell::new(BingoBoard::from(s)))
-            .collect();
-        Self {
-            numbers,
-            bingo_boards,
-        }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
     fn mark_boards(&self, number: u8) {
@@ -120,6 +142,31 @@ impl Day4 {
     }
 }
 
+impl FromLines for Day4 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let sections = utils::sections(lines);
+        let numbers = sections
+            .first()
+            .ok_or("missing drawn numbers")?
+            .iter()
+            .flat_map(|line| line.split(','))
+            .map(|n| n.parse())
+            .collect::<std::result::Result<_, _>>()?;
+        let bingo_boards = sections
+            .iter()
+            .skip(1)
+            .map(|board| BingoBoard::parse(board).map(RefCell::new))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            numbers,
+            bingo_boards,
+        })
+    }
+}
+
 impl Puzzle for Day4 {
     // Figure out which board will win first. What will your final score be if
     // you choose that board?
@@ -165,4 +212,65 @@ impl Puzzle for Day4 {
             None => Err(PuzzleError::NoSolution.into()),
         }
     }
+
+    fn dump(&self) -> Option<String> {
+        let mut sections = vec![format!("numbers: {}", self.numbers.iter().join(","))];
+        for (i, board) in self.bingo_boards.iter().enumerate() {
+            sections.push(format!("board {}:\n{}", i, board.borrow().render()));
+        }
+        Some(sections.join("\n\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str =
+        "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
+    #[test]
+    fn test_example() {
+        let day = Day4::new(TEST_INPUT).unwrap();
+        assert_eq!(day.part_1().unwrap().to_string(), "4512");
+        assert_eq!(day.part_2().unwrap().to_string(), "1924");
+    }
+
+    #[test]
+    fn test_board_dimensions() {
+        let mut board = BingoBoard::parse(&["1 2 3", "4 5 6"]).unwrap();
+        assert_eq!((board.width, board.height), (3, 2));
+        // a row is complete regardless of which of its positions was marked first
+        board.mark(6);
+        board.mark(5);
+        assert!(!board.is_complete());
+        board.mark(4);
+        assert!(board.is_complete());
+        assert_eq!(board.score(4), (1 + 2 + 3) * 4);
+
+        board.reset();
+        board.mark(2);
+        board.mark(5);
+        assert!(board.is_complete());
+
+        assert!(BingoBoard::parse(&["1 2 3", "4 5"]).is_err());
+    }
 }
@@ -3,20 +3,19 @@
 ** https://adventofcode.com/2021/day/5
 */
 
-use crate::types::{Line, Point, Puzzle, Result, Solution};
-use crate::utils;
+use crate::types::{Context, Counter, FromLines, Line, Point, Puzzle, Result, Solution};
+use crate::viz::{heatmap, Frame, Heatmap};
 
 use std::cmp;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 pub struct Day5 {
     vent_lines: Vec<Line>,
 }
 
 impl Day5 {
-    pub fn new(input: &'static str) -> Self {
-        let vent_lines = utils::input_to_lines(input).map(Line::from).collect();
-        Self { vent_lines }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
     fn intersection_with_vertical(line_a: &Line, line_b: &Line) -> Option<Point> {
@@ -97,28 +96,121 @@ impl Day5 {
 
         intersections
     }
+
+    // how many lines cover each point along every line
+    fn cover_counts(lines: &[Line]) -> Counter<Point> {
+        let mut counts = Counter::new();
+        for line in lines.iter() {
+            let (dx, dy) = (
+                (line.p1.x - line.p0.x).signum(),
+                (line.p1.y - line.p0.y).signum(),
+            );
+            let mut p = line.p0.clone();
+            loop {
+                counts.insert(p.clone());
+                if p == line.p1 {
+                    break;
+                }
+                p.x += dx;
+                p.y += dy;
+            }
+        }
+        counts
+    }
+
+    // as find_intersections, but counts how many lines cover each point along every line
+    fn count_overlaps(lines: &[Line]) -> usize {
+        Self::cover_counts(lines)
+            .iter()
+            .filter(|&(_, &n)| n >= 2)
+            .count()
+    }
+
+    // the vent lines, optionally including the diagonals
+    pub fn lines(&self, diagonals: bool) -> Vec<Line> {
+        self.vent_lines
+            .iter()
+            .filter(|l| diagonals || l.is_horizontal() || l.is_vertical())
+            // note: need to dereference
+            .cloned()
+            .collect()
+    }
+
+    // the number of points at which at least two lines overlap
+    pub fn overlaps(&self, diagonals: bool) -> usize {
+        Self::find_intersections(&self.lines(diagonals)).len()
+    }
+
+    // the points at which at least two lines overlap, with the number of lines over each
+    pub fn overlap_counts(&self, diagonals: bool) -> HashMap<Point, usize> {
+        Self::cover_counts(&self.lines(diagonals))
+            .iter()
+            .filter(|&(_, &n)| n >= 2)
+            .map(|(p, &n)| (p.clone(), n))
+            .collect()
+    }
+
+    // as overlaps, by counting the lines over each point rather than intersecting them pairwise
+    pub fn overlaps_counted(&self, diagonals: bool) -> usize {
+        Self::count_overlaps(&self.lines(diagonals))
+    }
+}
+
+impl Heatmap for Day5 {
+    // how many lines, including the diagonals, cover each point
+    fn heatmap(&self) -> Frame {
+        heatmap::from_counter(&Self::cover_counts(&self.lines(true)))
+    }
+}
+
+impl FromLines for Day5 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let vent_lines = lines
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(i, line)| Line::parse(line).with_context(|| format!("line {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(Self { vent_lines })
+    }
 }
 
 impl Puzzle for Day5 {
     // Consider only horizontal and vertical lines. At how many points do at
     // least two lines overlap?
     fn part_1(&self) -> Result<Solution> {
-        // filter horizontal/vertical lines
-        let horizontal_vertical = self
-            .vent_lines
-            .iter()
-            .filter(|l| l.is_horizontal() || l.is_vertical())
-            // note: need to dereference
-            .cloned()
-            .collect::<Vec<_>>();
-        let intersections = Self::find_intersections(&horizontal_vertical);
-        Ok(intersections.len().into())
+        Ok(self.overlaps(false).into())
     }
 
     // Consider all of the lines. At how many points do at least two lines
     // overlap?
     fn part_2(&self) -> Result<Solution> {
-        let intersections = Self::find_intersections(&self.vent_lines);
-        Ok(intersections.len().into())
+        Ok(self.overlaps(true).into())
+    }
+
+    fn dump(&self) -> Option<String> {
+        let lines = self
+            .vent_lines
+            .iter()
+            .map(|line| format!("{:?} (slope {:?})", line, line.slope))
+            .collect::<Vec<_>>();
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day5::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(err("x,y\n\nzz 3"), "line 1: invalid line \"x,y\"");
+        assert_eq!(
+            err("0,9 -> 5,9\n8,0 -> 0,x"),
+            "line 2: y: invalid digit found in string"
+        );
     }
 }
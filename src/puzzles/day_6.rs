@@ -3,45 +3,29 @@
 ** https://adventofcode.com/2021/day/6
 */
 
-use crate::types::{Puzzle, Result, Solution};
-
-use std::cell::RefCell;
+use crate::types::{mat_pow, FromLines, Matrix, Puzzle, Result, Simulation, Solution};
 
 const LIFECYCLE: usize = 6;
 const INACTIVE_PERIOD: usize = 2;
 
-pub struct Day6 {
-    input: &'static str,
-    // count the number of fish with each timer to save space/time
-    // need RefCell for interior mutability
-    fish: RefCell<[u64; LIFECYCLE + INACTIVE_PERIOD + 1]>,
+// count the number of fish with each timer to save space/time
+struct School {
+    fish: [u64; LIFECYCLE + INACTIVE_PERIOD + 1],
 }
 
-impl Day6 {
-    pub fn new(input: &'static str) -> Self {
-        // empty initialization then call initialize_fish_array
-        // marginally more inefficient but cleaner
-        let new = Self {
-            input,
-            fish: RefCell::new([0; LIFECYCLE + INACTIVE_PERIOD + 1]),
-        };
-        new.initialize_fish_array();
-        new
-    }
-
-    fn initialize_fish_array(&self) {
-        let mut fish = [0; LIFECYCLE + INACTIVE_PERIOD + 1];
-        for n in self.input.split(',') {
-            fish[n.parse::<usize>().unwrap()] += 1;
-        }
-        let _ = self.fish.replace(fish);
+impl School {
+    fn size(&self) -> u64 {
+        self.fish.iter().sum()
     }
+}
 
-    fn simulate_day(&self) {
+impl Simulation for School {
+    // advances by a single day
+    fn step(&mut self) {
         // double-buffer for updates
         let mut fish_new = [0; LIFECYCLE + INACTIVE_PERIOD + 1];
 
-        for (i, &n_fish) in self.fish.borrow().iter().enumerate() {
+        for (i, &n_fish) in self.fish.iter().enumerate() {
             if i == 0 {
                 // fish whose timers have expired are reset
                 fish_new[LIFECYCLE] += n_fish;
@@ -53,31 +37,97 @@ impl Day6 {
             }
         }
 
-        let _ = self.fish.replace(fish_new);
+        self.fish = fish_new;
+    }
+}
+
+pub struct Day6 {
+    // the number of fish with each timer at the start
+    fish: [u64; LIFECYCLE + INACTIVE_PERIOD + 1],
+}
+
+impl Day6 {
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+
+    fn school(&self) -> School {
+        School { fish: self.fish }
+    }
+
+    // the number of fish after an arbitrary number of days, or None if it does not fit into a
+    // u128, by raising the daily update to the given power rather than simulating each day
+    pub fn count_after(&self, days: u64) -> Option<u128> {
+        // maps the number of fish with each timer onto the number with each timer the next day
+        let mut day = Matrix::zero(LIFECYCLE + INACTIVE_PERIOD + 1);
+        for i in 1..(LIFECYCLE + INACTIVE_PERIOD + 1) {
+            day.set(i - 1, i, 1);
+        }
+        day.set(LIFECYCLE, 0, 1);
+        day.set(LIFECYCLE + INACTIVE_PERIOD, 0, 1);
+
+        let fish = self.fish.map(u128::from);
+        let fish = mat_pow(&day, days)?.checked_mul_vector(&fish)?;
+        fish.into_iter()
+            .try_fold(0u128, |sum, n| sum.checked_add(n))
+    }
+}
+
+impl FromLines for Day6 {
+    fn from_lines<'a, I>(mut lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let line = lines
+            .find(|line| !line.is_empty())
+            .ok_or("missing fish timers")?;
+        let mut fish = [0; LIFECYCLE + INACTIVE_PERIOD + 1];
+        for n in line.trim().split(',') {
+            let timer = n.parse::<usize>()?;
+            *fish
+                .get_mut(timer)
+                .ok_or_else(|| format!("invalid fish timer {}", timer))? += 1;
+        }
+        Ok(Self { fish })
     }
 }
 
 impl Puzzle for Day6 {
     // How many lanternfish would there be after 80 days?
     fn part_1(&self) -> Result<Solution> {
-        let days = 80;
-        for _ in 0..days {
-            self.simulate_day();
-        }
-
-        Ok(self.fish.borrow().iter().sum::<u64>().into())
+        let mut school = self.school();
+        school.run(80);
+        Ok(school.size().into())
     }
 
     // How many lanternfish would there be after 256 days?
     fn part_2(&self) -> Result<Solution> {
-        // note: re-initialize the fish array
-        self.initialize_fish_array();
+        let mut school = self.school();
+        school.run(256);
+        Ok(school.size().into())
+    }
+}
 
-        let days = 256;
-        for _ in 0..days {
-            self.simulate_day();
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_after() {
+        let day = Day6::new("3,4,3,1,2").unwrap();
+        assert_eq!(day.count_after(18), Some(26));
+        assert_eq!(day.count_after(80), Some(5934));
+        assert_eq!(day.count_after(256), Some(26984457539));
+        assert_eq!(day.part_2().unwrap().to_string(), "26984457539");
+        assert_eq!(day.count_after(1_000_000), None);
+    }
 
-        Ok(self.fish.borrow().iter().sum::<u64>().into())
+    #[test]
+    fn test_simulate() {
+        let day = Day6::new("3,4,3,1,2").unwrap();
+        let mut school = day.school();
+        school.run(18);
+        assert_eq!(school.size(), 26);
+        assert_eq!(day.part_1().unwrap().to_string(), "5934");
     }
 }
@@ -3,16 +3,34 @@
 ** https://adventofcode.com/2021/day/7
 */
 
-use crate::types::{Puzzle, Result, Solution};
+use crate::explain;
+use crate::types::{FromLines, Puzzle, Result, Solution};
+use crate::utils;
 
 pub struct Day7 {
     input: Vec<i64>,
 }
 
 impl Day7 {
-    pub fn new(input: &'static str) -> Self {
-        let input = input.split(',').map(|n| n.parse().unwrap()).collect();
-        Self { input }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+}
+
+impl FromLines for Day7 {
+    fn from_lines<'a, I>(mut lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let line = lines
+            .find(|line| !line.is_empty())
+            .ok_or("missing crab positions")?;
+        let input = line
+            .trim()
+            .split(',')
+            .map(|n| n.parse())
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { input })
     }
 }
 
@@ -25,6 +43,7 @@ impl Puzzle for Day7 {
         let mut numbers = self.input.clone();
         numbers.sort_unstable();
         let median = numbers[numbers.len() / 2];
+        explain::report("position", median);
 
         // determine the fuel used to align all crabs at the median
         let fuel = self.input.iter().map(|n| i64::abs(n - median)).sum::<i64>();
@@ -36,7 +55,8 @@ impl Puzzle for Day7 {
     fn part_2(&self) -> Result<Solution> {
         // the most efficient position is the average of the inputs
         let average = self.input.iter().sum::<i64>() as f64 / self.input.len() as f64;
-        let average_int = average.floor() as i64;
+        let average_int: i64 = utils::cast(average.floor())?;
+        explain::report("position", average_int);
 
         // determine the fuel used to align all crabs at the median
         let fuel = self
@@ -3,7 +3,7 @@
 ** https://adventofcode.com/2021/day/8
 */
 
-use crate::types::{Puzzle, Result, Solution};
+use crate::types::{Context, FromLines, Puzzle, Result, Solution};
 use crate::utils;
 
 use std::collections::HashMap;
@@ -20,17 +20,17 @@ enum Segment {
     G,
 }
 
-impl From<char> for Segment {
-    fn from(c: char) -> Self {
+impl Segment {
+    fn parse(c: char) -> Result<Self> {
         match c {
-            'a' => Self::A,
-            'b' => Self::B,
-            'c' => Self::C,
-            'd' => Self::D,
-            'e' => Self::E,
-            'f' => Self::F,
-            'g' => Self::G,
-            _ => panic!("invalid character: {}", c),
+            'a' => Ok(Self::A),
+            'b' => Ok(Self::B),
+            'c' => Ok(Self::C),
+            'd' => Ok(Self::D),
+            'e' => Ok(Self::E),
+            'f' => Ok(Self::F),
+            'g' => Ok(Self::G),
+            _ => Err(format!("invalid segment {:?}", c).into()),
         }
     }
 }
@@ -139,15 +139,18 @@ impl SevenSegment {
     }
 }
 
-impl From<&str> for SevenSegment {
-    fn from(s: &str) -> Self {
+impl SevenSegment {
+    fn parse(s: &str) -> Result<Self> {
         let mut segment_inner = [None; 7];
+        if s.is_empty() || s.chars().count() > segment_inner.len() {
+            return Err(format!("invalid pattern {:?}", s).into());
+        }
 
         for (i, c) in s.chars().enumerate() {
-            segment_inner[i] = Some(Segment::from(c));
+            segment_inner[i] = Some(Segment::parse(c)?);
         }
 
-        Self { segment_inner }
+        Ok(Self { segment_inner })
     }
 }
 
@@ -269,25 +272,27 @@ impl Entry {
     }
 }
 
-impl From<&str> for Entry {
-    fn from(s: &str) -> Self {
+// parses exactly N space-separated patterns
+fn parse_patterns<const N: usize>(s: &str) -> Result<[SevenSegment; N]> {
+    let patterns = s
+        .split(' ')
+        .map(SevenSegment::parse)
+        .collect::<Result<Vec<_>>>()?;
+    let n_patterns = patterns.len();
+    patterns
+        .try_into()
+        .map_err(|_| format!("expected {} patterns, found {}", N, n_patterns).into())
+}
+
+impl Entry {
+    fn parse(s: &str) -> Result<Self> {
         match split!(s, " | ") {
             [signals_str, output_str] => {
-                let signals = signals_str
-                    .split(' ')
-                    .map(SevenSegment::from)
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap();
-                let output = output_str
-                    .split(' ')
-                    .map(SevenSegment::from)
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap();
-                Self::new(signals, output)
+                let signals = parse_patterns(signals_str).context("signals")?;
+                let output = parse_patterns(output_str).context("output")?;
+                Ok(Self::new(signals, output))
             }
-            _ => unreachable!(),
+            _ => Err(format!("invalid entry {:?}", s).into()),
         }
     }
 }
@@ -297,12 +302,22 @@ pub struct Day8 {
 }
 
 impl Day8 {
-    pub fn new(input: &'static str) -> Self {
-        let mut entries = Vec::new();
-        for line in utils::input_to_lines(input) {
-            entries.push(Entry::from(line));
-        }
-        Self { entries }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
+    }
+}
+
+impl FromLines for Day8 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let entries = lines
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(i, line)| Entry::parse(line).with_context(|| format!("entry {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
     }
 }
 
@@ -346,7 +361,7 @@ mod tests {
     #[test]
     fn test_solve() {
         let entry_string = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe";
-        let entry = Entry::from(entry_string);
+        let entry = Entry::parse(entry_string).unwrap();
 
         let mut exp = HashMap::new();
         exp.insert(Segment::A, Segment::E);
@@ -365,9 +380,27 @@ mod tests {
     fn test_solve_sum() {
         let entry_string =
             "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
-        let entry = Entry::from(entry_string);
+        let entry = Entry::parse(entry_string).unwrap();
 
         let sol = entry.solve_segments();
         assert_eq!(entry.output_value(&sol), 5353);
     }
+
+    #[test]
+    fn test_parse_malformed() {
+        let err = |input: &str| Day8::from_lines(input.lines()).err().unwrap().to_string();
+        assert_eq!(err("x,y\n\nzz 3"), "entry 1: invalid entry \"x,y\"");
+        assert_eq!(
+            err("ab | cd"),
+            "entry 1: signals: expected 10 patterns, found 1"
+        );
+        assert_eq!(
+            err("a b c d e f g ab ac ad | ab cd ef gz"),
+            "entry 1: output: invalid segment 'z'"
+        );
+        assert_eq!(
+            err("a b c d e f g ab ac ad | ab cd ef abcdefga"),
+            "entry 1: output: invalid pattern \"abcdefga\""
+        );
+    }
 }
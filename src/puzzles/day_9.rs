@@ -3,10 +3,11 @@
 ** https://adventofcode.com/2021/day/9
 */
 
-use crate::types::{Array2D, Puzzle, Result, Solution};
+use crate::types::{Array2D, Coord, FromLines, InlineVec, Puzzle, Result, Solution};
+use crate::viz::{heatmap, Frame, Heatmap, Visualize};
 
 use std::collections::{HashSet, VecDeque};
-use std::convert::TryInto;
+use std::iter;
 
 const WIDTH: usize = 100;
 const HEIGHT: usize = 100;
@@ -16,75 +17,84 @@ pub struct Day9 {
 }
 
 impl Day9 {
-    pub fn new(input: &'static str) -> Self {
-        let heightmap = Array2D::from(input);
-        Self { heightmap }
+    pub fn new(input: &'static str) -> Result<Self> {
+        Self::from_lines(input.lines())
     }
 
-    fn neighbors(&self, i: usize, j: usize) -> [Option<u8>; 4] {
-        Array2D::<u8, WIDTH, HEIGHT>::neighbors(i, j)
-            .iter()
-            .map(|n| n.map(|(i, j)| self.heightmap.get(i, j)))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+    // the heights of the neighbors within the heightmap
+    fn neighbors(&self, coord: Coord) -> InlineVec<u8, 4> {
+        Array2D::<u8, WIDTH, HEIGHT>::neighbors(coord)
+            .into_iter()
+            .map(|neighbor| self.heightmap.get(neighbor))
+            .collect()
     }
 
-    fn neighbors_with_coords(&self, i: usize, j: usize) -> [Option<(usize, usize, u8)>; 4] {
-        Array2D::<u8, WIDTH, HEIGHT>::neighbors(i, j)
-            .iter()
-            .map(|n| n.map(|(i, j)| (i, j, self.heightmap.get(i, j))))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+    fn is_lowpoint(&self, coord: Coord) -> bool {
+        let here = self.heightmap.get(coord);
+        self.neighbors(coord).iter().all(|&x| x > here)
     }
 
-    fn is_lowpoint(&self, i: usize, j: usize) -> bool {
-        let here = self.heightmap.get(i, j);
-        self.neighbors(i, j)
-            .iter()
-            .filter_map(|&x| x)
-            .all(|x| x > here)
-    }
-
-    fn basin_size(&self, i: usize, j: usize) -> usize {
+    fn basin_size(&self, coord: Coord) -> usize {
         // points to be explored
         let mut frontier = VecDeque::new();
         // points already explored
         let mut explored = HashSet::new();
 
         // start with the given point
-        frontier.push_back((i, j));
+        frontier.push_back(coord);
 
-        while !frontier.is_empty() {
-            // pop from the front of the frontier
-            let (ii, jj) = frontier.pop_front().unwrap();
+        while let Some(point) = frontier.pop_front() {
             // add unexplored neighbors to the frontier
             // note: exclude neighbors at the maximum height (9)
-            for (iii, jjj, v) in self.neighbors_with_coords(ii, jj).iter().flatten() {
-                if !explored.contains(&(*iii, *jjj)) && *v < 9 {
-                    frontier.push_back((*iii, *jjj));
+            for neighbor in Array2D::<u8, WIDTH, HEIGHT>::neighbors(point).iter() {
+                if !explored.contains(neighbor) && self.heightmap.get(*neighbor) < 9 {
+                    frontier.push_back(*neighbor);
                 }
             }
             // add the current point to the explored set
-            explored.insert((ii, jj));
+            explored.insert(point);
         }
 
         explored.len()
     }
 }
 
+impl Visualize for Day9 {
+    // the heightmap, where lower points are brighter so that the basins stand out
+    fn frames(&self) -> impl Iterator<Item = Frame> {
+        let frame = Frame::from_fn(WIDTH, HEIGHT, |i, j| {
+            (9 - self.heightmap.get(Coord::new(i, j))) * 28
+        });
+        iter::once(frame)
+    }
+}
+
+impl Heatmap for Day9 {
+    // the heights themselves, where higher points are brighter
+    fn heatmap(&self) -> Frame {
+        heatmap::from_array(&self.heightmap)
+    }
+}
+
+impl FromLines for Day9 {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let heightmap = Array2D::parse_digits(&lines.collect::<Vec<_>>().join("\n"))?;
+        Ok(Self { heightmap })
+    }
+}
+
 impl Puzzle for Day9 {
     // Find all of the low points on your heightmap. What is the sum of the
     // risk levels of all low points on your heightmap?
     fn part_1(&self) -> Result<Solution> {
         let mut sum = 0;
 
-        for i in 0..HEIGHT {
-            for j in 0..WIDTH {
-                if self.is_lowpoint(i, j) {
-                    sum += 1 + self.heightmap.get(i, j) as u64;
-                }
+        for coord in self.heightmap.coords() {
+            if self.is_lowpoint(coord) {
+                sum += 1 + self.heightmap.get(coord) as u64;
             }
         }
 
@@ -95,9 +105,11 @@ impl Puzzle for Day9 {
     // basins?
     fn part_2(&self) -> Result<Solution> {
         // gather all low points and determine the sizes of their corresponding basins
-        let mut lowpoints = itertools::iproduct!(0..HEIGHT, 0..WIDTH)
-            .filter(|(i, j)| self.is_lowpoint(*i, *j))
-            .map(|(i, j)| self.basin_size(i, j))
+        let mut lowpoints = self
+            .heightmap
+            .coords()
+            .filter(|&coord| self.is_lowpoint(coord))
+            .map(|coord| self.basin_size(coord))
             .collect::<Vec<_>>();
         // sort and grab the 3 largest basins
         lowpoints.sort_unstable();
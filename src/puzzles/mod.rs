@@ -2,32 +2,43 @@
 ** src/puzzles/mod.rs
 */
 
-mod day_1;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
-mod day_16;
-mod day_17;
-mod day_18;
-mod day_19;
-mod day_2;
-mod day_20;
-mod day_21;
-mod day_22;
-mod day_3;
-mod day_4;
-mod day_5;
-mod day_6;
-mod day_7;
-mod day_8;
-mod day_9;
-
-use crate::types::Puzzle;
-
-const INPUTS: [&str; 22] = [
+pub mod day_1;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod day_17;
+pub mod day_18;
+pub mod day_19;
+pub mod day_2;
+pub mod day_20;
+pub mod day_21;
+pub mod day_22;
+pub mod day_3;
+pub mod day_4;
+pub mod day_5;
+pub mod day_6;
+pub mod day_7;
+pub mod day_8;
+pub mod day_9;
+
+use crate::inputs;
+use crate::types::{FromLines, Puzzle, Result};
+
+use std::io::{self, BufRead};
+use std::sync::RwLock;
+
+// the number of days solved in this crate
+pub const N_DAYS: usize = 22;
+// the last day of the calendar, up to which registered puzzles may fill in the rest
+pub const MAX_DAY: usize = 25;
+
+// note: inputs are only bundled with the bundled-inputs feature, see inputs::load
+#[cfg(feature = "bundled-inputs")]
+pub const INPUTS: [&str; N_DAYS] = [
     include_str!("../../input/1.txt"),
     include_str!("../../input/2.txt"),
     include_str!("../../input/3.txt"),
@@ -52,29 +63,195 @@ const INPUTS: [&str; 22] = [
     include_str!("../../input/22.txt"),
 ];
 
-pub fn all() -> Vec<Box<dyn Puzzle>> {
-    vec![
-        Box::new(day_1::Day1::new(INPUTS[0])),
-        Box::new(day_2::Day2::new(INPUTS[1])),
-        Box::new(day_3::Day3::new(INPUTS[2])),
-        Box::new(day_4::Day4::new(INPUTS[3])),
-        Box::new(day_5::Day5::new(INPUTS[4])),
-        Box::new(day_6::Day6::new(INPUTS[5])),
-        Box::new(day_7::Day7::new(INPUTS[6])),
-        Box::new(day_8::Day8::new(INPUTS[7])),
-        Box::new(day_9::Day9::new(INPUTS[8])),
-        Box::new(day_10::Day10::new(INPUTS[9])),
-        Box::new(day_11::Day11::new(INPUTS[10])),
-        Box::new(day_12::Day12::new(INPUTS[11])),
-        Box::new(day_13::Day13::new(INPUTS[12])),
-        Box::new(day_14::Day14::new(INPUTS[13])),
-        Box::new(day_15::Day15::new(INPUTS[14])),
-        Box::new(day_16::Day16::new(INPUTS[15])),
-        Box::new(day_17::Day17::new(INPUTS[16])),
-        Box::new(day_18::Day18::new(INPUTS[17])),
-        Box::new(day_19::Day19::new(INPUTS[18])),
-        Box::new(day_20::Day20::new(INPUTS[19])),
-        Box::new(day_21::Day21::new(INPUTS[20])),
-        Box::new(day_22::Day22::new(INPUTS[21])),
-    ]
+// a puzzle from outside this crate, such as an experimental solution, which is registered at
+// startup and then runs like any other, in place of the built-in puzzle for its day if there is
+// one
+#[derive(Clone, Copy)]
+struct Plugin {
+    day: usize,
+    from_lines: fn(&mut dyn Iterator<Item = &str>) -> Result<Box<dyn Puzzle>>,
+}
+
+static PLUGINS: RwLock<Vec<Plugin>> = RwLock::new(Vec::new());
+
+fn plugin_from_lines<P>(lines: &mut dyn Iterator<Item = &str>) -> Result<Box<dyn Puzzle>>
+where
+    P: Puzzle + FromLines + 'static,
+{
+    P::from_lines(lines).map(boxed)
+}
+
+// registers a puzzle for the given day, replacing any puzzle registered for it before
+pub fn register<P>(day: usize) -> Result<()>
+where
+    P: Puzzle + FromLines + 'static,
+{
+    if !(1..=MAX_DAY).contains(&day) {
+        return Err(format!(
+            "cannot register day {}, days run from 1 to {}",
+            day, MAX_DAY
+        )
+        .into());
+    }
+    let mut plugins = PLUGINS.write().unwrap();
+    plugins.retain(|plugin| plugin.day != day);
+    plugins.push(Plugin {
+        day,
+        from_lines: plugin_from_lines::<P>,
+    });
+    Ok(())
+}
+
+fn plugin(day: usize) -> Option<Plugin> {
+    PLUGINS
+        .read()
+        .unwrap()
+        .iter()
+        .find(|plugin| plugin.day == day)
+        .copied()
+}
+
+pub fn is_registered(day: usize) -> bool {
+    plugin(day).is_some()
+}
+
+pub fn is_known(day: usize) -> bool {
+    (1..=N_DAYS).contains(&day) || is_registered(day)
+}
+
+// every day with a puzzle, built-in or registered, in order
+pub fn days() -> Vec<usize> {
+    (1..=MAX_DAY).filter(|&day| is_known(day)).collect()
+}
+
+fn boxed<P: Puzzle + 'static>(puzzle: P) -> Box<dyn Puzzle> {
+    Box::new(puzzle)
+}
+
+// parses a puzzle a line at a time, such that its input need not live for the whole program
+pub fn from_lines<'a, I>(day: usize, lines: I) -> Option<Result<Box<dyn Puzzle>>>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let _span = tracing::info_span!("parse", day).entered();
+    if let Some(plugin) = plugin(day) {
+        let mut lines = lines;
+        return Some((plugin.from_lines)(&mut lines));
+    }
+    let puzzle = match day {
+        1 => day_1::Day1::from_lines(lines).map(boxed),
+        2 => day_2::Day2::from_lines(lines).map(boxed),
+        3 => day_3::Day3::from_lines(lines).map(boxed),
+        4 => day_4::Day4::from_lines(lines).map(boxed),
+        5 => day_5::Day5::from_lines(lines).map(boxed),
+        6 => day_6::Day6::from_lines(lines).map(boxed),
+        7 => day_7::Day7::from_lines(lines).map(boxed),
+        8 => day_8::Day8::from_lines(lines).map(boxed),
+        9 => day_9::Day9::from_lines(lines).map(boxed),
+        10 => day_10::Day10::from_lines(lines).map(boxed),
+        11 => day_11::Day11::from_lines(lines).map(boxed),
+        12 => day_12::Day12::from_lines(lines).map(boxed),
+        13 => day_13::Day13::from_lines(lines).map(boxed),
+        14 => day_14::Day14::from_lines(lines).map(boxed),
+        15 => day_15::Day15::from_lines(lines).map(boxed),
+        16 => day_16::Day16::from_lines(lines).map(boxed),
+        17 => day_17::Day17::from_lines(lines).map(boxed),
+        18 => day_18::Day18::from_lines(lines).map(boxed),
+        19 => day_19::Day19::from_lines(lines).map(boxed),
+        20 => day_20::Day20::from_lines(lines).map(boxed),
+        21 => day_21::Day21::from_lines(lines).map(boxed),
+        22 => day_22::Day22::from_lines(lines).map(boxed),
+        _ => return None,
+    };
+    Some(puzzle)
+}
+
+pub fn from_reader<R>(day: usize, reader: R) -> Option<Result<Box<dyn Puzzle>>>
+where
+    R: BufRead,
+{
+    let lines = match reader.lines().collect::<io::Result<Vec<_>>>() {
+        Ok(lines) => lines,
+        Err(err) => return Some(Err(err.into())),
+    };
+    from_lines(day, lines.iter().map(String::as_str))
+}
+
+pub fn all() -> Result<Vec<Box<dyn Puzzle>>> {
+    days()
+        .into_iter()
+        .map(|day| {
+            let input = inputs::load(day)?;
+            from_lines(day, input.lines())
+                .ok_or_else(|| format!("day {} is not a known puzzle", day))?
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Solution;
+
+    // counts the lines of its input
+    struct LineCount(usize);
+
+    impl FromLines for LineCount {
+        fn from_lines<'a, I>(lines: I) -> Result<Self>
+        where
+            I: Iterator<Item = &'a str>,
+        {
+            Ok(Self(lines.count()))
+        }
+    }
+
+    impl Puzzle for LineCount {
+        fn part_1(&self) -> Result<Solution> {
+            Ok(self.0.into())
+        }
+
+        fn part_2(&self) -> Result<Solution> {
+            Ok((self.0 * 2).into())
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        // note: the registry is shared by every test, so only the last day is registered
+        assert!(!is_known(MAX_DAY));
+        register::<LineCount>(MAX_DAY).unwrap();
+        assert!(is_known(MAX_DAY) && is_registered(MAX_DAY));
+        assert_eq!(days().last(), Some(&MAX_DAY));
+        assert_eq!(days().len(), N_DAYS + 1);
+
+        let puzzle = from_lines(MAX_DAY, "a\nb\nc".lines()).unwrap().unwrap();
+        assert_eq!(puzzle.part_1().unwrap().to_string(), "3");
+        let puzzle = from_lines(MAX_DAY, ["a", "b"].into_iter())
+            .unwrap()
+            .unwrap();
+        assert_eq!(puzzle.part_2().unwrap().to_string(), "4");
+
+        assert!(register::<LineCount>(0).is_err());
+        assert!(register::<LineCount>(MAX_DAY + 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        // no parser panics on malformed input, only reporting it as an error or reading what
+        // it can of it
+        let inputs = [
+            "x,y\n\nzz 3",
+            "",
+            "\n\n\n",
+            "[[[[[[[",
+            "0\n-1 -> ,\n\n#=99999999999999999999",
+        ];
+        for day in 1..=N_DAYS {
+            for input in inputs {
+                let result =
+                    std::panic::catch_unwind(|| from_lines(day, input.lines()).unwrap().is_err());
+                assert!(result.is_ok(), "day {} panicked parsing {:?}", day, input);
+            }
+        }
+    }
 }
@@ -0,0 +1,188 @@
+/*
+** src/server.rs
+*/
+
+use aoc2021::puzzles;
+use aoc2021::types::{Provenance, RunError, RunResult, Severity};
+use aoc2021::utils;
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use std::io::Read;
+use std::panic;
+use std::time::Instant;
+
+type HttpResponse = Response<std::io::Cursor<Vec<u8>>>;
+
+// the largest request body accepted, well above the size of any puzzle input
+const MAX_BODY_BYTES: usize = 1 << 20;
+
+// parses the day and part out of a /solve/{day}/{part} path
+fn parse_path(path: &str) -> Option<(usize, usize)> {
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        ["", "solve", day, part] => {
+            let day = day.parse().ok()?;
+            let part = part.parse().ok()?;
+            if part == 1 || part == 2 {
+                Some((day, part))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn error_response(status: u16, message: &str) -> HttpResponse {
+    let body = serde_json::json!({ "error": message }).to_string();
+    json_response(body).with_status_code(status)
+}
+
+fn json_response(body: String) -> HttpResponse {
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_string(body).with_header(header)
+}
+
+fn handle(request: &mut Request) -> HttpResponse {
+    let (day, part) = match parse_path(request.url()) {
+        Some(path) => path,
+        None => return error_response(404, "expected /solve/{day}/{part}"),
+    };
+    if *request.method() != Method::Post {
+        return error_response(405, "expected a POST request");
+    }
+
+    if request
+        .body_length()
+        .is_some_and(|len| len > MAX_BODY_BYTES)
+    {
+        return error_response(413, "puzzle input too large");
+    }
+    solve(day, part, request.as_reader())
+}
+
+// parses the body as the day's input and solves the given part of it
+fn solve<R>(day: usize, part: usize, body: R) -> HttpResponse
+where
+    R: Read,
+{
+    // note: the length is only known up front if the client sends it, so the body is read up to
+    // one byte past the limit to tell whether it is over
+    let mut input = String::new();
+    if let Err(err) = body
+        .take(MAX_BODY_BYTES as u64 + 1)
+        .read_to_string(&mut input)
+    {
+        return error_response(400, &format!("invalid puzzle input: {}", err));
+    }
+    if input.len() > MAX_BODY_BYTES {
+        return error_response(413, "puzzle input too large");
+    }
+
+    // note: a panic in a single request must not take down the server, which serves requests
+    // one at a time
+    let start = Instant::now();
+    let puzzle = match panic::catch_unwind(|| puzzles::from_lines(day, input.lines())) {
+        Ok(puzzle) => puzzle,
+        Err(payload) => return panic_response(RunError::from_panic(payload)),
+    };
+    match puzzle {
+        Some(Ok(puzzle)) => {
+            let provenance = Provenance {
+                iterations: Some(1),
+                parse_duration: Some(start.elapsed()),
+                input_hash: Some(utils::input_digest(&input)),
+                ..Provenance::default()
+            };
+            // note: RunResult catches panics in solving
+            let result = RunResult::new(day, part, puzzle.as_ref()).with_provenance(provenance);
+            match result.answer {
+                Err(err) if err.severity == Severity::Panic => panic_response(err),
+                _ => match serde_json::to_string(&result) {
+                    Ok(body) => json_response(body),
+                    Err(err) => error_response(500, &err.to_string()),
+                },
+            }
+        }
+        Some(Err(err)) => error_response(400, &format!("invalid puzzle input: {}", err)),
+        None => error_response(404, "no puzzle for the requested day"),
+    }
+}
+
+fn panic_response(err: RunError) -> HttpResponse {
+    error_response(500, &format!("puzzle panicked: {}", err.message))
+}
+
+pub fn serve(port: u16) {
+    let server = match Server::http(("0.0.0.0", port)) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("failed to start the server: {}", err);
+            std::process::exit(1);
+        }
+    };
+    println!("listening on port {}", port);
+
+    for mut request in server.incoming_requests() {
+        let response = handle(&mut request);
+        if let Err(err) = request.respond(response) {
+            eprintln!("failed to respond to request: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2021::types::{FromLines, Puzzle, Result, Solution};
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(parse_path("/solve/1/2"), Some((1, 2)));
+        assert_eq!(parse_path("/solve/22/1"), Some((22, 1)));
+        assert_eq!(parse_path("/solve/1/3"), None);
+        assert_eq!(parse_path("/solve/x/1"), None);
+        assert_eq!(parse_path("/solve/1"), None);
+        assert_eq!(parse_path("/"), None);
+    }
+
+    #[test]
+    fn test_solve() {
+        puzzles::register::<Panicking>(puzzles::MAX_DAY).unwrap();
+        let status = |day, body: &[u8]| solve(day, 1, body).status_code().0;
+        assert_eq!(status(2, b"forward 5\ndown 5"), 200);
+        assert_eq!(status(2, b"x,y\n\nzz 3"), 400);
+        assert_eq!(status(99, b""), 404);
+        assert_eq!(status(2, &vec![b'\n'; MAX_BODY_BYTES + 1]), 413);
+
+        // panics in parsing or solving fail the request alone
+        assert_eq!(status(puzzles::MAX_DAY, b"panic"), 500);
+        assert_eq!(status(puzzles::MAX_DAY, b"solve"), 500);
+        assert_eq!(status(puzzles::MAX_DAY, b"0"), 200);
+    }
+
+    // panics on parsing the line "panic", and on solving any other line but "0"
+    struct Panicking(String);
+
+    impl FromLines for Panicking {
+        fn from_lines<'a, I>(mut lines: I) -> Result<Self>
+        where
+            I: Iterator<Item = &'a str>,
+        {
+            let line = lines.next().unwrap_or_default();
+            assert_ne!(line, "panic", "failed to parse");
+            Ok(Self(line.to_string()))
+        }
+    }
+
+    impl Puzzle for Panicking {
+        fn part_1(&self) -> Result<Solution> {
+            assert_eq!(self.0, "0", "failed to solve");
+            Ok(0.into())
+        }
+
+        fn part_2(&self) -> Result<Solution> {
+            self.part_1()
+        }
+    }
+}
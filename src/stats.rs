@@ -0,0 +1,142 @@
+/*
+** src/stats.rs
+*/
+
+use crate::cache::Cache;
+use crate::client::Client;
+use crate::history;
+
+use aoc2021::types::Result;
+
+use serde::Serialize;
+
+use std::collections::HashMap;
+use std::process;
+
+// the personal times page no longer changes once the event is over, so it is cached
+const PERSONAL_TIMES_PAGE: &str = "personal-times.html";
+
+// how long it took me to solve a part, against how long the program takes to run it
+#[derive(Debug, PartialEq, Serialize)]
+struct PartStats {
+    day: usize,
+    part: usize,
+    // as displayed on the personal times page, e.g. "00:12:34" or ">24h"
+    solve_time: Option<String>,
+    rank: Option<u64>,
+    runtime_ms: Option<f64>,
+}
+
+// parses the rows of the personal times table, which look like:
+//   day   time   rank  score   time   rank  score
+// where the part 2 columns are "-" if it has not been solved
+fn parse_personal_times(page: &str) -> Vec<PartStats> {
+    let mut stats = vec![];
+    for line in page.lines() {
+        let columns = line.split_whitespace().collect::<Vec<_>>();
+        let day = match columns.first().and_then(|day| day.parse::<usize>().ok()) {
+            Some(day) if columns.len() == 7 => day,
+            _ => continue,
+        };
+        for part in 1..=2 {
+            let time = columns[(part * 3) - 2];
+            let rank = columns[(part * 3) - 1];
+            stats.push(PartStats {
+                day,
+                part,
+                solve_time: Some(time).filter(|&t| t != "-").map(String::from),
+                rank: rank.parse().ok(),
+                runtime_ms: None,
+            });
+        }
+    }
+    stats.sort_by_key(|s| (s.day, s.part));
+    stats
+}
+
+fn personal_times(cache: &Cache) -> Result<String> {
+    if let Some(page) = cache.get_page(PERSONAL_TIMES_PAGE) {
+        return Ok(page);
+    }
+    let page = Client::new()?.get("leaderboard/self")?;
+    cache.insert_page(PERSONAL_TIMES_PAGE, &page)?;
+    Ok(page)
+}
+
+fn render(stats: &[PartStats]) -> String {
+    let mut lines = vec![format!(
+        "{:>3}  {:>4}  {:>10}  {:>7}  {:>12}",
+        "day", "part", "solve time", "rank", "runtime"
+    )];
+    for s in stats.iter() {
+        lines.push(format!(
+            "{:>3}  {:>4}  {:>10}  {:>7}  {:>12}",
+            s.day,
+            s.part,
+            s.solve_time.as_deref().unwrap_or("-"),
+            s.rank
+                .map(|rank| rank.to_string())
+                .unwrap_or_else(|| "-".into()),
+            s.runtime_ms
+                .map(|ms| format!("{:.3}ms", ms))
+                .unwrap_or_else(|| "-".into())
+        ));
+    }
+    lines.join("\n")
+}
+
+pub fn stats(json: bool) {
+    let cache = Cache::new();
+    let page = match personal_times(&cache) {
+        Ok(page) => page,
+        Err(err) => {
+            eprintln!("failed to fetch personal times: {}", err);
+            process::exit(1);
+        }
+    };
+    let durations = history::latest_durations().unwrap_or_else(|_| HashMap::new());
+
+    let mut stats = parse_personal_times(&page);
+    for s in stats.iter_mut() {
+        s.runtime_ms = durations.get(&(s.day, s.part)).copied();
+    }
+
+    if json {
+        match serde_json::to_string_pretty(&stats) {
+            Ok(json) => println!("{}", json),
+            Err(err) => eprintln!("failed to serialize stats: {}", err),
+        }
+    } else {
+        println!("{}", render(&stats));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PAGE: &str = "<pre>      --------Part 1--------   --------Part 2--------
+Day       Time   Rank  Score       Time   Rank  Score
+  2   00:04:10    987      0   00:06:01    901      0
+  1       >24h  75012      0          -      -      -
+</pre>";
+
+    #[test]
+    fn test_parse_personal_times() {
+        let stats = parse_personal_times(TEST_PAGE);
+        assert_eq!(stats.len(), 4);
+        assert_eq!(
+            stats[0],
+            PartStats {
+                day: 1,
+                part: 1,
+                solve_time: Some(String::from(">24h")),
+                rank: Some(75012),
+                runtime_ms: None,
+            }
+        );
+        assert_eq!(stats[1].solve_time, None);
+        assert_eq!(stats[1].rank, None);
+        assert_eq!(stats[3].solve_time, Some(String::from("00:06:01")));
+    }
+}
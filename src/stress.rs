@@ -0,0 +1,211 @@
+/*
+** src/stress.rs
+*/
+
+// random puzzle inputs within each puzzle's spec, for stressing the solutions at larger than
+// official input sizes
+
+use std::ops::RangeInclusive;
+
+// a small xorshift64* generator, seeded such that any input can be reproduced from its seed
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // note: the state must not be zero, so the seed is scrambled with a splitmix64 step
+        let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        Self {
+            state: (z ^ (z >> 31)) | 1,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    // note: slightly biased towards the low end of wide ranges, which is fine for test inputs
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = range.end().abs_diff(*range.start()) + 1;
+        range.start().wrapping_add_unsigned(self.next_u64() % span)
+    }
+
+    // true with the given probability, in percent
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+}
+
+pub struct Generator {
+    pub day: usize,
+    // the number of lines generated by default, larger than the official inputs
+    pub default_size: usize,
+    pub generate: fn(&mut Rng, usize) -> String,
+}
+
+pub const GENERATORS: [Generator; 5] = [
+    Generator {
+        day: 5,
+        default_size: 1000,
+        generate: vent_lines,
+    },
+    Generator {
+        day: 14,
+        default_size: 102,
+        generate: insertion_rules,
+    },
+    Generator {
+        day: 18,
+        default_size: 200,
+        generate: snailfish_numbers,
+    },
+    Generator {
+        day: 21,
+        default_size: 2,
+        generate: starting_positions,
+    },
+    Generator {
+        day: 22,
+        default_size: 600,
+        generate: reboot_steps,
+    },
+];
+
+pub fn generator(day: usize) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|generator| generator.day == day)
+}
+
+// horizontal, vertical and 45-degree diagonal lines on a 1000x1000 grid
+fn vent_lines(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let (x0, y0) = (rng.range(0..=999), rng.range(0..=999));
+        let (x1, y1) = match rng.range(0..=2) {
+            0 => (x0, rng.range(0..=999)),
+            1 => (rng.range(0..=999), y0),
+            _ => {
+                // the furthest that the diagonal can run in its direction without leaving the grid
+                let (dx, dy) = (rng.range(0..=1) * 2 - 1, rng.range(0..=1) * 2 - 1);
+                let max_x = if dx > 0 { 999 - x0 } else { x0 };
+                let max_y = if dy > 0 { 999 - y0 } else { y0 };
+                let length = rng.range(0..=max_x.min(max_y));
+                (x0 + (dx * length), y0 + (dy * length))
+            }
+        };
+        input.push_str(&format!("{},{} -> {},{}\n", x0, y0, x1, y1));
+    }
+    input
+}
+
+// a template and then insertion rules for distinct pairs, over just enough elements to have
+// that many pairs
+fn insertion_rules(rng: &mut Rng, size: usize) -> String {
+    let n_rules = size.saturating_sub(2);
+    let n_elements = (1..=26).find(|n| n * n >= n_rules).unwrap_or(26) as i64;
+    let element = |rng: &mut Rng| (b'A' + rng.range(0..=(n_elements - 1)) as u8) as char;
+    let template = (0..rng.range(2..=20))
+        .map(|_| element(rng))
+        .collect::<String>();
+    let mut pairs = (0..n_elements)
+        .flat_map(|a| (0..n_elements).map(move |b| (a, b)))
+        .collect::<Vec<_>>();
+    let mut input = format!("{}\n\n", template);
+    for _ in 0..n_rules.min(pairs.len()) {
+        let (a, b) = pairs.swap_remove(rng.range(0..=(pairs.len() as i64 - 1)) as usize);
+        input.push_str(&format!(
+            "{}{} -> {}\n",
+            (b'A' + a as u8) as char,
+            (b'A' + b as u8) as char,
+            element(rng)
+        ));
+    }
+    input
+}
+
+// a reduced snailfish number: no pair is nested inside four others and every number is below 10
+fn snailfish_number(rng: &mut Rng, depth: usize) -> String {
+    // pairs become more likely to be regular numbers the deeper they are
+    if depth > 0 && (depth == 4 || rng.chance(25 * depth as u64)) {
+        return rng.range(0..=9).to_string();
+    }
+    format!(
+        "[{},{}]",
+        snailfish_number(rng, depth + 1),
+        snailfish_number(rng, depth + 1)
+    )
+}
+
+fn snailfish_numbers(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| snailfish_number(rng, 0) + "\n").collect()
+}
+
+// note: there are always exactly two players, so the size is ignored
+fn starting_positions(rng: &mut Rng, _size: usize) -> String {
+    (1..=2)
+        .map(|player| {
+            format!(
+                "Player {} starting position: {}\n",
+                player,
+                rng.range(1..=10)
+            )
+        })
+        .collect()
+}
+
+// reboot steps which start within the initialization region and then spread far beyond it
+fn reboot_steps(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for i in 0..size {
+        let (extent, length) = if i < size / 20 {
+            (50, 50)
+        } else {
+            (100_000, 40_000)
+        };
+        let ranges = ["x", "y", "z"].map(|axis| {
+            let start = rng.range(-extent..=extent);
+            let end = (start + rng.range(0..=length)).min(extent);
+            format!("{}={}..{}", axis, start, end)
+        });
+        // note: the first step turns cubes on, since turning them off would do nothing
+        let instr = if i == 0 || rng.chance(70) {
+            "on"
+        } else {
+            "off"
+        };
+        input.push_str(&format!("{} {}\n", instr, ranges.join(",")));
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(0);
+        let mut b = Rng::new(0);
+        assert_eq!(a.next_u64(), b.next_u64());
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+        assert!((0..1000).all(|_| (-3..=3).contains(&a.range(-3..=3))));
+    }
+
+    #[test]
+    fn test_generators_validate() {
+        for generator in GENERATORS.iter() {
+            for seed in 0..10 {
+                let size = generator.default_size.min(50);
+                let input = (generator.generate)(&mut Rng::new(seed), size);
+                assert_eq!(input.lines().count(), size);
+                assert_eq!(validate::validate(generator.day, &input), Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+/*
+** src/summary.rs
+*/
+
+use crate::cache::Cache;
+use crate::history::{self, RecordedProvenance};
+use crate::info;
+use crate::notes;
+
+use aoc2021::inputs;
+use aoc2021::output::markdown_cell as cell;
+use aoc2021::puzzles;
+
+// prints a markdown table of each day's answers, runtime and notes, from the cached answers and
+// the latest recorded run rather than by solving anything
+pub fn summary() {
+    let cache = Cache::new();
+    let durations = history::latest_durations().unwrap_or_default();
+    let provenance = history::latest_provenance().unwrap_or_default();
+
+    println!("| day | title | part 1 | part 2 | runtime | notes |");
+    println!("| --: | ----- | -----: | -----: | ------: | ----- |");
+    for day in puzzles::days() {
+        let input = inputs::load(day).ok();
+        let answers = (1..=2)
+            .map(|part| {
+                input
+                    .and_then(|input| cache.get(day, part, input))
+                    .unwrap_or_else(|| "-".into())
+            })
+            .collect::<Vec<_>>();
+        let runtime = match (durations.get(&(day, 1)), durations.get(&(day, 2))) {
+            (None, None) => "-".into(),
+            (a, b) => format!("{:.3}ms", a.unwrap_or(&0.0) + b.unwrap_or(&0.0)),
+        };
+        // note: both parts share the one parse, so the first part's provenance speaks for both
+        let runtime = match provenance.get(&(day, 1)) {
+            Some(provenance) => with_provenance(runtime, provenance),
+            None => runtime,
+        };
+        let notes = notes::get(day);
+        let note = notes.as_deref().and_then(notes::first_line).unwrap_or("");
+        let title = info::cached_title(&cache, day).unwrap_or_default();
+        println!(
+            "| {} | {} | {} | {} | {} | {} |",
+            day,
+            cell(&title),
+            cell(&answers[0]),
+            cell(&answers[1]),
+            runtime,
+            cell(note)
+        );
+    }
+}
+
+// the runtime followed by the implementation and parse time which produced it, where recorded
+fn with_provenance(runtime: String, provenance: &RecordedProvenance) -> String {
+    let mut details = vec![];
+    if let Some(variant) = &provenance.variant {
+        details.push(variant.clone());
+    }
+    if let Some(parse_ms) = provenance.parse_ms {
+        details.push(format!("parse {:.3}ms", parse_ms));
+    }
+    match details.is_empty() {
+        true => runtime,
+        false => format!("{} ({})", runtime, details.join(", ")),
+    }
+}
@@ -0,0 +1,286 @@
+/*
+** src/types/binary_tree.rs
+*/
+
+// a node either holds a value or is a pair of exactly two children, by ID
+#[derive(Clone, Debug, PartialEq)]
+pub enum BinaryNode<T> {
+    Leaf(T),
+    Pair(u64, u64),
+}
+
+#[derive(Clone, Debug)]
+struct Slot<T> {
+    parent: Option<u64>,
+    node: BinaryNode<T>,
+}
+
+// a tree in which every node is a leaf or has exactly two children, bump-allocated from a single
+// arena, where node IDs index the arena
+// note: trees are built from the leaves up, so each node inserted without a parent becomes the
+// root until it is paired under another
+#[derive(Clone, Debug)]
+pub struct BinaryTree<T> {
+    pub root: Option<u64>,
+    slots: Vec<Option<Slot<T>>>,
+}
+
+impl<T> BinaryTree<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(64)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            root: None,
+            slots: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn slot(&self, id: u64) -> Option<&Slot<T>> {
+        self.slots.get(id as usize).and_then(Option::as_ref)
+    }
+
+    fn slot_mut(&mut self, id: u64) -> Option<&mut Slot<T>> {
+        self.slots.get_mut(id as usize).and_then(Option::as_mut)
+    }
+
+    fn push(&mut self, node: BinaryNode<T>) -> u64 {
+        let id = self.slots.len() as u64;
+        self.slots.push(Some(Slot { parent: None, node }));
+        self.root = Some(id);
+        id
+    }
+
+    pub fn node(&self, id: u64) -> Option<&BinaryNode<T>> {
+        self.slot(id).map(|slot| &slot.node)
+    }
+
+    pub fn parent(&self, id: u64) -> Option<u64> {
+        self.slot(id).and_then(|slot| slot.parent)
+    }
+
+    // the value of a leaf, or None for a pair
+    pub fn leaf(&self, id: u64) -> Option<&T> {
+        match self.node(id) {
+            Some(BinaryNode::Leaf(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn leaf_mut(&mut self, id: u64) -> Option<&mut T> {
+        match self.slot_mut(id).map(|slot| &mut slot.node) {
+            Some(BinaryNode::Leaf(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    // the children of a pair, or None for a leaf
+    pub fn children(&self, id: u64) -> Option<(u64, u64)> {
+        match self.node(id) {
+            Some(&BinaryNode::Pair(left, right)) => Some((left, right)),
+            _ => None,
+        }
+    }
+
+    pub fn left(&self, id: u64) -> Option<u64> {
+        self.children(id).map(|(left, _)| left)
+    }
+
+    pub fn right(&self, id: u64) -> Option<u64> {
+        self.children(id).map(|(_, right)| right)
+    }
+
+    pub fn insert_leaf(&mut self, value: T) -> u64 {
+        self.push(BinaryNode::Leaf(value))
+    }
+
+    // pairs two nodes which have no parent yet under a new node
+    pub fn insert_pair(&mut self, left: u64, right: u64) -> u64 {
+        let id = self.push(BinaryNode::Pair(left, right));
+        for child in [left, right] {
+            let slot = self.slot_mut(child).unwrap();
+            assert!(slot.parent.is_none(), "node {} already has a parent", child);
+            slot.parent = Some(id);
+        }
+        id
+    }
+
+    // turns a leaf into a pair of two new leaves
+    pub fn split(&mut self, id: u64, left: T, right: T) {
+        assert!(self.leaf(id).is_some(), "only a leaf can be split");
+        let left = self.insert_leaf(left);
+        let right = self.insert_leaf(right);
+        self.slot_mut(id).unwrap().node = BinaryNode::Pair(left, right);
+        for child in [left, right] {
+            self.slot_mut(child).unwrap().parent = Some(id);
+        }
+        // note: the new leaves briefly became the root as they were inserted
+        self.root = Some(self.root_of(id));
+    }
+
+    // turns a pair into a leaf, removing the nodes below it
+    // note: removed nodes leave a hole in the arena until the tree is next combined
+    pub fn collapse(&mut self, id: u64, value: T) {
+        if let Some((left, right)) = self.children(id) {
+            self.remove_subtree(left);
+            self.remove_subtree(right);
+        }
+        self.slot_mut(id).unwrap().node = BinaryNode::Leaf(value);
+    }
+
+    fn remove_subtree(&mut self, id: u64) {
+        if let Some((left, right)) = self.children(id) {
+            self.remove_subtree(left);
+            self.remove_subtree(right);
+        }
+        self.slots[id as usize] = None;
+    }
+
+    fn root_of(&self, mut id: u64) -> u64 {
+        while let Some(parent) = self.parent(id) {
+            id = parent;
+        }
+        id
+    }
+
+    pub fn leftmost_leaf(&self, mut id: u64) -> u64 {
+        while let Some(left) = self.left(id) {
+            id = left;
+        }
+        id
+    }
+
+    pub fn rightmost_leaf(&self, mut id: u64) -> u64 {
+        while let Some(right) = self.right(id) {
+            id = right;
+        }
+        id
+    }
+
+    // the leaf immediately before the given node in order, if any
+    pub fn left_neighbor_leaf(&self, mut id: u64) -> Option<u64> {
+        // climb until the node is a right child, then descend the rightmost path of its sibling
+        while let Some(parent) = self.parent(id) {
+            let (left, right) = self.children(parent).unwrap();
+            if right == id {
+                return Some(self.rightmost_leaf(left));
+            }
+            id = parent;
+        }
+        None
+    }
+
+    // the leaf immediately after the given node in order, if any
+    pub fn right_neighbor_leaf(&self, mut id: u64) -> Option<u64> {
+        while let Some(parent) = self.parent(id) {
+            let (left, right) = self.children(parent).unwrap();
+            if left == id {
+                return Some(self.leftmost_leaf(right));
+            }
+            id = parent;
+        }
+        None
+    }
+
+    // the IDs of the leaves from left to right
+    pub fn leaves(&self) -> Leaves<'_, T> {
+        Leaves {
+            tree: self,
+            next: self.root.map(|root| self.leftmost_leaf(root)),
+        }
+    }
+
+    fn copy_subtree(&mut self, tree: &Self, id: u64) -> u64
+    where
+        T: Clone,
+    {
+        match tree.node(id).unwrap() {
+            BinaryNode::Leaf(value) => self.insert_leaf(value.clone()),
+            &BinaryNode::Pair(left, right) => {
+                let left = self.copy_subtree(tree, left);
+                let right = self.copy_subtree(tree, right);
+                self.insert_pair(left, right)
+            }
+        }
+    }
+
+    // pairs two trees under a new root, into a fresh arena without the holes of either
+    pub fn combine(left: &Self, right: &Self) -> Self
+    where
+        T: Clone,
+    {
+        let mut tree = Self::with_capacity(left.slots.len() + right.slots.len() + 1);
+        if let (Some(left_root), Some(right_root)) = (left.root, right.root) {
+            let left_id = tree.copy_subtree(left, left_root);
+            let right_id = tree.copy_subtree(right, right_root);
+            tree.insert_pair(left_id, right_id);
+        }
+        tree
+    }
+}
+
+impl<T> Default for BinaryTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// an in-order walk over the leaves of a tree
+// note: steps from each leaf to its right neighbor through the parent links, so needs no stack
+pub struct Leaves<'a, T> {
+    tree: &'a BinaryTree<T>,
+    next: Option<u64>,
+}
+
+impl<T> Iterator for Leaves<'_, T> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.next?;
+        self.next = self.tree.right_neighbor_leaf(id);
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_tree() {
+        // [[1,2],3]
+        let mut tree = BinaryTree::new();
+        let (one, two) = (tree.insert_leaf(1), tree.insert_leaf(2));
+        let pair = tree.insert_pair(one, two);
+        let three = tree.insert_leaf(3);
+        let root = tree.insert_pair(pair, three);
+        assert_eq!(tree.root, Some(root));
+        assert_eq!(tree.children(root), Some((pair, three)));
+        assert_eq!(
+            (tree.leftmost_leaf(root), tree.rightmost_leaf(root)),
+            (one, three)
+        );
+        assert_eq!(tree.left_neighbor_leaf(three), Some(two));
+        assert_eq!(tree.right_neighbor_leaf(two), Some(three));
+        assert_eq!(tree.left_neighbor_leaf(one), None);
+
+        // [[1,2],[1,2]], then [[1,2],[0,2]]
+        tree.split(three, 1, 2);
+        assert_eq!(tree.root, Some(root));
+        let leaves = |tree: &BinaryTree<u8>| {
+            tree.leaves()
+                .map(|id| *tree.leaf(id).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(leaves(&tree), vec![1, 2, 1, 2]);
+        let split_left = tree.left(three).unwrap();
+        *tree.leaf_mut(split_left).unwrap() = 0;
+        tree.collapse(pair, 5);
+        assert_eq!(leaves(&tree), vec![5, 0, 2]);
+
+        let combined = BinaryTree::combine(&tree, &tree);
+        assert_eq!(leaves(&combined), vec![5, 0, 2, 5, 0, 2]);
+        assert_eq!(combined.slots.len(), 11);
+    }
+}
@@ -0,0 +1,748 @@
+/*
+** src/types/bits.rs
+*/
+
+// the Buoyancy Interchange Transmission System from day 16: decodes hexadecimal transmissions
+// into trees of packets, which evaluate as expressions (see types::expression)
+
+use crate::types::expression::{Expression, ExpressionError, Op};
+use crate::utils;
+
+use num::{Integer, NumCast};
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PacketType {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    Literal,
+    Greater,
+    Less,
+    Equal,
+}
+
+impl From<u8> for PacketType {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => Self::Sum,
+            1 => Self::Product,
+            2 => Self::Minimum,
+            3 => Self::Maximum,
+            4 => Self::Literal,
+            5 => Self::Greater,
+            6 => Self::Less,
+            7 => Self::Equal,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl PacketType {
+    const COUNT: usize = 8;
+
+    // the type ID which the packet header encodes
+    fn id(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PacketData {
+    Literal(u64),
+    Subpackets(Vec<Packet>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Packet {
+    // position of the first bit of the packet in the transmission
+    pub offset: usize,
+    pub version: u8,
+    pub type_id: PacketType,
+    pub length_type_id: u8,
+    pub data: PacketData,
+}
+
+impl Packet {
+    pub fn literal(&self) -> u64 {
+        match self.data {
+            PacketData::Literal(n) => n,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn subpackets(&self) -> &Vec<Packet> {
+        match &self.data {
+            PacketData::Subpackets(subpackets) => subpackets,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn version_sum(&self) -> u64 {
+        let v = self.version as u64;
+        match self.type_id {
+            PacketType::Literal => v,
+            _ => {
+                v + self
+                    .subpackets()
+                    .iter()
+                    .map(|p| p.version_sum())
+                    .sum::<u64>()
+            }
+        }
+    }
+
+    pub fn stats(&self) -> PacketStats {
+        let mut stats = PacketStats::default();
+        self.visit(0, &mut stats);
+        stats
+    }
+
+    fn visit(&self, depth: usize, stats: &mut PacketStats) {
+        stats.counts[self.type_id.id()] += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+        match &self.data {
+            PacketData::Literal(n) => {
+                *stats
+                    .literal_bits
+                    .entry(u64::BITS - n.leading_zeros())
+                    .or_insert(0) += 1
+            }
+            PacketData::Subpackets(subpackets) => {
+                for packet in subpackets.iter() {
+                    packet.visit(depth + 1, stats);
+                }
+            }
+        }
+    }
+
+    // the packet with the given subpacket indices leading to it from this one
+    fn descendant(&self, path: &[usize]) -> &Packet {
+        path.iter().fold(self, |packet, &i| &packet.subpackets()[i])
+    }
+
+    // note: evaluates in 128 bits and reports an error rather than wrapping on overflow
+    pub fn evaluate(&self) -> PacketResult<u128> {
+        Expression::from(self).evaluate().map_err(|err| {
+            let packet = self.descendant(err.path());
+            match err {
+                ExpressionError::InvalidOperands { count, .. } => PacketError::InvalidOperands {
+                    offset: packet.offset,
+                    type_id: packet.type_id,
+                    count,
+                },
+                ExpressionError::Overflow { .. } => PacketError::Overflow {
+                    offset: packet.offset,
+                    type_id: packet.type_id,
+                },
+            }
+        })
+    }
+
+    // renders the packet and each of its subpackets on its own line, indented by depth
+    pub fn tree(&self) -> String {
+        let mut lines = vec![];
+        self.tree_lines(0, &mut lines);
+        lines.join("\n")
+    }
+
+    fn tree_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        let header = format!(
+            "{}v{} {:?} at bit {}",
+            "  ".repeat(depth),
+            self.version,
+            self.type_id,
+            self.offset
+        );
+        match &self.data {
+            PacketData::Literal(n) => lines.push(format!("{}: {}", header, n)),
+            PacketData::Subpackets(subpackets) => {
+                lines.push(format!(
+                    "{} (length type {}, {} subpackets)",
+                    header,
+                    self.length_type_id,
+                    subpackets.len()
+                ));
+                for packet in subpackets.iter() {
+                    packet.tree_lines(depth + 1, lines);
+                }
+            }
+        }
+    }
+}
+
+impl From<&Packet> for Expression {
+    fn from(packet: &Packet) -> Self {
+        let op = match packet.type_id {
+            PacketType::Literal => return Self::Value(packet.literal() as u128),
+            PacketType::Sum => Op::Sum,
+            PacketType::Product => Op::Product,
+            PacketType::Minimum => Op::Minimum,
+            PacketType::Maximum => Op::Maximum,
+            PacketType::Greater => Op::Greater,
+            PacketType::Less => Op::Less,
+            PacketType::Equal => Op::Equal,
+        };
+        let operands = packet.subpackets().iter().map(Self::from).collect();
+        Self::Operation(op, operands)
+    }
+}
+
+// renders the packet as the expression which it represents
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Expression::from(self))
+    }
+}
+
+// the make-up of a tree of packets
+#[derive(Debug, Default, PartialEq)]
+pub struct PacketStats {
+    // the number of packets of each type, indexed by type ID
+    pub counts: [usize; PacketType::COUNT],
+    // the deepest that a packet is nested within others
+    pub max_depth: usize,
+    // the number of literals needing each number of bits
+    pub literal_bits: BTreeMap<u32, usize>,
+}
+
+impl PacketStats {
+    pub fn count(&self, type_id: PacketType) -> usize {
+        self.counts[type_id.id()]
+    }
+}
+
+impl fmt::Display for PacketStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = (0..PacketType::COUNT)
+            .map(|id| PacketType::from(id as u8))
+            .filter(|&type_id| self.count(type_id) > 0)
+            .map(|type_id| format!("{:?} {}", type_id, self.count(type_id)))
+            .collect::<Vec<_>>();
+        let literal_bits = self
+            .literal_bits
+            .iter()
+            .map(|(bits, count)| format!("{}b {}", bits, count))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{} packets ({}), max depth {}, literals by width ({})",
+            self.counts.iter().sum::<usize>(),
+            counts.join(", "),
+            self.max_depth,
+            literal_bits.join(", ")
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PacketError {
+    // a character of the transmission is not a hexadecimal digit
+    InvalidHex {
+        offset: usize,
+        c: char,
+    },
+    // a field runs past the end of the transmission
+    Truncated {
+        offset: usize,
+        needed: usize,
+        remaining: usize,
+    },
+    // a literal value does not fit into 64 bits
+    LiteralOverflow {
+        offset: usize,
+    },
+    // the subpackets of an operator do not fill its declared length
+    LengthMismatch {
+        offset: usize,
+        expected: usize,
+        actual: usize,
+    },
+    // an operator has the wrong number of subpackets for its type
+    InvalidOperands {
+        offset: usize,
+        type_id: PacketType,
+        count: usize,
+    },
+    // the transmission contains nothing but padding
+    Empty,
+    // evaluating an operator exceeds 128 bits
+    Overflow {
+        offset: usize,
+        type_id: PacketType,
+    },
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHex { offset, c } => {
+                write!(f, "invalid hex digit {:?} at character {}", c, offset)
+            }
+            Self::Truncated {
+                offset,
+                needed,
+                remaining,
+            } => write!(
+                f,
+                "truncated transmission at bit {}: needed {} bits but only {} remain",
+                offset, needed, remaining
+            ),
+            Self::LiteralOverflow { offset } => {
+                write!(f, "literal at bit {} overflows 64 bits", offset)
+            }
+            Self::LengthMismatch {
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "subpackets starting at bit {} span {} bits but {} were declared",
+                offset, actual, expected
+            ),
+            Self::InvalidOperands {
+                offset,
+                type_id,
+                count,
+            } => write!(
+                f,
+                "{:?} operator at bit {} has {} subpackets",
+                type_id, offset, count
+            ),
+            Self::Empty => write!(f, "transmission contains no packets"),
+            Self::Overflow { offset, type_id } => {
+                write!(
+                    f,
+                    "{:?} operator at bit {} overflows 128 bits",
+                    type_id, offset
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for PacketError {}
+
+pub type PacketResult<T> = std::result::Result<T, PacketError>;
+
+// reads big-endian bit fields from a transmission while tracking the remaining bit budget
+struct BitReader<'a> {
+    data: &'a [u8],
+    // position of the next bit to be read
+    offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn remaining(&self) -> usize {
+        (self.data.len() * 8) - self.offset
+    }
+
+    fn bit(&self, offset: usize) -> u8 {
+        let shift = 7 - (offset % 8);
+        (self.data[offset / 8] >> shift) & 0x1
+    }
+
+    fn read<T>(&mut self, n_bits: usize) -> PacketResult<T>
+    where
+        T: Integer + NumCast,
+    {
+        if n_bits > self.remaining() {
+            return Err(PacketError::Truncated {
+                offset: self.offset,
+                needed: n_bits,
+                remaining: self.remaining(),
+            });
+        }
+
+        // combine into a single integer
+        let mut n = 0u64;
+        for offset in self.offset..(self.offset + n_bits) {
+            n = (n << 1) | self.bit(offset) as u64;
+        }
+        self.offset += n_bits;
+        // note: callers only read fields which fit in the requested type
+        Ok(utils::try_cast(n).unwrap())
+    }
+
+    // skips ahead to the next byte boundary
+    fn align(&mut self) {
+        self.offset = self.offset.next_multiple_of(8).min(self.data.len() * 8);
+    }
+
+    // checks if everything left in the transmission is zero padding
+    fn is_padding(&self) -> bool {
+        (self.offset..(self.data.len() * 8)).all(|offset| self.bit(offset) == 0)
+    }
+}
+
+pub fn parse_transmission(transmission: &str) -> PacketResult<Vec<u8>> {
+    let nibbles = transmission
+        .trim()
+        .chars()
+        .enumerate()
+        .map(|(offset, c)| {
+            c.to_digit(16)
+                .map(|b| b as u8)
+                .ok_or(PacketError::InvalidHex { offset, c })
+        })
+        .collect::<PacketResult<Vec<_>>>()?;
+
+    // note: an odd number of hex characters leaves the final nibble zero-padded
+    let data = nibbles
+        .chunks(2)
+        .map(|chunk| match chunk {
+            [b0, b1] => (b0 << 4) | b1,
+            [b] => b << 4,
+            _ => unreachable!(),
+        })
+        .collect();
+    Ok(data)
+}
+
+fn parse_packet_header(reader: &mut BitReader) -> PacketResult<(u8, PacketType, u8)> {
+    let version = reader.read::<u8>(3)?;
+    let type_id = reader.read::<u8>(3)?;
+    // note: length type ID is only valid for operators
+    let length_type_id = match type_id {
+        4 => 0,
+        _ => reader.read::<u8>(1)?,
+    };
+
+    Ok((version, type_id.into(), length_type_id))
+}
+
+fn parse_packet_literal(reader: &mut BitReader) -> PacketResult<u64> {
+    let offset = reader.offset();
+    let flag = 0x10;
+    let mask = 0xF;
+
+    let mut n = 0u64;
+    loop {
+        let chunk = reader.read::<u8>(5)?;
+        // shifting in another 4 bits must not push set bits off the top
+        if n.leading_zeros() < 4 {
+            return Err(PacketError::LiteralOverflow { offset });
+        }
+        n = (n << 4) | (chunk & mask) as u64;
+        if chunk & flag == 0 {
+            break;
+        }
+    }
+
+    Ok(n)
+}
+
+fn parse_packet_operator_length(reader: &mut BitReader, length_type_id: u8) -> PacketResult<usize> {
+    match length_type_id {
+        // operator length is 15 bits
+        0 => reader.read(15),
+        // operator length is 11 bits
+        1 => reader.read(11),
+        _ => unreachable!(),
+    }
+}
+
+fn parse_subpackets(reader: &mut BitReader, length_type_id: u8) -> PacketResult<Vec<Packet>> {
+    let mut subpackets = vec![];
+    let op_length = parse_packet_operator_length(reader, length_type_id)?;
+    match length_type_id {
+        0 => {
+            // length is the total length in bits of the subpackets
+            let offset = reader.offset();
+            if op_length > reader.remaining() {
+                return Err(PacketError::Truncated {
+                    offset,
+                    needed: op_length,
+                    remaining: reader.remaining(),
+                });
+            }
+            let end = offset + op_length;
+            while reader.offset() < end {
+                let subpacket = parse_packet(reader)?;
+                subpackets.push(subpacket);
+            }
+            if reader.offset() != end {
+                return Err(PacketError::LengthMismatch {
+                    offset,
+                    expected: op_length,
+                    actual: reader.offset() - offset,
+                });
+            }
+        }
+        1 => {
+            // length is the number of subpackets
+            for _ in 0..op_length {
+                let subpacket = parse_packet(reader)?;
+                subpackets.push(subpacket);
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(subpackets)
+}
+
+fn parse_packet(reader: &mut BitReader) -> PacketResult<Packet> {
+    let offset = reader.offset();
+    // parse the packet header
+    let (version, type_id, length_type_id) = parse_packet_header(reader)?;
+
+    // parse the remaining portion of the packet based on the type ID
+    let packet_data = match type_id {
+        // literal
+        PacketType::Literal => {
+            let literal = parse_packet_literal(reader)?;
+            PacketData::Literal(literal)
+        }
+        // operator
+        _ => {
+            let subpackets = parse_subpackets(reader, length_type_id)?;
+            // check the operand count up-front so that evaluation cannot fail
+            let valid = match type_id {
+                PacketType::Minimum | PacketType::Maximum => !subpackets.is_empty(),
+                PacketType::Greater | PacketType::Less | PacketType::Equal => subpackets.len() == 2,
+                _ => true,
+            };
+            if !valid {
+                return Err(PacketError::InvalidOperands {
+                    offset,
+                    type_id,
+                    count: subpackets.len(),
+                });
+            }
+            PacketData::Subpackets(subpackets)
+        }
+    };
+
+    Ok(Packet {
+        offset,
+        version,
+        type_id,
+        length_type_id,
+        data: packet_data,
+    })
+}
+
+pub fn parse_packets(transmission: &[u8]) -> PacketResult<Vec<Packet>> {
+    let mut packets = vec![];
+    let mut reader = BitReader::new(transmission);
+
+    // stop once only zero padding is left in the transmission
+    while !reader.is_padding() {
+        let packet = parse_packet(&mut reader)?;
+        packets.push(packet);
+        // account for trailing bits
+        reader.align();
+    }
+
+    if packets.is_empty() {
+        Err(PacketError::Empty)
+    } else {
+        Ok(packets)
+    }
+}
+
+// decodes a hexadecimal transmission into its top-level packets
+pub fn decode(transmission: &str) -> PacketResult<Vec<Packet>> {
+    let data = parse_transmission(transmission)?;
+    parse_packets(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn try_parse_packets(transmission: &str) -> PacketResult<Vec<Packet>> {
+        decode(transmission)
+    }
+
+    fn parse_packets(transmission: &str) -> Vec<Packet> {
+        try_parse_packets(transmission).unwrap()
+    }
+
+    #[test]
+    fn test_parse_packet_literal() {
+        let packets = parse_packets("D2FE28");
+        assert_eq!(packets.len(), 1);
+
+        let packet = &packets[0];
+        assert_eq!(packet.version, 6);
+        assert_eq!(packet.type_id, PacketType::Literal);
+        assert_eq!(packet.data, PacketData::Literal(2021));
+    }
+
+    #[test]
+    fn test_parse_packets_multiple_literals() {
+        let packets = parse_packets("D2FE28D2FE28D2FE28D2FE28");
+        assert_eq!(packets.len(), 4);
+
+        for packet in packets.iter() {
+            assert_eq!(packet.version, 6);
+            assert_eq!(packet.type_id, PacketType::Literal);
+            assert_eq!(packet.data, PacketData::Literal(2021));
+        }
+    }
+
+    #[test]
+    fn test_parse_packet_operator_length_type_0() {
+        let packets = parse_packets("38006F45291200");
+        assert_eq!(packets.len(), 1);
+
+        let packet = &packets[0];
+        assert_eq!(packet.version, 1);
+        assert_eq!(packet.type_id, PacketType::Less);
+        assert_eq!(packet.length_type_id, 0);
+        assert!(matches!(packet.data, PacketData::Subpackets(_)));
+        assert_eq!(packet.subpackets().len(), 2);
+
+        let subpacket = &packet.subpackets()[0];
+        assert_eq!(subpacket.data, PacketData::Literal(10));
+
+        let subpacket = &packet.subpackets()[1];
+        assert_eq!(subpacket.data, PacketData::Literal(20));
+    }
+
+    #[test]
+    fn test_parse_packet_operator_length_type_1() {
+        let packets = parse_packets("EE00D40C823060");
+        assert_eq!(packets.len(), 1);
+
+        let packet = &packets[0];
+        assert_eq!(packet.version, 7);
+        assert_eq!(packet.type_id, PacketType::Maximum);
+        assert_eq!(packet.length_type_id, 1);
+        assert!(matches!(packet.data, PacketData::Subpackets(_)));
+        assert_eq!(packet.subpackets().len(), 3);
+
+        let subpacket = &packet.subpackets()[0];
+        assert_eq!(subpacket.data, PacketData::Literal(1));
+
+        let subpacket = &packet.subpackets()[1];
+        assert_eq!(subpacket.data, PacketData::Literal(2));
+
+        let subpacket = &packet.subpackets()[2];
+        assert_eq!(subpacket.data, PacketData::Literal(3));
+    }
+
+    #[test]
+    fn test_evaluate_packets() {
+        let packet = &parse_packets("C200B40A82")[0];
+        assert_eq!(packet.evaluate(), Ok(3));
+
+        let packet = &parse_packets("04005AC33890")[0];
+        assert_eq!(packet.evaluate(), Ok(54));
+
+        let packet = &parse_packets("880086C3E88112")[0];
+        assert_eq!(packet.evaluate(), Ok(7));
+
+        let packet = &parse_packets("CE00C43D881120")[0];
+        assert_eq!(packet.evaluate(), Ok(9));
+    }
+
+    #[test]
+    fn test_display_packets() {
+        let packet = &parse_packets("9C0141080250320F1802104A08")[0];
+        assert_eq!(packet.to_string(), "((1 + 3) == (2 * 2))");
+
+        let packet = &parse_packets("880086C3E88112")[0];
+        assert_eq!(packet.to_string(), "min(7, 8, 9)");
+    }
+
+    #[test]
+    fn test_packet_stats() {
+        let stats = parse_packets("9C0141080250320F1802104A08")[0].stats();
+        assert_eq!(stats.count(PacketType::Literal), 4);
+        assert_eq!(stats.count(PacketType::Sum), 1);
+        assert_eq!(stats.count(PacketType::Minimum), 0);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(
+            stats.to_string(),
+            "7 packets (Sum 1, Product 1, Literal 4, Equal 1), max depth 2, literals by width (1b 1, 2b 3)"
+        );
+    }
+
+    #[test]
+    fn test_tree_packets() {
+        let packet = &parse_packets("38006F45291200")[0];
+        assert_eq!(
+            packet.tree(),
+            "v1 Less at bit 0 (length type 0, 2 subpackets)\n  v6 Literal at bit 22: 10\n  v2 Literal at bit 33: 20"
+        );
+    }
+
+    #[test]
+    fn test_parse_packets_trailing_padding() {
+        let packets = parse_packets("D2FE280000");
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].data, PacketData::Literal(2021));
+    }
+
+    #[test]
+    fn test_parse_packets_malformed() {
+        assert_eq!(
+            try_parse_packets("D2FG28"),
+            Err(PacketError::InvalidHex { offset: 3, c: 'G' })
+        );
+        assert_eq!(
+            try_parse_packets("D2FE"),
+            Err(PacketError::Truncated {
+                offset: 16,
+                needed: 5,
+                remaining: 0
+            })
+        );
+        assert_eq!(
+            try_parse_packets("38006745291200"),
+            Err(PacketError::LengthMismatch {
+                offset: 22,
+                expected: 25,
+                actual: 27
+            })
+        );
+        assert_eq!(try_parse_packets("0000"), Err(PacketError::Empty));
+    }
+
+    #[test]
+    fn test_evaluate_packets_overflow() {
+        let literal = |n| Packet {
+            offset: 0,
+            version: 0,
+            type_id: PacketType::Literal,
+            length_type_id: 0,
+            data: PacketData::Literal(n),
+        };
+        let product = |subpackets| Packet {
+            offset: 0,
+            version: 0,
+            type_id: PacketType::Product,
+            length_type_id: 1,
+            data: PacketData::Subpackets(subpackets),
+        };
+
+        // the product of two maximal literals still fits into 128 bits
+        let packet = product(vec![literal(u64::MAX), literal(u64::MAX)]);
+        let max = u64::MAX as u128;
+        assert_eq!(packet.evaluate(), Ok(max * max));
+
+        let packet = product(vec![literal(u64::MAX), literal(u64::MAX), literal(2)]);
+        assert_eq!(
+            packet.evaluate(),
+            Err(PacketError::Overflow {
+                offset: 0,
+                type_id: PacketType::Product
+            })
+        );
+    }
+}
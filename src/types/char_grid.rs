@@ -0,0 +1,129 @@
+/*
+** src/types/char_grid.rs
+*/
+
+use super::grid::{self, GridError};
+use super::Coord;
+
+use std::fmt;
+
+// a rectangular map of ASCII characters, as found in maze-style puzzle inputs
+#[derive(Clone, Eq, PartialEq)]
+pub struct CharGrid {
+    width: usize,
+    height: usize,
+    // row-major
+    // note: cells are restricted to ASCII such that each is a single byte
+    cells: Vec<u8>,
+}
+
+impl CharGrid {
+    pub fn parse(input: &str) -> Result<Self, GridError> {
+        let (width, height, cells) =
+            grid::parse_chars(input, |c| if c.is_ascii() { Some(c as u8) } else { None })?;
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.row < self.height && coord.col < self.width
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<char> {
+        if self.contains(coord) {
+            Some(self.cells[coord.to_index(self.width)] as char)
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, coord: Coord, c: char) {
+        assert!(c.is_ascii(), "non-ASCII cell {:?}", c);
+        self.cells[coord.to_index(self.width)] = c as u8;
+    }
+
+    // the first position of the character, in row-major order
+    pub fn find(&self, c: char) -> Option<Coord> {
+        self.positions(c).next()
+    }
+
+    pub fn positions(&self, c: char) -> impl Iterator<Item = Coord> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(move |(_, &cell)| cell as char == c)
+            .map(|(index, _)| Coord::from_index(index, self.width))
+    }
+
+    // the orthogonal neighbors which are within the grid
+    pub fn neighbors(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        [(0, -1), (0, 1), (-1, 0), (1, 0)]
+            .into_iter()
+            .filter_map(move |(drow, dcol)| coord.offset(drow, dcol))
+            .filter(|&neighbor| self.contains(neighbor))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &str> {
+        // note: cells are ASCII, so every row is valid UTF-8
+        self.cells
+            .chunks(self.width)
+            .map(|row| std::str::from_utf8(row).unwrap())
+    }
+}
+
+impl fmt::Display for CharGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CharGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "#####\n#S..#\n#.#E#\n#####\n";
+
+    #[test]
+    fn test_find() {
+        let grid = CharGrid::parse(TEST_INPUT).unwrap();
+        assert_eq!((grid.width(), grid.height()), (5, 4));
+        assert_eq!(grid.find('S'), Some(Coord::new(1, 1)));
+        assert_eq!(grid.find('X'), None);
+        assert_eq!(grid.positions('.').collect::<Vec<_>>().len(), 3);
+        assert_eq!(grid.positions('#').next(), Some(Coord::new(0, 0)));
+        assert_eq!(grid.neighbors(Coord::new(0, 0)).count(), 2);
+        assert_eq!(grid.get(Coord::new(2, 3)), Some('E'));
+        assert_eq!(grid.get(Coord::new(4, 0)), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = CharGrid::parse(TEST_INPUT).unwrap();
+        grid.set(Coord::new(1, 2), 'o');
+        assert_eq!(grid.to_string(), "#####\n#So.#\n#.#E#\n#####");
+        assert!(CharGrid::parse("ab\nc\u{e9}\n").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+/*
+** src/types/context.rs
+*/
+
+use super::Result;
+
+use std::error;
+use std::fmt;
+
+// an error along with a description of what was being done when it occurred
+pub struct ContextError {
+    context: String,
+    source: Box<dyn error::Error>,
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.source)
+    }
+}
+
+// note: matches Display, such that unwrapping a result reports the whole chain of context
+impl fmt::Debug for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+// attaches context to errors as they bubble up, outermost first, e.g.
+// "parsing day 22 step 17: z range: invalid digit found in string"
+pub trait Context<T> {
+    fn context<C>(self, context: C) -> Result<T>
+    where
+        C: fmt::Display;
+
+    // as context, but only builds the context on failure
+    fn with_context<C, F>(self, f: F) -> Result<T>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C;
+}
+
+impl<T, E> Context<T> for std::result::Result<T, E>
+where
+    E: Into<Box<dyn error::Error>>,
+{
+    fn context<C>(self, context: C) -> Result<T>
+    where
+        C: fmt::Display,
+    {
+        self.with_context(|| context)
+    }
+
+    fn with_context<C, F>(self, f: F) -> Result<T>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|err| {
+            let err = ContextError {
+                context: f().to_string(),
+                source: err.into(),
+            };
+            err.into()
+        })
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context<C>(self, context: C) -> Result<T>
+    where
+        C: fmt::Display,
+    {
+        self.ok_or_else(|| format!("missing {}", context).into())
+    }
+
+    fn with_context<C, F>(self, f: F) -> Result<T>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.ok_or_else(|| format!("missing {}", f()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<i64> {
+        let n = s.parse::<i64>().context("number")?;
+        Ok(n)
+    }
+
+    #[test]
+    fn test_context() {
+        assert_eq!(parse("12").unwrap(), 12);
+        let err = parse("1x")
+            .with_context(|| format!("parsing line {}", 3))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parsing line 3: number: invalid digit found in string"
+        );
+        assert!(err.source().is_some());
+        assert_eq!(
+            None::<i64>.context("y range").unwrap_err().to_string(),
+            "missing y range"
+        );
+    }
+}
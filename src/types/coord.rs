@@ -0,0 +1,123 @@
+/*
+** src/types/coord.rs
+*/
+
+use std::fmt;
+use std::ops::{Add, Sub};
+
+// a position in a row-major grid
+// note: the derived ordering compares rows first, which matches the order of a grid's linear
+// indices
+#[derive(Clone, Copy, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    pub const fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    // the coordinate of the given linear index in a grid with the given number of columns
+    pub const fn from_index(index: usize, width: usize) -> Self {
+        Self::new(index / width, index % width)
+    }
+
+    // the linear index of the coordinate in a grid with the given number of columns
+    pub const fn to_index(self, width: usize) -> usize {
+        (self.row * width) + self.col
+    }
+
+    // every coordinate of a grid with the given dimensions, in row-major order
+    pub fn iter_rect(width: usize, height: usize) -> impl ExactSizeIterator<Item = Self> {
+        (0..width * height).map(move |index| Self::from_index(index, width))
+    }
+
+    // moves by the given signed offsets, if the result does not leave the first quadrant
+    pub fn offset(self, drow: isize, dcol: isize) -> Option<Self> {
+        let row = self.row.checked_add_signed(drow)?;
+        let col = self.col.checked_add_signed(dcol)?;
+        Some(Self::new(row, col))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let row = self.row.checked_sub(other.row)?;
+        let col = self.col.checked_sub(other.col)?;
+        Some(Self::new(row, col))
+    }
+
+    pub fn manhattan_distance(self, other: Self) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+impl Add for Coord {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl Sub for Coord {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl From<(usize, usize)> for Coord {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self::new(row, col)
+    }
+}
+
+impl From<Coord> for (usize, usize) {
+    fn from(coord: Coord) -> Self {
+        (coord.row, coord.col)
+    }
+}
+
+impl fmt::Debug for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("({},{})", self.row, self.col))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        let coord = Coord::new(2, 3);
+        assert_eq!(coord.to_index(5), 13);
+        assert_eq!(Coord::from_index(13, 5), coord);
+        assert_eq!(Coord::from_index(0, 5), Coord::default());
+        assert!(Coord::new(0, 9) < Coord::new(1, 0));
+    }
+
+    #[test]
+    fn test_iter_rect() {
+        let coords = Coord::iter_rect(3, 2).collect::<Vec<_>>();
+        assert_eq!(coords.len(), 6);
+        assert_eq!(coords[1], Coord::new(0, 1));
+        assert_eq!(coords[3], Coord::new(1, 0));
+        assert!(coords.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(Coord::iter_rect(0, 4).count(), 0);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Coord::new(4, 1);
+        let b = Coord::new(1, 2);
+        assert_eq!(a + b, Coord::new(5, 3));
+        assert_eq!(a.checked_sub(b), None);
+        assert_eq!((a + b) - b, a);
+        assert_eq!(a.offset(-1, 1), Some(Coord::new(3, 2)));
+        assert_eq!(a.offset(0, -2), None);
+        assert_eq!(a.manhattan_distance(b), 4);
+    }
+}
@@ -0,0 +1,281 @@
+/*
+** src/types/expression.rs
+*/
+
+// trees of operators over integer values, which evaluate in 128 bits
+
+use std::error;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    Greater,
+    Less,
+    Equal,
+}
+
+impl Op {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Sum => "+",
+            Self::Product => "*",
+            Self::Minimum => "min",
+            Self::Maximum => "max",
+            Self::Greater => ">",
+            Self::Less => "<",
+            Self::Equal => "==",
+        }
+    }
+
+    // infix operators are written between their operands, the rest as function calls
+    fn is_infix(&self) -> bool {
+        !matches!(self, Self::Minimum | Self::Maximum)
+    }
+
+    fn accepts(&self, count: usize) -> bool {
+        match self {
+            Self::Sum | Self::Product => true,
+            Self::Minimum | Self::Maximum => count > 0,
+            Self::Greater | Self::Less | Self::Equal => count == 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Value(u128),
+    Operation(Op, Vec<Expression>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExpressionError {
+    // an operator has the wrong number of operands for its type
+    InvalidOperands {
+        op: Op,
+        count: usize,
+        // the operand indices leading from the root to the operator
+        path: Vec<usize>,
+    },
+    // evaluating an operator exceeds 128 bits
+    Overflow {
+        op: Op,
+        path: Vec<usize>,
+    },
+}
+
+impl ExpressionError {
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Self::InvalidOperands { path, .. } | Self::Overflow { path, .. } => path,
+        }
+    }
+
+    // records the operand which the error arose within, as errors propagate towards the root
+    fn within(mut self, index: usize) -> Self {
+        match &mut self {
+            Self::InvalidOperands { path, .. } | Self::Overflow { path, .. } => {
+                path.insert(0, index)
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidOperands { op, count, path } => {
+                write!(f, "{:?} operator at {:?} has {} operands", op, path, count)
+            }
+            Self::Overflow { op, path } => {
+                write!(f, "{:?} operator at {:?} overflows 128 bits", op, path)
+            }
+        }
+    }
+}
+
+impl error::Error for ExpressionError {}
+
+pub type ExpressionResult<T> = std::result::Result<T, ExpressionError>;
+
+impl Expression {
+    // note: reports an error rather than wrapping on overflow
+    pub fn evaluate(&self) -> ExpressionResult<u128> {
+        let (op, operands) = match self {
+            Self::Value(n) => return Ok(*n),
+            Self::Operation(op, operands) => (*op, operands),
+        };
+        if !op.accepts(operands.len()) {
+            return Err(ExpressionError::InvalidOperands {
+                op,
+                count: operands.len(),
+                path: vec![],
+            });
+        }
+        let operands = operands
+            .iter()
+            .enumerate()
+            .map(|(i, operand)| operand.evaluate().map_err(|err| err.within(i)))
+            .collect::<ExpressionResult<Vec<_>>>()?;
+        let overflow = || ExpressionError::Overflow { op, path: vec![] };
+        let value = match op {
+            Op::Sum => operands
+                .into_iter()
+                .try_fold(0u128, |acc, n| acc.checked_add(n))
+                .ok_or_else(overflow)?,
+            Op::Product => operands
+                .into_iter()
+                .try_fold(1u128, |acc, n| acc.checked_mul(n))
+                .ok_or_else(overflow)?,
+            Op::Minimum => operands.into_iter().min().unwrap(),
+            Op::Maximum => operands.into_iter().max().unwrap(),
+            Op::Greater => (operands[0] > operands[1]) as u128,
+            Op::Less => (operands[0] < operands[1]) as u128,
+            Op::Equal => (operands[0] == operands[1]) as u128,
+        };
+        Ok(value)
+    }
+
+    fn operands(&self) -> &[Expression] {
+        match self {
+            Self::Value(_) => &[],
+            Self::Operation(_, operands) => operands,
+        }
+    }
+
+    // an equivalent expression which folds comparisons into the 0 or 1 which they evaluate to,
+    // drops the operands which they then make redundant, flattens nested sums, products,
+    // minimums and maximums, and unwraps operators of a single operand
+    pub fn simplify(&self) -> ExpressionResult<Expression> {
+        let (op, operands) = match self {
+            Self::Value(n) => return Ok(Self::Value(*n)),
+            Self::Operation(op, operands) => (*op, operands),
+        };
+        if matches!(op, Op::Greater | Op::Less | Op::Equal) {
+            return self.evaluate().map(Self::Value);
+        }
+        let mut simplified = vec![];
+        for (i, operand) in operands.iter().enumerate() {
+            match operand.simplify().map_err(|err| err.within(i))? {
+                // note: operands of the same associative operator join this one
+                Self::Operation(inner, nested) if inner == op => simplified.extend(nested),
+                operand => simplified.push(operand),
+            }
+        }
+        match op {
+            Op::Sum => simplified.retain(|operand| *operand != Self::Value(0)),
+            Op::Product if simplified.contains(&Self::Value(0)) => return Ok(Self::Value(0)),
+            Op::Product => simplified.retain(|operand| *operand != Self::Value(1)),
+            _ => {}
+        }
+        match (op, simplified.len()) {
+            (Op::Sum, 0) => Ok(Self::Value(0)),
+            (Op::Product, 0) => Ok(Self::Value(1)),
+            (_, 1) => Ok(simplified.pop().unwrap()),
+            _ => Ok(Self::Operation(op, simplified)),
+        }
+    }
+
+    // the number of operators and values in the expression
+    pub fn size(&self) -> usize {
+        1 + self.operands().iter().map(Self::size).sum::<usize>()
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (op, operands) = match self {
+            Self::Value(n) => return write!(f, "{}", n),
+            Self::Operation(op, operands) => (op, operands),
+        };
+        let operands = operands
+            .iter()
+            .map(|operand| operand.to_string())
+            .collect::<Vec<_>>();
+        if op.is_infix() {
+            write!(f, "({})", operands.join(&format!(" {} ", op.symbol())))
+        } else {
+            write!(f, "{}({})", op.symbol(), operands.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_expression() {
+        use Expression::{Operation, Value};
+
+        // (1 + 3) == (2 * 2)
+        let expression = Operation(
+            Op::Equal,
+            vec![
+                Operation(Op::Sum, vec![Value(1), Value(3)]),
+                Operation(Op::Product, vec![Value(2), Value(2)]),
+            ],
+        );
+        assert_eq!(expression.evaluate(), Ok(1));
+        assert_eq!(expression.to_string(), "((1 + 3) == (2 * 2))");
+
+        let expression = Operation(Op::Minimum, vec![Value(7), Value(8), Value(9)]);
+        assert_eq!(expression.evaluate(), Ok(7));
+        assert_eq!(expression.to_string(), "min(7, 8, 9)");
+
+        // errors record the path to the offending operator
+        let expression = Operation(Op::Sum, vec![Value(1), Operation(Op::Less, vec![Value(1)])]);
+        assert_eq!(
+            expression.evaluate(),
+            Err(ExpressionError::InvalidOperands {
+                op: Op::Less,
+                count: 1,
+                path: vec![1],
+            })
+        );
+        let expression = Operation(
+            Op::Maximum,
+            vec![Operation(Op::Product, vec![Value(u128::MAX), Value(2)])],
+        );
+        assert_eq!(
+            expression.evaluate(),
+            Err(ExpressionError::Overflow {
+                op: Op::Product,
+                path: vec![0],
+            })
+        );
+    }
+
+    #[test]
+    fn test_simplify_expression() {
+        use Expression::{Operation, Value};
+
+        // ((1 + 2) + ((1 < 2) * 5) + ((2 < 1) * 6) + min(7))
+        let expression = Operation(
+            Op::Sum,
+            vec![
+                Operation(Op::Sum, vec![Value(1), Value(2)]),
+                Operation(
+                    Op::Product,
+                    vec![Operation(Op::Less, vec![Value(1), Value(2)]), Value(5)],
+                ),
+                Operation(
+                    Op::Product,
+                    vec![Operation(Op::Less, vec![Value(2), Value(1)]), Value(6)],
+                ),
+                Operation(Op::Minimum, vec![Value(7)]),
+            ],
+        );
+        let simplified = expression.simplify().unwrap();
+        assert_eq!(simplified.to_string(), "(1 + 2 + 5 + 7)");
+        assert_eq!(simplified.evaluate(), expression.evaluate());
+        assert_eq!((expression.size(), simplified.size()), (16, 5));
+
+        let expression = Operation(Op::Product, vec![Operation(Op::Sum, vec![])]);
+        assert_eq!(expression.simplify(), Ok(Value(0)));
+    }
+}
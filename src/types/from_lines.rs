@@ -0,0 +1,47 @@
+/*
+** src/types/from_lines.rs
+*/
+
+use super::Result;
+
+use std::io::{self, BufRead};
+
+// a puzzle which parses its input a line at a time, such that the input can come from any source
+// (a file, stdin, a request body) rather than only a string which lives for the whole program
+pub trait FromLines: Sized {
+    fn from_lines<'a, I>(lines: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>;
+
+    // note: the lines are read up-front, since each borrowed line must outlive the iterator
+    fn from_reader<R>(reader: R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let lines = reader.lines().collect::<io::Result<Vec<_>>>()?;
+        Self::from_lines(lines.iter().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Words(Vec<String>);
+
+    impl FromLines for Words {
+        fn from_lines<'a, I>(lines: I) -> Result<Self>
+        where
+            I: Iterator<Item = &'a str>,
+        {
+            Ok(Self(lines.map(String::from).collect()))
+        }
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let words = Words::from_reader("abc\ndef\n".as_bytes()).unwrap();
+        assert_eq!(words.0, vec!["abc", "def"]);
+        assert!(Words::from_reader(&[0xff, b'\n'][..]).is_err());
+    }
+}
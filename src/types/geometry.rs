@@ -2,7 +2,8 @@
 ** src/types/geometry.rs
 */
 
-use super::{FMatrix2x2, FVector2};
+use super::{Context, FMatrix2x2, FVector2, Result};
+use crate::utils;
 // TODO: REPLACE WITH NALGEBRA
 // use nalgebra::{Matrix2, Vector2};
 
@@ -30,6 +31,31 @@ impl Point {
         Self::new(self.x, y - dy)
     }
 
+    // rotations treat the point as the gaussian integer x + yi, such that a quarter-turn
+    // counter-clockwise is multiplication by i and a quarter-turn clockwise by -i
+    pub fn rotate_ccw(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    pub fn rotate_cw(&self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    // rotates about the origin by the given number of quarter-turns, counter-clockwise if positive
+    pub fn rotate(&self, quarter_turns: i64) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => self.clone(),
+            1 => self.rotate_ccw(),
+            2 => Self::new(-self.x, -self.y),
+            _ => self.rotate_cw(),
+        }
+    }
+
+    pub fn rotate_around(&self, origin: &Point, quarter_turns: i64) -> Self {
+        let offset = Self::new(self.x - origin.x, self.y - origin.y).rotate(quarter_turns);
+        Self::new(origin.x + offset.x, origin.y + offset.y)
+    }
+
     // are the 3 points listed in counter-clockwise order?
     pub fn ccw(a: &Point, b: &Point, c: &Point) -> bool {
         // if the slope of the line AB is less than the slope of the line AC
@@ -45,16 +71,16 @@ impl Point {
     }
 }
 
-impl From<&str> for Point {
-    fn from(s: &str) -> Self {
+impl Point {
+    pub fn parse(s: &str) -> Result<Self> {
         // format: x,y
         match split!(s, ',') {
             [x_str, y_str] => {
-                let x = x_str.parse().unwrap();
-                let y = y_str.parse().unwrap();
-                Self { x, y }
+                let x = x_str.parse().context("x")?;
+                let y = y_str.parse().context("y")?;
+                Ok(Self { x, y })
             }
-            _ => unreachable!(),
+            _ => Err(format!("invalid point {:?}", s).into()),
         }
     }
 }
@@ -191,7 +217,9 @@ impl Line {
             let y = sol.data[1];
             // ensure that the intersection is a whole number
             if x.fract() == 0.0 && y.fract() == 0.0 {
-                Some(Point::new(x as i64, y as i64))
+                let x = utils::try_cast(x).ok()?;
+                let y = utils::try_cast(y).ok()?;
+                Some(Point::new(x, y))
             } else {
                 None
             }
@@ -201,16 +229,16 @@ impl Line {
     }
 }
 
-impl From<&str> for Line {
-    fn from(s: &str) -> Self {
+impl Line {
+    pub fn parse(s: &str) -> Result<Self> {
         // format: x0,y0 -> x1,y1
         match split!(s, " -> ") {
             [sp0, sp1] => {
-                let p0 = Point::from(*sp0);
-                let p1 = Point::from(*sp1);
-                Self::new(p0, p1)
+                let p0 = Point::parse(sp0)?;
+                let p1 = Point::parse(sp1)?;
+                Ok(Self::new(p0, p1))
             }
-            _ => panic!("invalid line: {}", s),
+            _ => Err(format!("invalid line {:?}", s).into()),
         }
     }
 }
@@ -220,3 +248,34 @@ impl fmt::Debug for Line {
         f.write_fmt(format_args!("{:?}->{:?}", self.p0, self.p1))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Point::parse("3,-4").unwrap(), Point::new(3, -4));
+        assert_eq!(Line::parse("0,9 -> 5,9").unwrap().p1, Point::new(5, 9));
+        let err = |s: &str| Line::parse(s).err().unwrap().to_string();
+        assert_eq!(err("x,y"), "invalid line \"x,y\"");
+        assert_eq!(err("0,9 -> 5"), "invalid point \"5\"");
+        assert_eq!(err("0,a -> 5,9"), "y: invalid digit found in string");
+    }
+
+    #[test]
+    fn test_rotate() {
+        let p = Point::new(3, 1);
+        assert_eq!(p.rotate_ccw(), Point::new(-1, 3));
+        assert_eq!(p.rotate_cw(), Point::new(1, -3));
+        assert_eq!(p.rotate(2), Point::new(-3, -1));
+        assert_eq!(p.rotate(-1), p.rotate_cw());
+        assert_eq!(p.rotate(5), p.rotate_ccw());
+        assert_eq!(p.rotate(4), p);
+
+        let origin = Point::new(2, 2);
+        assert_eq!(p.rotate_around(&origin, 1), Point::new(3, 3));
+        assert_eq!(p.rotate_around(&origin, -1), Point::new(1, 1));
+        assert_eq!(origin.rotate_around(&origin, 3), origin);
+    }
+}
@@ -0,0 +1,187 @@
+/*
+** src/types/grid.rs
+*/
+
+use std::error;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum GridError {
+    Empty,
+    RaggedRow {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    InvalidCell {
+        row: usize,
+        col: usize,
+        c: char,
+    },
+    WrongSize {
+        width: usize,
+        height: usize,
+        expected_width: usize,
+        expected_height: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty grid"),
+            Self::RaggedRow {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "row {} has width {} but expected {}",
+                row, width, expected
+            ),
+            Self::InvalidCell { row, col, c } => {
+                write!(f, "invalid cell {:?} at row {}, column {}", c, row, col)
+            }
+            Self::WrongSize {
+                width,
+                height,
+                expected_width,
+                expected_height,
+            } => write!(
+                f,
+                "grid is {}x{} but expected {}x{}",
+                width, height, expected_width, expected_height
+            ),
+        }
+    }
+}
+
+impl error::Error for GridError {}
+
+// parses the non-empty lines of the input into a rectangular grid, mapping each character to a
+// cell; returns the inferred width and height along with the cells in row-major order
+pub fn parse_chars<T, F>(input: &str, mut mapper: F) -> Result<(usize, usize, Vec<T>), GridError>
+where
+    F: FnMut(char) -> Option<T>,
+{
+    let mut width = 0;
+    let mut height = 0;
+    let mut cells = vec![];
+    for (row, line) in input.lines().filter(|s| !s.is_empty()).enumerate() {
+        let start = cells.len();
+        for (col, c) in line.chars().enumerate() {
+            cells.push(mapper(c).ok_or(GridError::InvalidCell { row, col, c })?);
+        }
+        let row_width = cells.len() - start;
+        if row == 0 {
+            width = row_width;
+        } else if row_width != width {
+            return Err(GridError::RaggedRow {
+                row,
+                width: row_width,
+                expected: width,
+            });
+        }
+        height += 1;
+    }
+
+    if cells.is_empty() {
+        Err(GridError::Empty)
+    } else {
+        Ok((width, height, cells))
+    }
+}
+
+// parses a grid of single decimal digits
+pub fn parse_digits<T>(input: &str) -> Result<(usize, usize, Vec<T>), GridError>
+where
+    T: From<u8>,
+{
+    parse_chars(input, |c| c.to_digit(10).map(|d| T::from(d as u8)))
+}
+
+// the orthogonal neighbors of every cell of a grid, by row-major index, computed once up-front
+// such that searches need not compute and bounds-check the neighbors of each cell they visit
+// note: indices are stored as u32 to halve the size of the table for large grids
+pub struct NeighborTable {
+    // the neighbors of each cell, of which only the first count are valid
+    cells: Vec<([u32; 4], u8)>,
+}
+
+impl NeighborTable {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut cells = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for col in 0..width {
+                let index = (row * width) + col;
+                let mut neighbors = [0; 4];
+                let mut count = 0;
+                let mut push = |neighbor: usize| {
+                    neighbors[count] = neighbor as u32;
+                    count += 1;
+                };
+                // note: in the same order as Array2D::neighbors, i.e. left, right, up then down
+                if col > 0 {
+                    push(index - 1);
+                }
+                if col < width - 1 {
+                    push(index + 1);
+                }
+                if row > 0 {
+                    push(index - width);
+                }
+                if row < height - 1 {
+                    push(index + width);
+                }
+                cells.push((neighbors, count as u8));
+            }
+        }
+        Self { cells }
+    }
+
+    pub fn neighbors(&self, index: usize) -> &[u32] {
+        let (neighbors, count) = &self.cells[index];
+        &neighbors[..(*count as usize)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_digits() {
+        let (width, height, cells) = parse_digits::<u8>("123\n456\n").unwrap();
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(cells, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_digits::<u8>("\n"), Err(GridError::Empty));
+        assert_eq!(
+            parse_digits::<u8>("123\n45\n"),
+            Err(GridError::RaggedRow {
+                row: 1,
+                width: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            parse_digits::<u8>("123\n4x6\n"),
+            Err(GridError::InvalidCell {
+                row: 1,
+                col: 1,
+                c: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn test_neighbor_table() {
+        let table = NeighborTable::new(3, 2);
+        assert_eq!(table.neighbors(0), &[1, 3]);
+        assert_eq!(table.neighbors(1), &[0, 2, 4]);
+        assert_eq!(table.neighbors(5), &[4, 2]);
+    }
+}
@@ -0,0 +1,105 @@
+/*
+** src/types/inline_vec.rs
+*/
+
+use std::ops::Deref;
+
+// a vector of at most N elements stored inline, for the short lists which would otherwise each
+// need an allocation of their own, such as the neighbors of a cell
+#[derive(Clone, Copy, Debug)]
+pub struct InlineVec<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T, const N: usize> InlineVec<T, N>
+where
+    T: Copy + Default,
+{
+    pub fn new() -> Self {
+        Self {
+            items: [T::default(); N],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        assert!(self.len < N, "inline vector holds at most {} elements", N);
+        self.items[self.len] = item;
+        self.len += 1;
+    }
+}
+
+impl<T, const N: usize> InlineVec<T, N> {
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+}
+
+impl<T, const N: usize> Default for InlineVec<T, N>
+where
+    T: Copy + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for InlineVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> PartialEq for InlineVec<T, N>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+// note: panics if the iterator yields more than N elements
+impl<T, const N: usize> FromIterator<T> for InlineVec<T, N>
+where
+    T: Copy + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vec = Self::new();
+        for item in iter {
+            vec.push(item);
+        }
+        vec
+    }
+}
+
+impl<T, const N: usize> IntoIterator for InlineVec<T, N> {
+    type Item = T;
+    type IntoIter = std::iter::Take<std::array::IntoIter<T, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().take(self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_vec() {
+        let mut vec = (1..=2).collect::<InlineVec<u64, 4>>();
+        assert_eq!(vec.as_slice(), &[1, 2]);
+        vec.push(3);
+        vec.push(4);
+        assert_eq!(vec.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(vec.len(), 4);
+        assert!(InlineVec::<u64, 2>::new().is_empty());
+    }
+}
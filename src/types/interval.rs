@@ -0,0 +1,153 @@
+/*
+** src/types/interval.rs
+*/
+
+use std::cmp;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+// a 1-dimensional range of integers
+// note: bounds are inclusive, so an interval is never empty
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct Interval {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Interval {
+    pub fn new(min: i64, max: i64) -> Self {
+        assert!(min <= max, "invalid interval {}..={}", min, max);
+        Self { min, max }
+    }
+
+    pub fn size(&self) -> i64 {
+        self.max - self.min + 1
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        self.min <= n && n <= self.max
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min <= other.max && other.min <= self.max
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if self.overlaps(other) {
+            Some(Self::new(
+                cmp::max(self.min, other.min),
+                cmp::min(self.max, other.max),
+            ))
+        } else {
+            None
+        }
+    }
+
+    // the union of two intervals, if it is itself an interval, i.e. they overlap or are adjacent
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        if self.min <= other.max + 1 && other.min <= self.max + 1 {
+            Some(Self::new(
+                cmp::min(self.min, other.min),
+                cmp::max(self.max, other.max),
+            ))
+        } else {
+            None
+        }
+    }
+
+    // the parts of this interval which are not in the other, of which there are at most 2
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let mut parts = vec![];
+        if self.min < other.min {
+            parts.push(Self::new(self.min, other.min - 1));
+        }
+        if other.max < self.max {
+            parts.push(Self::new(other.max + 1, self.max));
+        }
+        parts
+    }
+
+    // merges a collection of intervals into the fewest disjoint intervals which cover the same
+    // integers, sorted in ascending order
+    pub fn merge<I>(intervals: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut intervals = intervals.into_iter().collect::<Vec<_>>();
+        intervals.sort_by_key(|interval| interval.min);
+
+        let mut merged: Vec<Self> = vec![];
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if last.union(&interval).is_some() => {
+                    last.max = cmp::max(last.max, interval.max)
+                }
+                _ => merged.push(interval),
+            }
+        }
+        merged
+    }
+}
+
+impl From<RangeInclusive<i64>> for Interval {
+    fn from(range: RangeInclusive<i64>) -> Self {
+        let (min, max) = range.into_inner();
+        Self::new(min, max)
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("{}..={}", self.min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_operations() {
+        let a = Interval::from(0..=9);
+        let b = Interval::from(5..=14);
+        assert_eq!(a.size(), 10);
+        assert_eq!(a.intersection(&b), Some(Interval::from(5..=9)));
+        assert_eq!(a.union(&b), Some(Interval::from(0..=14)));
+        assert_eq!(a.subtract(&b), vec![Interval::from(0..=4)]);
+        assert_eq!(
+            b.subtract(&Interval::from(7..=8)),
+            vec![Interval::from(5..=6), Interval::from(9..=14)]
+        );
+        assert!(Interval::from(3..=4).subtract(&a).is_empty());
+
+        // adjacent intervals have a union but no intersection
+        let c = Interval::from(10..=12);
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(a.union(&c), Some(Interval::from(0..=12)));
+        assert_eq!(a.union(&Interval::from(11..=12)), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let intervals = [
+            Interval::from(20..=25),
+            Interval::from(0..=4),
+            Interval::from(3..=8),
+            Interval::from(9..=9),
+            Interval::from(22..=23),
+            Interval::from(12..=15),
+        ];
+        assert_eq!(
+            Interval::merge(intervals),
+            vec![
+                Interval::from(0..=9),
+                Interval::from(12..=15),
+                Interval::from(20..=25),
+            ]
+        );
+        assert!(Interval::merge(vec![]).is_empty());
+    }
+}
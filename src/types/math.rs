@@ -2,6 +2,8 @@
 ** src/types/math.rs
 */
 
+use num::PrimInt;
+
 use std::ops::{Div, Mul};
 
 macro_rules! bind_els {
@@ -86,3 +88,114 @@ impl Mul<&FVector2> for FMatrix2x2 {
         FVector2::new((a * e) + (b * f), (c * e) + (d * f))
     }
 }
+
+// a square matrix of integers, for stepping linear recurrences
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    size: usize,
+    data: Vec<T>,
+}
+
+impl<T> Matrix<T>
+where
+    T: PrimInt,
+{
+    pub fn zero(size: usize) -> Self {
+        let data = vec![T::zero(); size * size];
+        Self { size, data }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zero(size);
+        for i in 0..size {
+            matrix.set(i, i, T::one());
+        }
+        matrix
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.data[(row * self.size) + col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        self.data[(row * self.size) + col] = value;
+    }
+
+    // the product of two matrices, or None if any element overflows
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mut product = Self::zero(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let mut sum = T::zero();
+                for i in 0..self.size {
+                    sum = sum.checked_add(&self.get(row, i).checked_mul(&other.get(i, col))?)?;
+                }
+                product.set(row, col, sum);
+            }
+        }
+        Some(product)
+    }
+
+    // the product of the matrix with a column vector, or None if any element overflows
+    pub fn checked_mul_vector(&self, vector: &[T]) -> Option<Vec<T>> {
+        (0..self.size)
+            .map(|row| {
+                vector
+                    .iter()
+                    .enumerate()
+                    .try_fold(T::zero(), |sum, (col, &n)| {
+                        sum.checked_add(&self.get(row, col).checked_mul(&n)?)
+                    })
+            })
+            .collect()
+    }
+}
+
+// raises a matrix to the k-th power by repeated squaring, or None if any element overflows
+pub fn mat_pow<T>(matrix: &Matrix<T>, mut k: u64) -> Option<Matrix<T>>
+where
+    T: PrimInt,
+{
+    let mut result = Matrix::identity(matrix.size);
+    let mut base = matrix.clone();
+    while k > 0 {
+        if k & 1 == 1 {
+            result = result.checked_mul(&base)?;
+        }
+        k >>= 1;
+        // note: skip the final squaring, which would not be used and could overflow
+        if k > 0 {
+            base = base.checked_mul(&base)?;
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mat_pow() {
+        // the fibonacci recurrence
+        let mut fib = Matrix::<u64>::zero(2);
+        fib.set(0, 0, 1);
+        fib.set(0, 1, 1);
+        fib.set(1, 0, 1);
+        assert_eq!(mat_pow(&fib, 0), Some(Matrix::identity(2)));
+        assert_eq!(mat_pow(&fib, 10).unwrap().get(0, 1), 55);
+        assert_eq!(mat_pow(&fib, 92).unwrap().get(0, 0), 12200160415121876738);
+        // the 93rd power holds F(94), which does not fit into a u64, but does into a u128
+        assert_eq!(mat_pow(&fib, 93), None);
+        let mut fib = Matrix::<u128>::zero(2);
+        fib.set(0, 0, 1);
+        fib.set(0, 1, 1);
+        fib.set(1, 0, 1);
+        let v = mat_pow(&fib, 94).unwrap().checked_mul_vector(&[1, 0]);
+        assert_eq!(v, Some(vec![31940434634990099905, 19740274219868223167]));
+    }
+}
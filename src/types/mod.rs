@@ -2,21 +2,52 @@
 ** src/types/mod.rs
 */
 
+mod binary_tree;
+pub mod bits;
+mod char_grid;
+mod context;
+mod coord;
+pub mod expression;
+mod from_lines;
 mod geometry;
+pub mod grid;
+mod inline_vec;
+mod interval;
 mod math;
-
+pub mod ocr;
+mod priority_map;
+mod run_error;
+mod simulation;
+mod transposition;
+mod walker;
+mod wrapping_grid;
+
+pub use self::binary_tree::{BinaryNode, BinaryTree};
+pub use self::char_grid::CharGrid;
+pub use self::context::{Context, ContextError};
+pub use self::coord::Coord;
+pub use self::expression::{Expression, Op};
+pub use self::from_lines::FromLines;
 pub use self::geometry::{Line, Point};
-pub use self::math::{FMatrix2x2, FVector2};
-
-use crate::utils;
+pub use self::grid::{GridError, NeighborTable};
+pub use self::inline_vec::InlineVec;
+pub use self::interval::Interval;
+pub use self::math::{mat_pow, FMatrix2x2, FVector2, Matrix};
+pub use self::priority_map::PriorityMap;
+pub use self::run_error::{capture_panic_backtraces, RunError, Severity};
+pub use self::simulation::{Cycle, Simulation};
+pub use self::transposition::{Memo, Replacement, TranspositionTable};
+pub use self::walker::{Direction, Turn, Walkable, Walker};
+pub use self::wrapping_grid::WrappingGrid;
 
 use num::Integer;
+use rustc_hash::FxHashMap;
+use serde::{Serialize, Serializer};
 
-use std::collections::HashMap;
 use std::error;
 use std::fmt;
 use std::hash::Hash;
-use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 pub type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
@@ -25,9 +56,14 @@ pub type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 pub enum Solution {
     Int(i64),
     UInt(u64),
+    UInt128(u128),
+    Float(f64),
     String(String),
 }
 
+// the number of decimal places to which fractional solutions are displayed
+const FLOAT_PRECISION: usize = 6;
+
 impl From<i32> for Solution {
     fn from(n: i32) -> Self {
         Self::Int(n as i64)
@@ -52,12 +88,24 @@ impl From<u64> for Solution {
     }
 }
 
+impl From<u128> for Solution {
+    fn from(n: u128) -> Self {
+        Self::UInt128(n)
+    }
+}
+
 impl From<usize> for Solution {
     fn from(n: usize) -> Self {
         Self::UInt(n as u64)
     }
 }
 
+impl From<f64> for Solution {
+    fn from(x: f64) -> Self {
+        Self::Float(x)
+    }
+}
+
 impl From<String> for Solution {
     fn from(s: String) -> Self {
         Self::String(s)
@@ -69,14 +117,51 @@ impl fmt::Display for Solution {
         match self {
             Self::Int(i) => write!(f, "{}", i),
             Self::UInt(u) => write!(f, "{}", u),
+            Self::UInt128(u) => write!(f, "{}", u),
+            // note: rounded, and without trailing zeros, so that e.g. 0.1 + 0.2 displays as 0.3
+            // and whole numbers display as integers
+            Self::Float(x) if x.is_finite() => {
+                let s = format!("{:.*}", FLOAT_PRECISION, x);
+                let s = s.trim_end_matches('0').trim_end_matches('.');
+                write!(f, "{}", if s == "-0" { "0" } else { s })
+            }
+            Self::Float(x) => write!(f, "{}", x),
             Self::String(s) => write!(f, "{}", s),
         }
     }
 }
+
+// solutions serialize as their bare value rather than as a tagged variant
+// note: a string spanning several lines serializes as an array of its lines, which reads far
+// better than a single string of escaped newlines
+impl Serialize for Solution {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Int(i) => serializer.serialize_i64(*i),
+            Self::UInt(u) => serializer.serialize_u64(*u),
+            Self::UInt128(u) => serializer.serialize_u128(*u),
+            Self::Float(x) => serializer.serialize_f64(*x),
+            Self::String(s) if s.trim_end_matches('\n').contains('\n') => {
+                serializer.collect_seq(s.trim_end_matches('\n').lines())
+            }
+            Self::String(s) => serializer.serialize_str(s),
+        }
+    }
+}
+
 // puzzles are trait objects which conform to the following interface
 pub trait Puzzle {
     fn part_1(&self) -> Result<Solution>;
     fn part_2(&self) -> Result<Solution>;
+
+    // renders the parsed input, for inspecting the parser apart from the solutions
+    // note: puzzles which only parse their input within their solutions have nothing to render
+    fn dump(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Debug)]
@@ -94,6 +179,131 @@ impl fmt::Display for PuzzleError {
 
 impl error::Error for PuzzleError {}
 
+impl Serialize for PuzzleError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+// the outcome of running a single part of a puzzle
+#[derive(Debug, Serialize)]
+pub struct RunResult {
+    pub day: usize,
+    pub part: usize,
+    // errors are reduced to their message, as the boxed error cannot be serialized
+    #[serde(serialize_with = "serialize_answer")]
+    pub answer: std::result::Result<Solution, RunError>,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_duration")]
+    pub duration: Duration,
+    #[serde(skip_serializing_if = "Provenance::is_empty")]
+    pub provenance: Provenance,
+}
+
+// how a result was produced, such that results recorded on different machines or at different
+// commits can be told apart when compared
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Provenance {
+    // the named implementation which solved the part, if not the puzzle's own choice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    // the number of times the part was solved in measuring its duration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<usize>,
+    // the time taken to parse the input, which the duration of the part does not include
+    #[serde(
+        rename = "parse_ms",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_duration"
+    )]
+    pub parse_duration: Option<Duration>,
+    // the SHA-256 digest of the input, as from utils::input_digest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<String>,
+}
+
+impl Provenance {
+    pub fn is_empty(&self) -> bool {
+        self.variant.is_none()
+            && self.iterations.is_none()
+            && self.parse_duration.is_none()
+            && self.input_hash.is_none()
+    }
+}
+
+impl RunResult {
+    pub fn new(day: usize, part: usize, puzzle: &dyn Puzzle) -> Self {
+        let _span = tracing::info_span!("solve", day, part).entered();
+        let start = Instant::now();
+        // note: a panic fails only the part which raised it, rather than the whole run
+        let answer = RunError::catch(|| match part {
+            1 => puzzle.part_1(),
+            _ => puzzle.part_2(),
+        });
+        let duration = start.elapsed();
+        Self {
+            day,
+            part,
+            answer,
+            duration,
+            provenance: Provenance::default(),
+        }
+    }
+
+    // the result of a part which could not be run, such as for a puzzle whose input failed to
+    // parse
+    pub fn failed(day: usize, part: usize, err: RunError) -> Self {
+        Self {
+            day,
+            part,
+            answer: Err(err),
+            duration: Duration::ZERO,
+            provenance: Provenance::default(),
+        }
+    }
+
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+}
+
+fn serialize_answer<S>(
+    answer: &std::result::Result<Solution, RunError>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match answer {
+        Ok(solution) => solution.serialize(serializer),
+        Err(err) => serializer.serialize_str(&err.message),
+    }
+}
+
+fn serialize_duration<S>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+fn serialize_optional_duration<S>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        Some(duration) => serialize_duration(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[derive(Clone)]
 pub struct Array2D<T, const W: usize, const H: usize> {
     data: [[T; W]; H],
 }
@@ -107,122 +317,158 @@ impl<T, const W: usize, const H: usize> Array2D<T, W, H> {
         Self { data }
     }
 
-    pub const fn left(i: usize, j: usize) -> Option<(usize, usize)> {
-        if j > 0 {
-            Some((i, j - 1))
+    pub const fn left(coord: Coord) -> Option<Coord> {
+        if coord.col > 0 {
+            Some(Coord::new(coord.row, coord.col - 1))
         } else {
             None
         }
     }
 
-    pub const fn right(i: usize, j: usize) -> Option<(usize, usize)> {
-        if j < W - 1 {
-            Some((i, j + 1))
+    pub const fn right(coord: Coord) -> Option<Coord> {
+        if coord.col < W - 1 {
+            Some(Coord::new(coord.row, coord.col + 1))
         } else {
             None
         }
     }
 
-    pub const fn up(i: usize, j: usize) -> Option<(usize, usize)> {
-        if i > 0 {
-            Some((i - 1, j))
+    pub const fn up(coord: Coord) -> Option<Coord> {
+        if coord.row > 0 {
+            Some(Coord::new(coord.row - 1, coord.col))
         } else {
             None
         }
     }
 
-    pub const fn down(i: usize, j: usize) -> Option<(usize, usize)> {
-        if i < H - 1 {
-            Some((i + 1, j))
+    pub const fn down(coord: Coord) -> Option<Coord> {
+        if coord.row < H - 1 {
+            Some(Coord::new(coord.row + 1, coord.col))
         } else {
             None
         }
     }
 
-    pub const fn up_left(i: usize, j: usize) -> Option<(usize, usize)> {
-        if i > 0 && j > 0 {
-            Some((i - 1, j - 1))
+    pub const fn up_left(coord: Coord) -> Option<Coord> {
+        if coord.row > 0 && coord.col > 0 {
+            Some(Coord::new(coord.row - 1, coord.col - 1))
         } else {
             None
         }
     }
 
-    pub const fn up_right(i: usize, j: usize) -> Option<(usize, usize)> {
-        if i > 0 && j < W - 1 {
-            Some((i - 1, j + 1))
+    pub const fn up_right(coord: Coord) -> Option<Coord> {
+        if coord.row > 0 && coord.col < W - 1 {
+            Some(Coord::new(coord.row - 1, coord.col + 1))
         } else {
             None
         }
     }
 
-    pub const fn down_left(i: usize, j: usize) -> Option<(usize, usize)> {
-        if i < H - 1 && j > 0 {
-            Some((i + 1, j - 1))
+    pub const fn down_left(coord: Coord) -> Option<Coord> {
+        if coord.row < H - 1 && coord.col > 0 {
+            Some(Coord::new(coord.row + 1, coord.col - 1))
         } else {
             None
         }
     }
 
-    pub const fn down_right(i: usize, j: usize) -> Option<(usize, usize)> {
-        if i < H - 1 && j < W - 1 {
-            Some((i + 1, j + 1))
+    pub const fn down_right(coord: Coord) -> Option<Coord> {
+        if coord.row < H - 1 && coord.col < W - 1 {
+            Some(Coord::new(coord.row + 1, coord.col + 1))
         } else {
             None
         }
     }
 
-    pub const fn neighbors(i: usize, j: usize) -> [Option<(usize, usize)>; 4] {
+    // the neighbors within the grid, left, right, up, then down
+    pub fn neighbors(coord: Coord) -> InlineVec<Coord, 4> {
         [
-            Self::left(i, j),
-            Self::right(i, j),
-            Self::up(i, j),
-            Self::down(i, j),
+            Self::left(coord),
+            Self::right(coord),
+            Self::up(coord),
+            Self::down(coord),
         ]
+        .into_iter()
+        .flatten()
+        .collect()
     }
 
-    pub const fn neighbors_with_diagonal(i: usize, j: usize) -> [Option<(usize, usize)>; 8] {
+    // as neighbors, followed by the diagonals
+    pub fn neighbors_with_diagonal(coord: Coord) -> InlineVec<Coord, 8> {
         [
-            Self::left(i, j),
-            Self::right(i, j),
-            Self::up(i, j),
-            Self::down(i, j),
-            Self::up_left(i, j),
-            Self::up_right(i, j),
-            Self::down_left(i, j),
-            Self::down_right(i, j),
+            Self::left(coord),
+            Self::right(coord),
+            Self::up(coord),
+            Self::down(coord),
+            Self::up_left(coord),
+            Self::up_right(coord),
+            Self::down_left(coord),
+            Self::down_right(coord),
         ]
+        .into_iter()
+        .flatten()
+        .collect()
     }
 
-    pub fn get(&self, i: usize, j: usize) -> T
+    // the neighbors of every cell, by row-major index, for searches which visit cells many times
+    pub fn neighbor_table() -> NeighborTable {
+        NeighborTable::new(W, H)
+    }
+
+    pub fn get(&self, coord: Coord) -> T
     where
         T: Copy,
     {
-        self.data[i][j]
+        self.data[coord.row][coord.col]
+    }
+
+    pub fn set(&mut self, coord: Coord, val: T) {
+        self.data[coord.row][coord.col] = val;
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        Coord::iter_rect(W, H)
+    }
+
+    // cells in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter().flatten()
     }
 
-    pub fn set(&mut self, i: usize, j: usize, val: T) {
-        self.data[i][j] = val;
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.iter_mut().flatten()
     }
 
-    fn iter_indices(&self) -> impl Iterator<Item = (usize, usize)> {
-        itertools::iproduct!(0..H, 0..W)
+    pub fn iter_with_coords(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(self.iter())
     }
 
-    pub fn iter_with_indices(&self) -> impl Iterator<Item = (usize, usize, &T)> {
-        // need collect().into_iter() in order to avoid lifetime issues with the map closure
-        self.iter_indices()
-            .map(|(i, j)| (i, j, &self.data[i][j]))
-            .collect::<Vec<_>>()
-            .into_iter()
+    pub fn iter_mut_with_coords(&mut self) -> impl Iterator<Item = (Coord, &mut T)> {
+        Coord::iter_rect(W, H).zip(self.iter_mut())
+    }
+
+    // the first coordinate, in row-major order, whose cell satisfies the predicate
+    pub fn position_where<P>(&self, predicate: P) -> Option<Coord>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.iter()
+            .position(predicate)
+            .map(|index| Coord::from_index(index, W))
     }
 
-    pub fn find_index<P>(&self, predicate: P) -> Option<(usize, usize)>
+    // applies f to every cell which satisfies the predicate, in row-major order
+    pub fn for_each_mut<P, F>(&mut self, predicate: P, mut f: F)
     where
         P: Fn(&T) -> bool,
+        F: FnMut(Coord, &mut T),
     {
-        self.iter_with_indices()
-            .find(|(_, _, x)| predicate(x))
-            .map(|(i, j, _)| (i, j))
+        for (coord, cell) in self.iter_mut_with_coords() {
+            if predicate(cell) {
+                f(coord, cell);
+            }
+        }
     }
 }
 
@@ -230,24 +476,50 @@ impl<T, const W: usize, const H: usize> Array2D<T, W, H>
 where
     T: Copy + Integer,
 {
-    pub fn increment(&mut self, i: usize, j: usize) {
-        self.data[i][j] = self.data[i][j] + T::one();
+    pub fn increment(&mut self, coord: Coord) {
+        self.data[coord.row][coord.col] = self.data[coord.row][coord.col] + T::one();
     }
 }
 
-impl<T, const W: usize, const H: usize> From<&'static str> for Array2D<T, W, H>
+impl<T, const W: usize, const H: usize> Array2D<T, W, H>
 where
-    T: Copy + Default + FromStr,
-    <T as FromStr>::Err: fmt::Debug,
+    T: Copy + Default,
 {
-    fn from(s: &'static str) -> Self {
+    // fills the grid from parsed cells, which must be exactly W by H
+    fn from_cells(
+        width: usize,
+        height: usize,
+        cells: Vec<T>,
+    ) -> std::result::Result<Self, GridError> {
+        if (width, height) != (W, H) {
+            return Err(GridError::WrongSize {
+                width,
+                height,
+                expected_width: W,
+                expected_height: H,
+            });
+        }
         let mut arr = Self::new();
-        for (i, line) in utils::input_to_lines(s).enumerate() {
-            for (j, c) in line.chars().enumerate() {
-                arr.data[i][j] = c.to_string().parse().unwrap();
-            }
+        for (index, cell) in cells.into_iter().enumerate() {
+            arr.set(Coord::from_index(index, W), cell);
         }
-        arr
+        Ok(arr)
+    }
+
+    pub fn parse_chars<F>(input: &str, mapper: F) -> std::result::Result<Self, GridError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let (width, height, cells) = grid::parse_chars(input, mapper)?;
+        Self::from_cells(width, height, cells)
+    }
+
+    pub fn parse_digits(input: &str) -> std::result::Result<Self, GridError>
+    where
+        T: From<u8>,
+    {
+        let (width, height, cells) = grid::parse_digits(input)?;
+        Self::from_cells(width, height, cells)
     }
 }
 
@@ -260,8 +532,10 @@ where
     }
 }
 
+// note: uses a fixed hasher rather than std's randomly seeded one, such that iterating over the
+// counts visits them in the same order on every run
 pub struct Counter<T> {
-    counts: HashMap<T, usize>,
+    counts: FxHashMap<T, usize>,
 }
 
 impl<T> Counter<T>
@@ -270,7 +544,7 @@ where
 {
     pub fn new() -> Self {
         Self {
-            counts: HashMap::new(),
+            counts: FxHashMap::default(),
         }
     }
 
@@ -297,13 +571,22 @@ where
     }
 }
 
+impl<T> Default for Counter<T>
+where
+    T: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T, I> From<I> for Counter<T>
 where
     T: Clone + Eq + Hash,
     I: Iterator<Item = T>,
 {
     fn from(it: I) -> Self {
-        let mut counts = HashMap::new();
+        let mut counts = FxHashMap::default();
         for el in it {
             let count = counts.entry(el.clone()).or_insert(0);
             *count += 1;
@@ -312,219 +595,136 @@ where
     }
 }
 
-pub struct TreeNode<T> {
-    _id: u64,
-    pub data: T,
-    // ID of parent node
-    parent: Option<u64>,
-    // IDs of children nodes
-    pub children: Vec<u64>,
-}
-
-impl<T> TreeNode<T> {
-    fn new(_id: u64, data: T, parent: Option<u64>) -> Self {
-        let children = vec![];
-        Self {
-            _id,
-            data,
-            parent,
-            children,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_solution() {
+        let solutions = vec![
+            Solution::from(-1),
+            Solution::from(2u64),
+            Solution::from(u128::MAX),
+            Solution::from(0.25),
+            Solution::from(String::from("ABC")),
+        ];
+        assert_eq!(
+            serde_json::to_string(&solutions).unwrap(),
+            format!("[-1,2,{},0.25,\"ABC\"]", u128::MAX)
+        );
+        // multi-line answers serialize as their lines
+        let grid = Solution::from(String::from("#..#\n####\n"));
+        assert_eq!(serde_json::to_string(&grid).unwrap(), "[\"#..#\",\"####\"]");
+        assert_eq!(
+            serde_json::to_string(&PuzzleError::NoSolution).unwrap(),
+            "\"no solution found\""
+        );
+    }
+
+    #[test]
+    fn test_display_float_solution() {
+        let display = |x: f64| Solution::from(x).to_string();
+        assert_eq!(display(0.1 + 0.2), "0.3");
+        assert_eq!(display(2.5), "2.5");
+        assert_eq!(display(42.0), "42");
+        assert_eq!(display(-1.0 / 3.0), "-0.333333");
+        assert_eq!(display(-0.0000001), "0");
+        assert_eq!(display(f64::NAN), "NaN");
+        assert_eq!(display(f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn test_array2d_iter() {
+        let mut arr = Array2D::<u8, 3, 2>::parse_digits("123\n456").unwrap();
+        for (coord, cell) in arr.iter_mut_with_coords() {
+            *cell += coord.row as u8;
         }
-    }
-
-    fn find_child(&self, node_id: u64) -> Option<usize> {
-        self.children
-            .iter()
-            .enumerate()
-            .find(|(_, &child_id)| child_id == node_id)
-            .map(|(i, _)| i)
-    }
-}
-
-pub struct Tree<T> {
-    pub root: Option<u64>,
-    nodes: Vec<Option<TreeNode<T>>>,
-    // maps node IDs to their position in the nodes array
-    node_positions: HashMap<u64, usize>,
-    id_tracker: u64,
-}
-
-impl<T> Tree<T> {
-    pub fn new() -> Self {
-        let nodes = (0..64).map(|_| None).collect();
-        let node_positions = HashMap::new();
-        Self {
-            root: None,
-            nodes,
-            node_positions,
-            id_tracker: 0,
-        }
-    }
-
-    pub fn node(&self, id: u64) -> Option<&TreeNode<T>> {
-        let pos = self.node_positions[&id];
-        self.nodes[pos].as_ref()
-    }
-
-    pub fn node_data(&self, id: u64) -> Option<&T> {
-        self.node(id).map(|node| &node.data)
-    }
-
-    pub fn node_mut(&mut self, id: u64) -> Option<&mut TreeNode<T>> {
-        let pos = self.node_positions[&id];
-        self.nodes[pos].as_mut()
-    }
-
-    fn find_first_open_slot(&mut self) -> usize {
-        for (i, node) in self.nodes.iter().enumerate() {
-            if node.is_none() {
-                return i;
+        assert_eq!(
+            arr.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 6, 7]
+        );
+        assert_eq!(arr.iter_with_coords().nth(4), Some((Coord::new(1, 1), &6)));
+        assert_eq!(arr.position_where(|&x| x > 4), Some(Coord::new(1, 0)));
+        assert_eq!(arr.position_where(|&x| x > 7), None);
+
+        let mut reset = vec![];
+        arr.for_each_mut(
+            |&x| x % 2 == 1,
+            |coord, x| {
+                *x = 0;
+                reset.push(coord);
+            },
+        );
+        assert_eq!(
+            reset,
+            vec![
+                Coord::new(0, 0),
+                Coord::new(0, 2),
+                Coord::new(1, 0),
+                Coord::new(1, 2)
+            ]
+        );
+        assert_eq!(
+            arr.iter().copied().collect::<Vec<_>>(),
+            vec![0, 2, 0, 0, 6, 0]
+        );
+    }
+
+    #[test]
+    fn test_serialize_run_result() {
+        let result = RunResult {
+            day: 1,
+            part: 2,
+            answer: Err(RunError::from_error(Box::new(PuzzleError::NoSolution))),
+            duration: Duration::from_micros(1500),
+            provenance: Provenance::default(),
+        };
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"day":1,"part":2,"answer":"no solution found","duration_ms":1.5}"#
+        );
+
+        // only the provenance which is known is written
+        let result = result.with_provenance(Provenance {
+            variant: Some(String::from("fast")),
+            parse_duration: Some(Duration::from_micros(250)),
+            ..Provenance::default()
+        });
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"day":1,"part":2,"answer":"no solution found","duration_ms":1.5,"provenance":{"variant":"fast","parse_ms":0.25}}"#
+        );
+    }
+
+    #[test]
+    fn test_run_errors() {
+        struct Failing;
+        impl Puzzle for Failing {
+            fn part_1(&self) -> Result<Solution> {
+                Err(PuzzleError::NoSolution.into())
             }
-        }
-
-        // no slot found, resize
-        let size = self.nodes.len();
-        self.nodes.resize_with(size * 2, Default::default);
-        size
-    }
-
-    pub fn insert(&mut self, data: T, parent: Option<u64>) -> u64 {
-        let id = self.id_tracker;
-        let node = TreeNode::new(id, data, parent);
-
-        // add and track the new node
-        let pos = self.find_first_open_slot();
-        self.nodes[pos] = Some(node);
-        self.node_positions.insert(id, pos);
-        self.id_tracker += 1;
-
-        // if provided, hook the node up to its parent
-        if let Some(parent_id) = parent {
-            let parent_node = self.node_mut(parent_id).unwrap();
-            parent_node.children.push(id);
-        } else {
-            self.root = Some(id);
-        }
-
-        id
-    }
-
-    pub fn remove(&mut self, node_id: u64) {
-        if let Some(node) = self.node(node_id) {
-            // unhook from the parent
-            if let Some(parent_id) = node.parent {
-                let parent = self.node_mut(parent_id).unwrap();
-                let i = parent.find_child(node_id).unwrap();
-                parent.children.remove(i);
-            }
-
-            // remove from the nodes and node position structures
-            let pos = self.node_positions.remove(&node_id).unwrap();
-            self.nodes[pos] = None;
-        }
-    }
-
-    pub fn left_neighbor_node(&self, node_id: u64) -> Option<u64> {
-        if let Some(node) = self.node(node_id) {
-            // grab the parent
-            if let Some(parent_id) = node.parent {
-                let parent = self.node(parent_id).unwrap();
-                // check the parent's children for a left neighbor
-                let i = parent.find_child(node_id).unwrap();
-                if i > 0 {
-                    Some(parent.children[i - 1])
-                } else {
-                    // otherwise need to check the parent's parent
-                    self.left_neighbor_node(parent_id)
-                }
-            } else {
-                // otherwise there is no neighbor
-                None
-            }
-        } else {
-            None
-        }
-    }
-
-    pub fn left_neighbor_leaf(&self, node_id: u64) -> Option<u64> {
-        if let Some(neighbor_id) = self.left_neighbor_node(node_id) {
-            let mut id = neighbor_id;
-            let mut node = self.node(neighbor_id).unwrap();
-            while !node.children.is_empty() {
-                // check the rightmost child
-                id = node.children[node.children.len() - 1];
-                node = self.node(id).unwrap();
-            }
-            Some(id)
-        } else {
-            None
-        }
-    }
-
-    pub fn right_neighbor_node(&self, node_id: u64) -> Option<u64> {
-        if let Some(node) = self.node(node_id) {
-            // grab the parent
-            if let Some(parent_id) = node.parent {
-                let parent = self.node(parent_id).unwrap();
-                // check the parent's children for a right neighbor
-                let i = parent.find_child(node_id).unwrap();
-                if i < parent.children.len() - 1 {
-                    Some(parent.children[i + 1])
-                } else {
-                    // otherwise need to check the parent's parent
-                    self.right_neighbor_node(parent_id)
-                }
-            } else {
-                // otherwise there is no neighbor
-                None
+            fn part_2(&self) -> Result<Solution> {
+                panic!("unexpected character '{}'", '?')
             }
-        } else {
-            None
-        }
-    }
-
-    pub fn right_neighbor_leaf(&self, node_id: u64) -> Option<u64> {
-        if let Some(neighbor_id) = self.right_neighbor_node(node_id) {
-            let mut id = neighbor_id;
-            let mut node = self.node(neighbor_id).unwrap();
-            while !node.children.is_empty() {
-                // check the leftmost child
-                id = node.children[0];
-                node = self.node(id).unwrap();
-            }
-            Some(id)
-        } else {
-            None
-        }
-    }
-
-    fn consume_tree(&mut self, tree: &Self, from_node: u64, into_node: u64)
-    where
-        T: Clone,
-    {
-        if let Some(node) = tree.node(from_node) {
-            let new_id = self.insert(node.data.clone(), Some(into_node));
-            for child_id in node.children.iter() {
-                self.consume_tree(tree, *child_id, new_id);
-            }
-        }
-    }
-
-    // combines two trees under a single root
-    pub fn combine_trees(tree_a: &Self, tree_b: &Self, root: T) -> Self
-    where
-        T: Clone,
-    {
-        let mut tree = Self::new();
-        let root_id = tree.insert(root, None);
-        // add the left and right trees
-        if let Some(left_root) = tree_a.root {
-            tree.consume_tree(tree_a, left_root, root_id);
-        }
-        if let Some(right_root) = tree_b.root {
-            tree.consume_tree(tree_b, right_root, root_id);
         }
 
-        tree
+        // the default hook reports the panic on stderr, which the test harness captures
+        let part_1 = RunResult::new(1, 1, &Failing).answer.unwrap_err();
+        assert_eq!(part_1.severity, Severity::Warning);
+        assert_eq!(part_1.to_string(), "warning: no solution found");
+        let part_2 = RunResult::new(1, 2, &Failing).answer.unwrap_err();
+        assert_eq!(part_2.severity, Severity::Panic);
+        assert_eq!(part_2.to_string(), "panic: unexpected character '?'");
+
+        // a puzzle which fails to parse fails each part with the parse error
+        let parsed = RunError::catch(|| crate::puzzles::from_lines(2, "up x".lines()).unwrap());
+        let part_1 = RunResult::failed(2, 1, parsed.err().unwrap())
+            .answer
+            .unwrap_err();
+        assert_eq!(part_1.severity, Severity::Error);
+        assert_eq!(
+            part_1.to_string(),
+            "error: command 1: units: invalid digit found in string"
+        );
     }
 }
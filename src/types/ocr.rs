@@ -0,0 +1,168 @@
+/*
+** src/types/ocr.rs
+*/
+
+// reads capital letters drawn in lit cells, in the font which the puzzles use: glyphs 6 cells
+// tall, set 5 columns apart, with the letters which have been seen in puzzle answers
+
+use std::error;
+use std::fmt;
+
+pub const GLYPH_HEIGHT: usize = 6;
+// the columns from the start of one letter to the next
+pub const GLYPH_SPACING: usize = 5;
+
+const GLYPHS: [(char, [&str; GLYPH_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    // note: the only glyph which fills the column between letters
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, PartialEq)]
+pub enum OcrError {
+    Empty,
+    // the lit cells span the wrong number of rows to be a line of letters
+    WrongHeight(usize),
+    // the letter at the given index is not in the font
+    UnknownGlyph(usize),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no lit cells to read"),
+            Self::WrongHeight(height) => write!(
+                f,
+                "letters are {} cells tall but found {}",
+                GLYPH_HEIGHT, height
+            ),
+            Self::UnknownGlyph(index) => write!(f, "letter {} is not a known glyph", index + 1),
+        }
+    }
+}
+
+impl error::Error for OcrError {}
+
+// the letter drawn in the columns of the grid starting from the given one
+fn read_letter(grid: &[Vec<bool>], start: usize) -> Option<char> {
+    let lit = |row: usize, col: usize| grid[row].get(start + col).copied().unwrap_or(false);
+    GLYPHS
+        .iter()
+        .find(|(_, glyph)| {
+            glyph.iter().enumerate().all(|(row, pattern)| {
+                (0..GLYPH_SPACING)
+                    .all(|col| lit(row, col) == (pattern.as_bytes().get(col) == Some(&b'#')))
+            })
+        })
+        .map(|&(c, _)| c)
+}
+
+// reads a line of letters from rows of cells, where true is lit
+pub fn read_grid(grid: &[Vec<bool>]) -> Result<String, OcrError> {
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    if width == 0 {
+        return Err(OcrError::Empty);
+    }
+    if grid.len() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight(grid.len()));
+    }
+    (0..width.div_ceil(GLYPH_SPACING))
+        .map(|i| read_letter(grid, i * GLYPH_SPACING).ok_or(OcrError::UnknownGlyph(i)))
+        .collect()
+}
+
+// reads a line of letters from the (x, y) positions of the lit cells, where the first letter
+// starts at x = 0
+// note: every glyph is lit in its top row, but not every glyph is lit in its first column, so
+// the rows are found from the topmost lit cell but the columns are taken as they are
+pub fn read_points<I>(points: I) -> Result<String, OcrError>
+where
+    I: IntoIterator<Item = (usize, usize)>,
+{
+    let points = points.into_iter().collect::<Vec<_>>();
+    let y_min = points
+        .iter()
+        .map(|&(_, y)| y)
+        .min()
+        .ok_or(OcrError::Empty)?;
+    let mut grid = vec![];
+    for (col, y) in points {
+        let row = y - y_min;
+        if row >= grid.len() {
+            grid.resize(row + 1, vec![]);
+        }
+        if col >= grid[row].len() {
+            grid[row].resize(col + 1, false);
+        }
+        grid[row][col] = true;
+    }
+    read_grid(&grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // draws the glyphs side by side, as a puzzle would
+    fn draw(letters: &str) -> Vec<Vec<bool>> {
+        (0..GLYPH_HEIGHT)
+            .map(|row| {
+                letters
+                    .chars()
+                    .flat_map(|c| {
+                        let (_, glyph) = GLYPHS.iter().find(|&&(g, _)| g == c).unwrap();
+                        let pattern = format!("{:.<width$}", glyph[row], width = GLYPH_SPACING);
+                        pattern.chars().map(|c| c == '#').collect::<Vec<_>>()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_read_every_glyph() {
+        for &(c, _) in GLYPHS.iter() {
+            assert_eq!(read_grid(&draw(&c.to_string())), Ok(c.to_string()));
+        }
+        let letters = GLYPHS.iter().map(|&(c, _)| c).collect::<String>();
+        assert_eq!(read_grid(&draw(&letters)), Ok(letters));
+    }
+
+    #[test]
+    fn test_read_points() {
+        // "IH", shifted down from the origin
+        let points = draw("IH")
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|&(_, &lit)| lit)
+                    .map(move |(x, _)| (x, y + 3))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(read_points(points), Ok(String::from("IH")));
+        assert_eq!(read_points(vec![]), Err(OcrError::Empty));
+        assert_eq!(read_points(vec![(0, 0)]), Err(OcrError::WrongHeight(1)));
+
+        let mut grid = draw("AB");
+        grid[0][5] = false;
+        assert_eq!(read_grid(&grid), Err(OcrError::UnknownGlyph(1)));
+    }
+}
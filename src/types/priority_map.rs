@@ -0,0 +1,180 @@
+/*
+** src/types/priority_map.rs
+*/
+
+use rustc_hash::FxHashMap;
+
+use std::hash::Hash;
+
+// a min-priority queue in which each key appears at most once, such that its priority can be
+// updated in place rather than pushing duplicates and skipping the stale entries when popped
+pub struct PriorityMap<K, P> {
+    // a binary min-heap on priority
+    heap: Vec<(K, P)>,
+    // the index of each key in the heap
+    // note: this is updated at every level that an entry moves through, so it uses a cheaper hash
+    // than the standard library's
+    positions: FxHashMap<K, usize>,
+}
+
+impl<K, P> PriorityMap<K, P>
+where
+    K: Clone + Eq + Hash,
+    P: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            heap: vec![],
+            positions: FxHashMap::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.positions.get(key).map(|&i| &self.heap[i].1)
+    }
+
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.heap.first().map(|(key, priority)| (key, priority))
+    }
+
+    // adds a key, or sets its priority if it is already queued
+    pub fn push(&mut self, key: K, priority: P) {
+        if let Some(&i) = self.positions.get(&key) {
+            self.heap[i].1 = priority;
+            self.restore(i);
+        } else {
+            let i = self.heap.len();
+            self.positions.insert(key.clone(), i);
+            self.heap.push((key, priority));
+            self.sift_up(i);
+        }
+    }
+
+    // sets the priority of a queued key, returning false if it is not queued
+    pub fn update_priority(&mut self, key: &K, priority: P) -> bool {
+        match self.positions.get(key) {
+            Some(&i) => {
+                self.heap[i].1 = priority;
+                self.restore(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // removes the key with the lowest priority
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let (key, priority) = self.heap.swap_remove(0);
+        self.positions.remove(&key);
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some((key, priority))
+    }
+
+    fn set_position(&mut self, i: usize) {
+        if let Some(position) = self.positions.get_mut(&self.heap[i].0) {
+            *position = i;
+        }
+    }
+
+    // restores the heap order after the priority of the entry at index i has changed
+    fn restore(&mut self, i: usize) {
+        let i = self.sift_up(i);
+        self.sift_down(i);
+    }
+
+    // moves the entry at index i towards the root until its parent has a lower priority, and
+    // returns its new index
+    // note: only the entries which are displaced have their positions updated along the way
+    fn sift_up(&mut self, mut i: usize) -> usize {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.heap[i].1 >= self.heap[parent].1 {
+                break;
+            }
+            self.heap.swap(i, parent);
+            self.set_position(i);
+            i = parent;
+        }
+        self.set_position(i);
+        i
+    }
+
+    // moves the entry at index i towards the leaves until its children have higher priorities
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let mut lowest = i;
+            for child in [(2 * i) + 1, (2 * i) + 2] {
+                if child < self.heap.len() && self.heap[child].1 < self.heap[lowest].1 {
+                    lowest = child;
+                }
+            }
+            if lowest == i {
+                break;
+            }
+            self.heap.swap(i, lowest);
+            self.set_position(i);
+            i = lowest;
+        }
+        self.set_position(i);
+    }
+}
+
+impl<K, P> Default for PriorityMap<K, P>
+where
+    K: Clone + Eq + Hash,
+    P: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_map() {
+        let mut queue = PriorityMap::new();
+        for (key, priority) in [('a', 5), ('b', 3), ('c', 8), ('d', 1), ('e', 7)] {
+            queue.push(key, priority);
+        }
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.peek(), Some((&'d', &1)));
+
+        // pushing a queued key updates it rather than adding a duplicate
+        queue.push('c', 2);
+        assert!(queue.update_priority(&'d', 9));
+        assert!(!queue.update_priority(&'z', 0));
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.priority(&'c'), Some(&2));
+
+        let mut popped = vec![];
+        while let Some(entry) = queue.pop() {
+            popped.push(entry);
+        }
+        assert_eq!(
+            popped,
+            vec![('c', 2), ('b', 3), ('a', 5), ('e', 7), ('d', 9)]
+        );
+        assert!(queue.is_empty());
+        assert!(!queue.contains_key(&'a'));
+    }
+}
@@ -0,0 +1,113 @@
+/*
+** src/types/run_error.rs
+*/
+
+use serde::Serialize;
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::error;
+use std::fmt;
+use std::panic;
+
+use super::{PuzzleError, Result};
+
+thread_local! {
+    // the backtrace of the latest panic on this thread, once backtraces are captured
+    static PANIC_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// how badly a part failed
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    // the puzzle ran to completion but found no answer
+    Warning,
+    // the puzzle reported an error
+    Error,
+    // the puzzle panicked
+    Panic,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+            Self::Panic => write!(f, "panic"),
+        }
+    }
+}
+
+// the failure of a single part, reduced to its message, as the boxed error cannot be serialized
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunError {
+    pub severity: Severity,
+    pub message: String,
+    // where a panic was raised, if backtraces are captured
+    pub backtrace: Option<String>,
+}
+
+impl RunError {
+    pub fn from_error(err: Box<dyn error::Error>) -> Self {
+        let severity = match err.downcast_ref::<PuzzleError>() {
+            Some(PuzzleError::NoSolution) => Severity::Warning,
+            None => Severity::Error,
+        };
+        Self {
+            severity,
+            message: err.to_string(),
+            backtrace: None,
+        }
+    }
+
+    pub fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        // note: panics carry their message as either a literal or a formatted string
+        let message = match (
+            payload.downcast_ref::<&str>(),
+            payload.downcast_ref::<String>(),
+        ) {
+            (Some(message), _) => message.to_string(),
+            (_, Some(message)) => message.clone(),
+            _ => String::from("panicked"),
+        };
+        Self {
+            severity: Severity::Panic,
+            message,
+            backtrace: PANIC_BACKTRACE.with(|backtrace| backtrace.borrow_mut().take()),
+        }
+    }
+
+    // runs the given step, such as parsing a puzzle or solving one of its parts, reducing either
+    // an error or a panic to its message
+    pub fn catch<T, F>(f: F) -> std::result::Result<T, Self>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+            Ok(result) => result.map_err(Self::from_error),
+            Err(payload) => Err(Self::from_panic(payload)),
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+// replaces the panic hook with one which keeps the backtrace of each panic for the part which
+// raised it to report, and otherwise only reports where the panic was
+// note: the message is left to the report, as it may quote the input
+pub fn capture_panic_backtraces() {
+    panic::set_hook(Box::new(|info| {
+        match info.location() {
+            Some(location) => eprintln!("panicked at {}", location),
+            None => eprintln!("panicked"),
+        }
+        let backtrace = Backtrace::force_capture().to_string();
+        PANIC_BACKTRACE.with(|slot| *slot.borrow_mut() = Some(backtrace));
+    }));
+}
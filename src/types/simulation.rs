@@ -0,0 +1,138 @@
+/*
+** src/types/simulation.rs
+*/
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// the steps at which a simulation first repeats a state: the state after start steps is the same
+// as the state after start + length steps
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+// a state which evolves by repeatedly applying a step function
+pub trait Simulation {
+    fn step(&mut self);
+
+    fn run(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    // steps until the predicate holds, returning the number of steps taken
+    // note: the predicate is checked before each step, so this is 0 if it already holds
+    fn run_until<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(&Self) -> bool,
+    {
+        let mut steps = 0;
+        while !predicate(self) {
+            self.step();
+            steps += 1;
+        }
+        steps
+    }
+
+    // steps until a state repeats, leaving the simulation at the end of the first cycle
+    // note: never returns if the states do not repeat
+    fn find_cycle(&mut self) -> Cycle
+    where
+        Self: Clone + Eq + Hash,
+    {
+        let mut seen = HashMap::new();
+        let mut steps = 0;
+        loop {
+            if let Some(&start) = seen.get(self) {
+                return Cycle {
+                    start,
+                    length: steps - start,
+                };
+            }
+            seen.insert(self.clone(), steps);
+            self.step();
+            steps += 1;
+        }
+    }
+
+    // as run, but skips over whole cycles once the states start to repeat, such that a large
+    // number of steps only costs as much as the first cycle
+    fn run_cycled(&mut self, n: usize)
+    where
+        Self: Clone + Eq + Hash,
+    {
+        let mut seen = HashMap::new();
+        let mut steps = 0;
+        while steps < n {
+            if let Some(&start) = seen.get(self) {
+                let length = steps - start;
+                self.run((n - steps) % length);
+                return;
+            }
+            seen.insert(self.clone(), steps);
+            self.step();
+            steps += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // counts from 0 up to the modulus and then wraps, after a lead-in of offsets
+    #[derive(Clone, Eq, Hash, PartialEq)]
+    struct Counter {
+        lead_in: u64,
+        n: u64,
+        modulus: u64,
+    }
+
+    impl Simulation for Counter {
+        fn step(&mut self) {
+            if self.lead_in > 0 {
+                self.lead_in -= 1;
+            } else {
+                self.n = (self.n + 1) % self.modulus;
+            }
+        }
+    }
+
+    fn counter() -> Counter {
+        Counter {
+            lead_in: 3,
+            n: 0,
+            modulus: 5,
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let mut sim = counter();
+        sim.run(6);
+        assert_eq!(sim.n, 3);
+        assert_eq!(sim.run_until(|sim| sim.n == 1), 3);
+        assert_eq!(sim.run_until(|sim| sim.n == 1), 0);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut sim = counter();
+        assert_eq!(
+            sim.find_cycle(),
+            Cycle {
+                start: 3,
+                length: 5
+            }
+        );
+
+        let mut expected = counter();
+        expected.run(1_000_003);
+        let mut sim = counter();
+        sim.run_cycled(1_000_003);
+        assert!(sim == expected);
+    }
+}
@@ -0,0 +1,188 @@
+/*
+** src/types/transposition.rs
+*/
+
+// bounded memoization for recursive solvers, which store the value of each position solved so
+// far and look it up again when the position is reached by another sequence of moves
+
+use rustc_hash::FxHasher;
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+// which of two positions sharing a slot is kept
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Replacement {
+    // the newer position always replaces the older
+    Always,
+    // the position of greater depth is kept, since it took more work to solve, and the newer
+    // position on a tie
+    Deeper,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    depth: u32,
+}
+
+// a memo of a fixed number of slots, each holding at most one position chosen by its hash, such
+// that memory stays bounded however many positions are solved
+// note: a forgotten position is only solved again, so evictions cost time but not correctness
+pub struct TranspositionTable<K, V> {
+    slots: Vec<Option<Entry<K, V>>>,
+    replacement: Replacement,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K, V> TranspositionTable<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new(capacity: usize, replacement: Replacement) -> Self {
+        assert!(capacity > 0, "transposition table with no slots");
+        Self {
+            slots: (0..capacity).map(|_| None).collect(),
+            replacement,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    // note: uses a fixed hasher, such that the same positions are evicted on every run
+    fn slot(&self, key: &K) -> usize {
+        let mut hasher = FxHasher::default();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.slots.len() as u64) as usize
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    // the number of positions currently held
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let i = self.slot(key);
+        match &self.slots[i] {
+            Some(entry) if entry.key == *key => {
+                self.hits += 1;
+                Some(&entry.value)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    // stores a position unless its slot holds another which the replacement policy keeps, where
+    // depth measures how much work the position took to solve
+    pub fn insert(&mut self, key: K, value: V, depth: u32) {
+        let i = self.slot(&key);
+        if let Some(entry) = &self.slots[i] {
+            if entry.key != key {
+                if self.replacement == Replacement::Deeper && entry.depth > depth {
+                    return;
+                }
+                self.evictions += 1;
+            }
+        }
+        self.slots[i] = Some(Entry { key, value, depth });
+    }
+
+    // lookups which found their position, lookups which did not, and positions overwritten by
+    // others sharing their slot
+    pub fn stats(&self) -> (u64, u64, u64) {
+        (self.hits, self.misses, self.evictions)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+// the positions solved so far by a recursive solver, which may be bounded and forget some of them
+pub trait Memo<K, V> {
+    fn recall(&mut self, key: &K) -> Option<V>;
+    fn remember(&mut self, key: K, value: V, depth: u32);
+}
+
+// an unbounded memo, which never forgets a position
+impl<K, V> Memo<K, V> for HashMap<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    fn recall(&mut self, key: &K) -> Option<V> {
+        self.get(key).cloned()
+    }
+
+    fn remember(&mut self, key: K, value: V, _depth: u32) {
+        self.insert(key, value);
+    }
+}
+
+impl<K, V> Memo<K, V> for TranspositionTable<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    fn recall(&mut self, key: &K) -> Option<V> {
+        self.get(key).cloned()
+    }
+
+    fn remember(&mut self, key: K, value: V, depth: u32) {
+        self.insert(key, value, depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transposition_table() {
+        let mut table = TranspositionTable::new(1, Replacement::Deeper);
+        table.insert("a", 1, 5);
+        assert_eq!(table.get(&"a").copied(), Some(1));
+        // a shallower position does not replace a deeper one in its slot, but a deeper one does
+        table.insert("b", 2, 4);
+        assert_eq!(
+            (table.get(&"a").copied(), table.get(&"b").copied()),
+            (Some(1), None)
+        );
+        table.insert("c", 3, 6);
+        assert_eq!(
+            (table.get(&"a").copied(), table.get(&"c").copied()),
+            (None, Some(3))
+        );
+        assert_eq!(table.stats(), (3, 2, 1));
+        assert_eq!((table.len(), table.capacity()), (1, 1));
+
+        let mut table = TranspositionTable::new(1, Replacement::Always);
+        table.insert("a", 1, 5);
+        table.insert("b", 2, 4);
+        assert_eq!(
+            (table.get(&"a").copied(), table.get(&"b").copied()),
+            (None, Some(2))
+        );
+        // updating a position is not an eviction
+        table.insert("b", 3, 0);
+        assert_eq!(table.iter().collect::<Vec<_>>(), vec![(&"b", &3)]);
+        assert_eq!(table.stats().2, 1);
+    }
+}
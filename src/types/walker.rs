@@ -0,0 +1,162 @@
+/*
+** src/types/walker.rs
+*/
+
+use super::{Array2D, CharGrid, Coord, WrappingGrid};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    // the (row, col) offset of a single step
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (-1, 0),
+            Self::Right => (0, 1),
+            Self::Down => (1, 0),
+            Self::Left => (0, -1),
+        }
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match (self, turn) {
+            (Self::Up, Turn::Right) | (Self::Down, Turn::Left) => Self::Right,
+            (Self::Right, Turn::Right) | (Self::Left, Turn::Left) => Self::Down,
+            (Self::Down, Turn::Right) | (Self::Up, Turn::Left) => Self::Left,
+            (Self::Left, Turn::Right) | (Self::Right, Turn::Left) => Self::Up,
+            (Self::Up, Turn::Around) => Self::Down,
+            (Self::Right, Turn::Around) => Self::Left,
+            (Self::Down, Turn::Around) => Self::Up,
+            (Self::Left, Turn::Around) => Self::Right,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+// a grid which can be walked across one cell at a time
+pub trait Walkable {
+    // the coordinate a single step away in the given direction, if it is on the grid
+    fn step(&self, coord: Coord, direction: Direction) -> Option<Coord>;
+}
+
+impl<T, const W: usize, const H: usize> Walkable for Array2D<T, W, H> {
+    fn step(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        match direction {
+            Direction::Up => Self::up(coord),
+            Direction::Right => Self::right(coord),
+            Direction::Down => Self::down(coord),
+            Direction::Left => Self::left(coord),
+        }
+    }
+}
+
+impl Walkable for CharGrid {
+    fn step(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        let (drow, dcol) = direction.offset();
+        coord.offset(drow, dcol).filter(|&next| self.contains(next))
+    }
+}
+
+// note: stepping off of a wrapping grid re-enters from the opposite edge, so a step always lands
+impl<T> Walkable for WrappingGrid<T> {
+    fn step(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        let (drow, dcol) = direction.offset();
+        Some(WrappingGrid::step(self, coord, drow, dcol))
+    }
+}
+
+// a position on a grid along with the direction it is facing, for tracing paths
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Walker {
+    pub pos: Coord,
+    pub heading: Direction,
+}
+
+impl Walker {
+    pub fn new(pos: Coord, heading: Direction) -> Self {
+        Self { pos, heading }
+    }
+
+    // where the next step would land, without taking it
+    pub fn peek<G>(&self, grid: &G) -> Option<Coord>
+    where
+        G: Walkable,
+    {
+        grid.step(self.pos, self.heading)
+    }
+
+    // moves forward a single step, if doing so stays on the grid, returning whether it moved
+    pub fn step<G>(&mut self, grid: &G) -> bool
+    where
+        G: Walkable,
+    {
+        match self.peek(grid) {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        self.heading = self.heading.turn(turn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn() {
+        let mut heading = Direction::Up;
+        for expected in [
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+        ] {
+            heading = heading.turn(Turn::Right);
+            assert_eq!(heading, expected);
+        }
+        assert_eq!(Direction::Left.turn(Turn::Left), Direction::Down);
+        assert_eq!(Direction::Right.turn(Turn::Around), Direction::Left);
+    }
+
+    #[test]
+    fn test_walk() {
+        // follows the corridor, turning right at each wall, until it reaches the end
+        let grid = CharGrid::parse("S.#\n#.#\n#.E\n").unwrap();
+        let mut walker = Walker::new(grid.find('S').unwrap(), Direction::Right);
+        let mut path = vec![walker.pos];
+        while grid.get(walker.pos) != Some('E') {
+            if walker.peek(&grid).and_then(|next| grid.get(next)) == Some('#') {
+                walker.turn(Turn::Right);
+            }
+            if !walker.step(&grid) {
+                walker.turn(Turn::Left);
+                continue;
+            }
+            path.push(walker.pos);
+        }
+        assert_eq!(path.len(), 5);
+
+        // walking off of a bounded grid is refused, but a wrapping grid wraps around
+        let mut walker = Walker::new(Coord::new(0, 0), Direction::Up);
+        assert!(!walker.step(&grid));
+        assert!(walker.step(&WrappingGrid::<u8>::new(3, 3)));
+        assert_eq!(walker.pos, Coord::new(2, 0));
+    }
+}
@@ -0,0 +1,187 @@
+/*
+** src/types/wrapping_grid.rs
+*/
+
+use super::grid::{self, GridError};
+use super::Coord;
+
+// a grid on the surface of a torus, such that stepping off of one edge re-enters from the
+// opposite edge
+// note: unlike Array2D the dimensions are only known at runtime, as cyclic maps tend to be
+// irregularly sized
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WrappingGrid<T> {
+    width: usize,
+    height: usize,
+    // row-major
+    cells: Vec<T>,
+}
+
+impl<T> WrappingGrid<T> {
+    pub fn new(width: usize, height: usize) -> Self
+    where
+        T: Clone + Default,
+    {
+        let cells = vec![T::default(); width * height];
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // builds the grid from its rows, which must all be the same length
+    pub fn from_rows<I, R>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = T>,
+    {
+        let mut width = 0;
+        let mut height = 0;
+        let mut cells = vec![];
+        for row in rows {
+            cells.extend(row);
+            height += 1;
+            if height == 1 {
+                width = cells.len();
+            }
+            assert_eq!(cells.len(), width * height, "ragged row {}", height - 1);
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // parses the grid, inferring its dimensions
+    pub fn parse_chars<F>(input: &str, mapper: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let (width, height, cells) = grid::parse_chars(input, mapper)?;
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn parse_digits(input: &str) -> Result<Self, GridError>
+    where
+        T: From<u8>,
+    {
+        let (width, height, cells) = grid::parse_digits(input)?;
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // steps by the given signed offsets, wrapping around the edges
+    pub fn step(&self, coord: Coord, drow: isize, dcol: isize) -> Coord {
+        let row = (coord.row as isize + drow).rem_euclid(self.height as isize);
+        let col = (coord.col as isize + dcol).rem_euclid(self.width as isize);
+        Coord::new(row as usize, col as usize)
+    }
+
+    pub fn left(&self, coord: Coord) -> Coord {
+        self.step(coord, 0, -1)
+    }
+
+    pub fn right(&self, coord: Coord) -> Coord {
+        self.step(coord, 0, 1)
+    }
+
+    pub fn up(&self, coord: Coord) -> Coord {
+        self.step(coord, -1, 0)
+    }
+
+    pub fn down(&self, coord: Coord) -> Coord {
+        self.step(coord, 1, 0)
+    }
+
+    // note: on grids narrower than 3 cells a neighbor can be repeated or be the coord itself
+    pub fn neighbors(&self, coord: Coord) -> [Coord; 4] {
+        [
+            self.left(coord),
+            self.right(coord),
+            self.up(coord),
+            self.down(coord),
+        ]
+    }
+
+    pub fn neighbors_with_diagonal(&self, coord: Coord) -> [Coord; 8] {
+        [
+            self.left(coord),
+            self.right(coord),
+            self.up(coord),
+            self.down(coord),
+            self.step(coord, -1, -1),
+            self.step(coord, -1, 1),
+            self.step(coord, 1, -1),
+            self.step(coord, 1, 1),
+        ]
+    }
+
+    pub fn get(&self, coord: Coord) -> &T {
+        &self.cells[coord.to_index(self.width)]
+    }
+
+    pub fn set(&mut self, coord: Coord, val: T) {
+        self.cells[coord.to_index(self.width)] = val;
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        Coord::iter_rect(self.width, self.height)
+    }
+
+    pub fn iter_with_coords(&self) -> impl Iterator<Item = (Coord, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, val)| (Coord::from_index(index, width), val))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapping_neighbors() {
+        let grid = WrappingGrid::<u8>::new(4, 3);
+        let corner = Coord::new(0, 0);
+        assert_eq!(
+            grid.neighbors(corner),
+            [
+                Coord::new(0, 3),
+                Coord::new(0, 1),
+                Coord::new(2, 0),
+                Coord::new(1, 0)
+            ]
+        );
+        assert_eq!(grid.step(Coord::new(2, 3), 1, 1), corner);
+        assert_eq!(grid.step(corner, -7, 9), Coord::new(2, 1));
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let mut grid = WrappingGrid::from_rows(["ab".chars(), "cd".chars(), "ef".chars()]);
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(*grid.get(grid.up(Coord::new(0, 1))), 'f');
+        grid.set(Coord::new(1, 0), 'x');
+        let cells = grid.iter_with_coords().map(|(_, &c)| c).collect::<String>();
+        assert_eq!(cells, "abxdef");
+    }
+}
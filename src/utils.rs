@@ -2,8 +2,19 @@
 ** src/utils.rs
 */
 
+use crate::types;
+
+use num::{Integer, NumCast};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use std::any;
+use std::error;
+use std::fmt;
 use std::iter::Peekable;
+use std::ops::{Range, RangeInclusive};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 // a macro for a split-and-match pattern which is used frequently
 macro_rules! split {
@@ -12,31 +23,45 @@ macro_rules! split {
     };
 }
 
-// similar to the split! macro above, but binds the provided identifiers
-macro_rules! split_into {
-    ($string:expr, $splitter:expr, $($var:ident),+) => {
-        let ($($var),+) = match split!($string, $splitter) {
-            [$($var),+] => ($(*$var),+),
-            _ => unreachable!(),
-        };
-    };
-}
-
-// bind a variable to each Vec member when the vector is a known size
-macro_rules! bind_vec_deref {
-    ($vec:expr, $($var:ident),+) => {
-        let ($($var),+) = match $vec.as_slice() {
-            [$($var),+] => ($(*$var),+),
-            _ => unreachable!(),
-        };
-    };
+// times a named phase of a solution (parsing, searching, combining, ...) as a tracing span, such
+// that it shows up nested under the solve in the profiling output; evaluates to the block's value
+macro_rules! time_block {
+    ($name:literal, $body:expr) => {{
+        let _span = tracing::info_span!($name).entered();
+        $body
+    }};
 }
 
 // splits input into non-empty lines
-pub fn input_to_lines(input: &'static str) -> impl Iterator<Item = &str> {
+pub fn input_to_lines(input: &'static str) -> impl Iterator<Item = &'static str> {
     input.split('\n').filter(|s| !s.is_empty())
 }
 
+// groups lines into the sections separated by blank lines, dropping the blank lines
+pub fn sections<'a, I>(lines: I) -> Vec<Vec<&'a str>>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut sections = vec![vec![]];
+    for line in lines {
+        if line.is_empty() {
+            sections.push(vec![]);
+        } else {
+            sections.last_mut().unwrap().push(line);
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    sections
+}
+
+// a stable digest of a puzzle input, as a hex string
+pub fn input_digest(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 // splits input into non-empty lines, and parses a type from each line
 pub fn input_to_parsed_lines<T>(input: &'static str) -> impl Iterator<Item = T>
 where
@@ -46,6 +71,142 @@ where
     input_to_lines(input).map(|s| s.parse::<T>().unwrap())
 }
 
+// a numeric value which does not fit in the type it was cast to
+#[derive(Debug, PartialEq)]
+pub struct CastError {
+    value: String,
+    target: &'static str,
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} does not fit in {}", self.value, self.target)
+    }
+}
+
+impl error::Error for CastError {}
+
+// converts between numeric types, failing rather than wrapping or saturating when the value is
+// out of range for the target type
+// note: floats are truncated towards zero, as with an as cast, but NaN and infinities fail
+pub fn try_cast<T, U>(n: T) -> std::result::Result<U, CastError>
+where
+    T: Copy + NumCast + fmt::Display,
+    U: NumCast,
+{
+    num::cast(n).ok_or_else(|| CastError {
+        value: n.to_string(),
+        target: any::type_name::<U>(),
+    })
+}
+
+// as try_cast, but with the crate's boxed error for use with ? in solutions
+pub fn cast<T, U>(n: T) -> types::Result<U>
+where
+    T: Copy + NumCast + fmt::Display,
+    U: NumCast,
+{
+    Ok(try_cast(n)?)
+}
+
+// finds the first value in the range for which the predicate holds, given a predicate which is
+// false and then true across the range; returns the end of the range if it never holds
+pub fn binary_search_by<T, P>(range: Range<T>, predicate: P) -> T
+where
+    T: Copy + Integer,
+    P: Fn(T) -> bool,
+{
+    let two = T::one() + T::one();
+    let mut lo = range.start;
+    let mut hi = range.end;
+    while lo < hi {
+        // note: avoids overflowing on lo + hi
+        let mid = lo + ((hi - lo) / two);
+        if predicate(mid) {
+            hi = mid;
+        } else {
+            lo = mid + T::one();
+        }
+    }
+    lo
+}
+
+// which of the points satisfying its predicate search_2d finds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Scan {
+    // every point, in order
+    All,
+    // only the first point in order, stopping there
+    First,
+    // only the last point in order, searching from the end and stopping there
+    Last,
+}
+
+// brute-forces a grid of parameters for the points (x, y) satisfying the predicate, in order of x
+// and then of y, optionally searching each x in parallel; returns at most one point unless
+// scanning for all of them
+// note: the order of the points, and so which is first or last, does not depend on parallelism
+// note: reports each x searched as a trace event, to follow the progress of long searches
+pub fn search_2d<P>(
+    range_x: RangeInclusive<i64>,
+    range_y: RangeInclusive<i64>,
+    scan: Scan,
+    parallel: bool,
+    predicate: P,
+) -> Vec<(i64, i64)>
+where
+    P: Fn(i64, i64) -> bool + Sync,
+{
+    let _span = tracing::info_span!("search_2d", ?scan, parallel).entered();
+    let n_rows = range_x.clone().count();
+    let n_searched = AtomicUsize::new(0);
+    let progress = |x: i64| {
+        let searched = n_searched.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::trace!(x, searched, of = n_rows, "searched row");
+    };
+
+    let all = |x: i64| {
+        let points = range_y
+            .clone()
+            .filter(|&y| predicate(x, y))
+            .map(|y| (x, y))
+            .collect::<Vec<_>>();
+        progress(x);
+        points
+    };
+    let first = |x: i64| {
+        let point = range_y.clone().find(|&y| predicate(x, y)).map(|y| (x, y));
+        progress(x);
+        point
+    };
+    let last = |x: i64| {
+        let point = range_y
+            .clone()
+            .rev()
+            .find(|&y| predicate(x, y))
+            .map(|y| (x, y));
+        progress(x);
+        point
+    };
+
+    match (scan, parallel) {
+        (Scan::All, false) => range_x.flat_map(all).collect(),
+        (Scan::All, true) => range_x.into_par_iter().flat_map_iter(all).collect(),
+        (Scan::First, false) => range_x.into_iter().find_map(first).into_iter().collect(),
+        (Scan::First, true) => range_x
+            .into_par_iter()
+            .find_map_first(first)
+            .into_iter()
+            .collect(),
+        (Scan::Last, false) => range_x.rev().find_map(last).into_iter().collect(),
+        (Scan::Last, true) => range_x
+            .into_par_iter()
+            .find_map_last(last)
+            .into_iter()
+            .collect(),
+    }
+}
+
 // selects the other element in a 2-wide array
 pub fn other<T>(array: [T; 2], val: T) -> T
 where
@@ -127,3 +288,60 @@ where
     F: Fn(&'a N, &'a N) -> T,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let lines = "a\nb\n\nc\n\n\nd\n".lines();
+        assert_eq!(sections(lines), vec![vec!["a", "b"], vec!["c"], vec!["d"]]);
+        assert!(sections("".lines()).is_empty());
+    }
+
+    #[test]
+    fn test_binary_search_by() {
+        assert_eq!(binary_search_by(0..100, |n| n * n >= 50), 8);
+        assert_eq!(binary_search_by(-10..10i64, |n| n >= -10), -10);
+        assert_eq!(binary_search_by(0..10, |_| false), 10);
+        assert_eq!(binary_search_by(5..5, |_| true), 5);
+        assert_eq!(
+            binary_search_by(0..u64::MAX, |n| n > u64::MAX / 3),
+            u64::MAX / 3 + 1
+        );
+    }
+
+    #[test]
+    fn test_time_block() {
+        let n = time_block!("sum", (1..=4).sum::<u32>());
+        assert_eq!(n, 10);
+    }
+
+    #[test]
+    fn test_try_cast() {
+        assert_eq!(try_cast::<u64, u8>(255), Ok(255u8));
+        assert_eq!(
+            try_cast::<i64, usize>(-1).unwrap_err().to_string(),
+            "-1 does not fit in usize"
+        );
+        assert_eq!(try_cast::<f64, i64>(-2.5), Ok(-2));
+        assert!(try_cast::<f64, i64>(f64::NAN).is_err());
+        assert!(cast::<u32, u8>(256).is_err());
+    }
+
+    #[test]
+    fn test_search_2d() {
+        let on_circle = |x: i64, y: i64| x * x + y * y == 25;
+        for parallel in [false, true] {
+            let search = |scan| search_2d(-5..=5, 0..=5, scan, parallel, on_circle);
+            assert_eq!(
+                search(Scan::All),
+                vec![(-5, 0), (-4, 3), (-3, 4), (0, 5), (3, 4), (4, 3), (5, 0)]
+            );
+            assert_eq!(search(Scan::First), vec![(-5, 0)]);
+            assert_eq!(search(Scan::Last), vec![(5, 0)]);
+            assert!(search_2d(0..=2, 0..=2, Scan::First, parallel, on_circle).is_empty());
+        }
+    }
+}
@@ -0,0 +1,372 @@
+/*
+** src/validate.rs
+*/
+
+// checks that an input has the shape which a day's puzzle expects, so that malformed inputs are
+// reported as actionable errors rather than panics in the middle of parsing
+
+use crate::puzzles;
+
+pub type ValidationResult = std::result::Result<(), String>;
+
+// a validation failure, along with a version of its message which quotes nothing from the input
+struct Invalid {
+    message: String,
+    redacted: String,
+}
+
+impl From<String> for Invalid {
+    fn from(message: String) -> Self {
+        let redacted = message.clone();
+        Self { message, redacted }
+    }
+}
+
+type Check<T = ()> = std::result::Result<T, Invalid>;
+
+fn is_int(s: &str) -> bool {
+    s.parse::<i64>().is_ok()
+}
+
+fn is_int_list(s: &str, separator: char) -> bool {
+    !s.is_empty() && s.split(separator).all(is_int)
+}
+
+fn is_range(s: &str) -> bool {
+    match s.split_once("..") {
+        Some((min, max)) => is_int(min) && is_int(max),
+        None => false,
+    }
+}
+
+fn non_empty_lines(input: &str) -> Vec<&str> {
+    input.lines().filter(|line| !line.is_empty()).collect()
+}
+
+// every non-empty line must satisfy the predicate, where example describes a valid line
+fn expect_lines<F>(input: &str, example: &str, predicate: F) -> Check
+where
+    F: Fn(&str) -> bool,
+{
+    for (i, line) in input.lines().enumerate() {
+        if !line.is_empty() && !predicate(line) {
+            return Err(Invalid {
+                message: format!(
+                    "expects lines like \"{}\", got \"{}\" on line {}",
+                    example,
+                    line,
+                    i + 1
+                ),
+                redacted: format!(
+                    "expects lines like \"{}\", line {} does not match",
+                    example,
+                    i + 1
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn expect_line_count(input: &str, n: usize) -> Check {
+    let lines = non_empty_lines(input);
+    if lines.len() != n {
+        return Err(format!("expects {} lines, got {}", n, lines.len()).into());
+    }
+    Ok(())
+}
+
+fn expect_grid<F>(input: &str, width: usize, height: usize, kind: &str, valid: F) -> Check
+where
+    F: Fn(char) -> bool,
+{
+    let lines = non_empty_lines(input);
+    let shape_error = || {
+        let got_width = lines.first().map(|line| line.len()).unwrap_or(0);
+        format!(
+            "expects a {}x{} {} grid, got {} lines of {} characters",
+            width,
+            height,
+            kind,
+            lines.len(),
+            got_width
+        )
+    };
+    if lines.len() != height || lines.iter().any(|line| line.len() != width) {
+        return Err(shape_error().into());
+    }
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(c) = line.chars().find(|&c| !valid(c)) {
+            return Err(Invalid {
+                message: format!("expects a {} grid, got '{}' on line {}", kind, c, i + 1),
+                redacted: format!(
+                    "expects a {} grid, line {} has an invalid character",
+                    kind,
+                    i + 1
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn expect_sections(input: &str, n: Option<usize>) -> Check<Vec<&str>> {
+    let sections = input
+        .trim_end()
+        .split("\n\n")
+        .filter(|section| !section.trim().is_empty())
+        .collect::<Vec<_>>();
+    match n {
+        Some(n) if sections.len() != n => Err(format!(
+            "expects {} sections separated by blank lines, got {}",
+            n,
+            sections.len()
+        )
+        .into()),
+        _ => Ok(sections),
+    }
+}
+
+fn validate_bingo(input: &str) -> Check {
+    let sections = expect_sections(input, None)?;
+    expect_line_count(sections[0], 1)?;
+    expect_lines(sections[0], "7,4,9,5,11", |line| is_int_list(line, ','))?;
+    if sections.len() < 2 {
+        return Err(String::from("expects at least one bingo board").into());
+    }
+    for (i, board) in sections.iter().skip(1).enumerate() {
+        let rows = non_empty_lines(board);
+        let width = rows.first().map_or(0, |row| row.split_whitespace().count());
+        let valid = width > 0
+            && rows.iter().all(|row| {
+                let numbers = row.split_whitespace().collect::<Vec<_>>();
+                numbers.len() == width && numbers.iter().all(|n| is_int(n))
+            });
+        if !valid {
+            return Err(format!(
+                "expects rectangular bingo boards, board {} is malformed",
+                i + 1
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+fn validate_scanners(input: &str) -> Check {
+    for (i, section) in expect_sections(input, None)?.iter().enumerate() {
+        let header = format!("--- scanner {} ---", i);
+        let mut lines = section.lines();
+        if lines.next() != Some(header.as_str()) {
+            return Err(format!("expects scanner {} to begin with \"{}\"", i, header).into());
+        }
+        let beacons = lines.collect::<Vec<_>>().join("\n");
+        expect_lines(&beacons, "404,-588,-901", |line| {
+            line.split(',').count() == 3 && is_int_list(line, ',')
+        })?;
+    }
+    Ok(())
+}
+
+fn validate_image(input: &str) -> Check {
+    let sections = expect_sections(input, Some(2))?;
+    let algorithm = sections[0].trim();
+    if algorithm.len() != 512 {
+        return Err(format!(
+            "expects a 512-character enhancement algorithm, got {} characters",
+            algorithm.len()
+        )
+        .into());
+    }
+    let is_pixel = |c| c == '.' || c == '#';
+    if !algorithm.chars().all(is_pixel) {
+        return Err(
+            String::from("expects the enhancement algorithm to contain only '.' and '#'").into(),
+        );
+    }
+    let rows = non_empty_lines(sections[1]);
+    let width = rows.first().map(|row| row.len()).unwrap_or(0);
+    expect_grid(sections[1], width, rows.len(), "pixel", is_pixel)
+}
+
+fn validate_day(day: usize, input: &str) -> Check {
+    match day {
+        1 => expect_lines(input, "199", is_int),
+        2 => expect_lines(input, "forward 5", |line| match line.split_once(' ') {
+            Some((direction, n)) => ["forward", "down", "up"].contains(&direction) && is_int(n),
+            None => false,
+        }),
+        3 => expect_lines(input, "011000011101", |line| {
+            line.len() == 12 && line.chars().all(|c| c == '0' || c == '1')
+        }),
+        4 => validate_bingo(input),
+        5 => expect_lines(input, "0,9 -> 5,9", |line| match line.split_once(" -> ") {
+            Some((a, b)) => a.split(',').count() == 2 && is_int_list(a, ',') && is_int_list(b, ','),
+            None => false,
+        }),
+        6 | 7 | 16 | 17 => {
+            expect_line_count(input, 1)?;
+            match day {
+                6 | 7 => expect_lines(input, "3,4,3,1,2", |line| is_int_list(line, ',')),
+                16 => expect_lines(input, "8A004A801A8002F478", |line| {
+                    line.chars().all(|c| c.is_ascii_hexdigit())
+                }),
+                _ => expect_lines(input, "target area: x=20..30, y=-10..-5", |line| match line
+                    .strip_prefix("target area: x=")
+                {
+                    Some(ranges) => match ranges.split_once(", y=") {
+                        Some((x, y)) => is_range(x) && is_range(y),
+                        None => false,
+                    },
+                    None => false,
+                }),
+            }
+        }
+        8 => expect_lines(
+            input,
+            "acedgfb cdfbe ... | cdfeb fcadb cdfeb cdbaf",
+            |line| match line.split_once(" | ") {
+                Some((patterns, digits)) => {
+                    patterns.split(' ').count() == 10 && digits.split(' ').count() == 4
+                }
+                None => false,
+            },
+        ),
+        9 | 15 => expect_grid(input, 100, 100, "digit", |c| c.is_ascii_digit()),
+        10 => expect_lines(input, "[({(<(())[]>[[{[]{<()<>>", |line| {
+            line.chars().all(|c| "()[]{}<>".contains(c))
+        }),
+        11 => expect_grid(input, 10, 10, "digit", |c| c.is_ascii_digit()),
+        12 => expect_lines(input, "start-A", |line| match line.split_once('-') {
+            Some((a, b)) => {
+                let is_cave = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphabetic());
+                is_cave(a) && is_cave(b)
+            }
+            None => false,
+        }),
+        13 => {
+            let sections = expect_sections(input, Some(2))?;
+            expect_lines(sections[0], "6,10", |line| {
+                line.split(',').count() == 2 && is_int_list(line, ',')
+            })?;
+            expect_lines(sections[1], "fold along y=7", |line| {
+                match line.strip_prefix("fold along ") {
+                    Some(fold) => match fold.split_once('=') {
+                        Some((axis, n)) => (axis == "x" || axis == "y") && is_int(n),
+                        None => false,
+                    },
+                    None => false,
+                }
+            })
+        }
+        14 => {
+            let sections = expect_sections(input, Some(2))?;
+            expect_line_count(sections[0], 1)?;
+            expect_lines(sections[0], "NNCB", |line| {
+                line.chars().all(|c| c.is_ascii_uppercase())
+            })?;
+            expect_lines(sections[1], "CH -> B", |line| {
+                match line.split_once(" -> ") {
+                    Some((pair, element)) => pair.len() == 2 && element.len() == 1,
+                    None => false,
+                }
+            })
+        }
+        18 => expect_lines(input, "[[1,2],3]", |line| {
+            line.starts_with('[')
+                && line
+                    .chars()
+                    .all(|c| "[],".contains(c) || c.is_ascii_digit())
+        }),
+        19 => validate_scanners(input),
+        20 => validate_image(input),
+        21 => {
+            expect_line_count(input, 2)?;
+            expect_lines(input, "Player 1 starting position: 4", |line| {
+                line.starts_with("Player ")
+                    && line
+                        .split_once("starting position: ")
+                        .map(|(_, position)| is_int(position))
+                        .unwrap_or(false)
+            })
+        }
+        22 => expect_lines(input, "on x=10..12,y=10..12,z=10..12", |line| {
+            match line.split_once(' ') {
+                Some((instr, cuboid)) => {
+                    let ranges = cuboid.split(',').collect::<Vec<_>>();
+                    (instr == "on" || instr == "off")
+                        && ranges.len() == 3
+                        && ranges.iter().zip(["x=", "y=", "z="]).all(|(range, axis)| {
+                            range.strip_prefix(axis).map(is_range).unwrap_or(false)
+                        })
+                }
+                None => false,
+            }
+        }),
+        // note: registered puzzles bring no input spec, so their inputs are taken as they are
+        day if puzzles::is_registered(day) => Ok(()),
+        _ => Err(String::from("is not a known puzzle").into()),
+    }
+}
+
+// validates the input for the given day, prefixing errors with the day
+fn check(day: usize, input: &str) -> Check {
+    if input.trim().is_empty() {
+        return Err(format!("day {} input is empty", day).into());
+    }
+    validate_day(day, input).map_err(|err| Invalid {
+        message: format!("day {} {}", day, err.message),
+        redacted: format!("day {} {}", day, err.redacted),
+    })
+}
+
+pub fn validate(day: usize, input: &str) -> ValidationResult {
+    check(day, input).map_err(|err| err.message)
+}
+
+// as validate, but errors never quote the input, so that they can be shared without publishing it
+pub fn validate_redacted(day: usize, input: &str) -> ValidationResult {
+    check(day, input).map_err(|err| err.redacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate(2, "forward 5\ndown 5\nup 3\n"), Ok(()));
+        assert_eq!(
+            validate(2, "forward 5\nbackward 5\n"),
+            Err(String::from(
+                "day 2 expects lines like \"forward 5\", got \"backward 5\" on line 2"
+            ))
+        );
+        assert_eq!(
+            validate_redacted(2, "forward 5\nbackward 5\n"),
+            Err(String::from(
+                "day 2 expects lines like \"forward 5\", line 2 does not match"
+            ))
+        );
+        assert_eq!(validate(1, "\n"), Err(String::from("day 1 input is empty")));
+        assert_eq!(
+            validate(9, &"1234567890\n".repeat(40)),
+            Err(String::from(
+                "day 9 expects a 100x100 digit grid, got 40 lines of 10 characters"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_examples() {
+        let examples = [
+            (19, include_str!("../input/19.dbg.txt")),
+            (20, include_str!("../input/20.dbg.txt")),
+            (21, include_str!("../input/21.dbg.txt")),
+            (22, include_str!("../input/22.dbg.txt")),
+        ];
+        for (day, input) in examples {
+            assert_eq!(validate(day, input), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,193 @@
+/*
+** src/variants.rs
+*/
+
+// days with a naive and a fast implementation of their solutions, either of which can be chosen
+// by name in place of the puzzle's own choice, to time or check them one against the other
+// note: fast is whichever measured faster on the real inputs, not whichever is cleverer
+
+use crate::puzzles::day_21::GameRules;
+use crate::puzzles::{self, day_15, day_21, day_22, day_5};
+use crate::types::{Puzzle, Result, Solution};
+
+pub const NAMES: [&str; 2] = ["naive", "fast"];
+
+pub struct Variant {
+    pub day: usize,
+    pub name: &'static str,
+    // the implementations used by each part
+    pub description: &'static str,
+    pub new: fn(&'static str) -> Result<Box<dyn Puzzle>>,
+}
+
+// a puzzle whose parts are solved by the given functions
+struct Choice<P> {
+    puzzle: P,
+    part_1: fn(&P) -> Result<Solution>,
+    part_2: fn(&P) -> Result<Solution>,
+}
+
+impl<P> Puzzle for Choice<P> {
+    fn part_1(&self) -> Result<Solution> {
+        (self.part_1)(&self.puzzle)
+    }
+
+    fn part_2(&self) -> Result<Solution> {
+        (self.part_2)(&self.puzzle)
+    }
+}
+
+pub const VARIANTS: [Variant; 8] = [
+    Variant {
+        day: 5,
+        name: "fast",
+        description: "pairwise intersections",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_5::Day5::new(input)?,
+                part_1: |day| Ok(day.overlaps(false).into()),
+                part_2: |day| Ok(day.overlaps(true).into()),
+            }))
+        },
+    },
+    Variant {
+        day: 5,
+        name: "naive",
+        description: "point counter",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_5::Day5::new(input)?,
+                part_1: |day| Ok(day.overlaps_counted(false).into()),
+                part_2: |day| Ok(day.overlaps_counted(true).into()),
+            }))
+        },
+    },
+    Variant {
+        day: 15,
+        name: "naive",
+        description: "priority map",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_15::Day15::new(input)?,
+                part_1: |day| Ok(day.lowest_risk(false).into()),
+                part_2: |day| Ok(day.lowest_risk(true).into()),
+            }))
+        },
+    },
+    Variant {
+        day: 15,
+        name: "fast",
+        description: "binary heap with lazy deletion",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_15::Day15::new(input)?,
+                part_1: |day| Ok(day.lowest_risk_by_heap(false).into()),
+                part_2: |day| Ok(day.lowest_risk_by_heap(true).into()),
+            }))
+        },
+    },
+    Variant {
+        day: 21,
+        name: "naive",
+        description: "recursive",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_21::Day21::new(input)?,
+                part_1: |day| Ok(day.play_game_deterministic(&GameRules::PRACTICE).into()),
+                part_2: |day| Ok(day.play_game_dirac(&GameRules::DIRAC).into()),
+            }))
+        },
+    },
+    Variant {
+        day: 21,
+        name: "fast",
+        description: "memoized",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_21::Day21::new(input)?,
+                part_1: |day| Ok(day.play_game_deterministic(&GameRules::PRACTICE).into()),
+                part_2: |day| Ok(day.play_game_dirac_memoized(&GameRules::DIRAC).into()),
+            }))
+        },
+    },
+    Variant {
+        day: 22,
+        name: "naive",
+        description: "clipped inclusion-exclusion",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_22::Day22::new(input)?,
+                part_1: |day| Ok(day.execute_initialization_by_volume().into()),
+                part_2: |day| Ok(day.execute_procedure().into()),
+            }))
+        },
+    },
+    Variant {
+        day: 22,
+        name: "fast",
+        description: "bit cube, then splitting",
+        new: |input| {
+            Ok(Box::new(Choice {
+                puzzle: day_22::Day22::new(input)?,
+                part_1: |day| Ok(day.execute_initialization().into()),
+                part_2: |day| Ok(day.execute_procedure_by_splitting().into()),
+            }))
+        },
+    },
+];
+
+pub fn variants(day: usize) -> impl Iterator<Item = &'static Variant> {
+    VARIANTS.iter().filter(move |variant| variant.day == day)
+}
+
+pub fn variant(day: usize, name: &str) -> Option<&'static Variant> {
+    variants(day).find(|variant| variant.name == name)
+}
+
+// the puzzle for the given day, solved by the named implementation if the day has it, and
+// otherwise by the puzzle's own
+pub fn new(day: usize, name: Option<&str>, input: &'static str) -> Option<Result<Box<dyn Puzzle>>> {
+    match name.and_then(|name| variant(day, name)) {
+        Some(variant) => {
+            let _span = tracing::info_span!("parse", day).entered();
+            Some((variant.new)(input))
+        }
+        None => puzzles::from_lines(day, input.lines()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs;
+
+    #[test]
+    fn test_variants_agree() {
+        for day in [5, 15, 21, 22] {
+            assert_eq!(variants(day).count(), NAMES.len());
+            // note: the puzzle inputs are optional, so missing inputs are skipped
+            let input = match inputs::load(day) {
+                Ok(input) => input,
+                Err(_) => continue,
+            };
+            let puzzles = variants(day)
+                .map(|variant| (variant.new)(input).unwrap())
+                .collect::<Vec<_>>();
+            for (part, solve) in [(1, Puzzle::part_1 as fn(&_) -> _), (2, Puzzle::part_2)] {
+                // note: the recursive day 21 takes many seconds in debug builds, and is already
+                // checked against the memoized one by its differential
+                if (day, part) == (21, 2) {
+                    continue;
+                }
+                let answers = puzzles
+                    .iter()
+                    .map(|puzzle| solve(puzzle.as_ref()).unwrap().to_string())
+                    .collect::<Vec<_>>();
+                assert!(answers.iter().all(|answer| *answer == answers[0]));
+            }
+        }
+        assert!(variant(1, "fast").is_none());
+        assert!(new(5, Some("fast"), "0,9 -> 5").unwrap().is_err());
+        assert!(new(2, Some("fast"), "sideways 3").unwrap().is_err());
+    }
+}
@@ -0,0 +1,217 @@
+/*
+** src/verify.rs
+*/
+
+use aoc2021::inputs;
+use aoc2021::puzzles;
+use aoc2021::types::Puzzle;
+use aoc2021::validate;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::process;
+
+// the expected answers to the real inputs, e.g.
+//     [day1]
+//     part1 = 1521
+//     part2 = "1543"
+// note: only this subset of TOML is understood, which is all that the answers need
+const ANSWERS_FILE: &str = "answers.toml";
+
+// the example inputs which are stored alongside the real ones, with the answers given in the
+// puzzle descriptions
+const EXAMPLES: [(usize, &str, [&str; 2]); 4] = [
+    (19, include_str!("../input/19.dbg.txt"), ["79", "3621"]),
+    (20, include_str!("../input/20.dbg.txt"), ["35", "3351"]),
+    (
+        21,
+        include_str!("../input/21.dbg.txt"),
+        ["739785", "444356092776315"],
+    ),
+    (
+        22,
+        include_str!("../input/22.dbg.txt"),
+        ["474140", "2758514936282235"],
+    ),
+];
+
+pub type Answers = HashMap<(usize, usize), String>;
+
+fn parse_answers(contents: &str) -> Result<Answers, String> {
+    let mut answers = HashMap::new();
+    let mut day = None;
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || format!("{} line {}: cannot parse {:?}", ANSWERS_FILE, i + 1, line);
+        if let Some(table) = line.strip_prefix("[day").and_then(|s| s.strip_suffix(']')) {
+            day = Some(table.parse::<usize>().map_err(|_| invalid())?);
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        let part = key
+            .trim()
+            .strip_prefix("part")
+            .and_then(|part| part.parse::<usize>().ok())
+            .filter(|part| (1..=2).contains(part))
+            .ok_or_else(invalid)?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        answers.insert((day.ok_or_else(invalid)?, part), value.to_string());
+    }
+    Ok(answers)
+}
+
+pub fn load_answers() -> Result<Answers, String> {
+    match fs::read_to_string(ANSWERS_FILE) {
+        Ok(contents) => parse_answers(&contents),
+        // note: without the file the real inputs are still solved, but nothing is checked
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(err) => Err(format!("failed to read {}: {}", ANSWERS_FILE, err)),
+    }
+}
+
+// describes an answer which deviates from the recorded one, in the style of a diff
+fn deviation(day: usize, part: usize, expected: &str, answer: &str) -> String {
+    format!(
+        "day {:02} part {} deviates from {}\n- {}\n+ {}",
+        day, part, ANSWERS_FILE, expected, answer
+    )
+}
+
+// exits the moment an answer deviates from the recorded one, if there is one
+pub fn assert_answer(answers: &Answers, day: usize, part: usize, answer: &str) {
+    if let Some(expected) = answers.get(&(day, part)) {
+        if expected != answer {
+            eprintln!("{}", deviation(day, part, expected, answer));
+            process::exit(1);
+        }
+    }
+}
+
+// the outcome of a single part against a single input
+enum Check {
+    Pass,
+    Fail(String),
+    // solved, but there is no expected answer to check against
+    Unchecked,
+    // there is no input to solve
+    Missing,
+}
+
+impl Check {
+    fn cell(&self) -> String {
+        match self {
+            Self::Pass => "ok".into(),
+            Self::Fail(reason) => format!("FAIL: {}", reason.replace('|', "\\|")),
+            Self::Unchecked => "unchecked".into(),
+            Self::Missing => "-".into(),
+        }
+    }
+}
+
+fn check(puzzle: &dyn Puzzle, part: usize, expected: Option<&str>) -> Check {
+    let answer = match part {
+        1 => puzzle.part_1(),
+        _ => puzzle.part_2(),
+    };
+    match (answer, expected) {
+        (Err(err), _) => Check::Fail(err.to_string()),
+        (Ok(_), None) => Check::Unchecked,
+        (Ok(answer), Some(expected)) if answer.to_string() == expected => Check::Pass,
+        (Ok(answer), Some(expected)) => {
+            Check::Fail(format!("got {}, expected {}", answer, expected))
+        }
+    }
+}
+
+fn check_input(day: usize, input: &'static str, expected: [Option<&str>; 2]) -> [Check; 2] {
+    // validate first, since puzzles panic on inputs of the wrong shape
+    if let Err(err) = validate::validate(day, input) {
+        return [Check::Fail(err.clone()), Check::Fail(err)];
+    }
+    let puzzle = match puzzles::from_lines(day, input.lines()).unwrap() {
+        Ok(puzzle) => puzzle,
+        Err(err) => return [Check::Fail(err.to_string()), Check::Fail(err.to_string())],
+    };
+    [
+        check(puzzle.as_ref(), 1, expected[0]),
+        check(puzzle.as_ref(), 2, expected[1]),
+    ]
+}
+
+// runs every day against its example input, if there is one, and its real input, printing a
+// markdown table of the outcomes and exiting with an error if any part fails
+pub fn verify_all() {
+    let answers = load_answers().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    let mut failed = false;
+    println!("| day | example 1 | example 2 | input 1 | input 2 |");
+    println!("| --: | --------- | --------- | ------- | ------- |");
+    for day in puzzles::days() {
+        let example = match EXAMPLES.iter().find(|(d, _, _)| *d == day) {
+            Some(&(_, input, [a, b])) => check_input(day, input, [Some(a), Some(b)]),
+            None => [Check::Missing, Check::Missing],
+        };
+        let real = match inputs::load(day) {
+            Ok(input) => {
+                let expected = [1, 2].map(|part| answers.get(&(day, part)).map(|s| s.as_str()));
+                check_input(day, input, expected)
+            }
+            Err(_) => [Check::Missing, Check::Missing],
+        };
+
+        let cells = example.iter().chain(real.iter()).collect::<Vec<_>>();
+        failed |= cells.iter().any(|check| matches!(check, Check::Fail(_)));
+        println!(
+            "| {} | {} |",
+            day,
+            cells
+                .iter()
+                .map(|check| check.cell())
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let contents =
+            "# my answers\n[day1]\npart1 = 1521\npart2 = \"1543\"\n\n[day12]\npart2 = \"x\"\n";
+        let answers = parse_answers(contents).unwrap();
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers[&(1, 1)], "1521");
+        assert_eq!(answers[&(1, 2)], "1543");
+        assert_eq!(answers[&(12, 2)], "x");
+
+        assert!(parse_answers("part1 = 3\n").is_err());
+        assert!(parse_answers("[day1]\npart3 = 3\n").is_err());
+        assert!(parse_answers("[dayone]\n").is_err());
+    }
+
+    #[test]
+    fn test_deviation() {
+        assert_eq!(
+            deviation(3, 2, "1543", "1544"),
+            "day 03 part 2 deviates from answers.toml\n- 1543\n+ 1544"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+/*
+** src/viz/ascii.rs
+*/
+
+use super::Frame;
+
+// characters in order of increasing intensity
+const RAMP: &[u8] = b" .:-=+*#%@";
+// the character of highlighted cells, whatever their intensity
+const HIGHLIGHT: char = 'o';
+
+pub fn render_ascii(frame: &Frame) -> String {
+    frame
+        .rows()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, &cell)| match frame.is_highlighted(i, j) {
+                    true => HIGHLIGHT,
+                    false => RAMP[(cell as usize * (RAMP.len() - 1)) / 255] as char,
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
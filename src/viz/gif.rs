@@ -0,0 +1,50 @@
+/*
+** src/viz/gif.rs
+*/
+
+use super::Frame;
+use crate::types::{Coord, Result};
+
+use gif::{Encoder, Repeat};
+
+use std::io::Write;
+
+// size of each cell, in pixels
+const CELL_SIZE: usize = 4;
+
+// encodes the frames as a looping animation, with the given delay between frames
+pub fn render_gif<W>(frames: &[Frame], delay_ms: u16, writer: W) -> Result<()>
+where
+    W: Write,
+{
+    // frames may shrink or grow, so the animation is sized to fit the largest of them
+    let width = frames.iter().map(|f| f.width).max().unwrap_or(0) * CELL_SIZE;
+    let height = frames.iter().map(|f| f.height).max().unwrap_or(0) * CELL_SIZE;
+    let width = u16::try_from(width).map_err(|_| "frames are too wide for a GIF")?;
+    let height = u16::try_from(height).map_err(|_| "frames are too tall for a GIF")?;
+
+    // a grayscale palette, such that each cell intensity is its own palette index
+    let palette = (0..=255).flat_map(|i| [i, i, i]).collect::<Vec<u8>>();
+    let mut encoder = Encoder::new(writer, width, height, &palette)?;
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for frame in frames.iter() {
+        let mut buffer = vec![0; width as usize * height as usize];
+        for (i, row) in frame.rows().enumerate() {
+            for (j, &cell) in row.iter().enumerate() {
+                for offset in Coord::iter_rect(CELL_SIZE, CELL_SIZE) {
+                    let y = (i * CELL_SIZE) + offset.row;
+                    let x = (j * CELL_SIZE) + offset.col;
+                    buffer[(y * width as usize) + x] = cell;
+                }
+            }
+        }
+
+        let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, &buffer, None);
+        // note: GIF delays are in units of 10ms
+        gif_frame.delay = delay_ms / 10;
+        encoder.write_frame(&gif_frame)?;
+    }
+
+    Ok(())
+}
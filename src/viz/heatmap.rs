@@ -0,0 +1,79 @@
+/*
+** src/viz/heatmap.rs
+*/
+
+// frames of values scaled to intensities, from sparse counts or dense grids, and the color ramp
+// which they are drawn with
+
+use super::Frame;
+use crate::types::{Array2D, Counter, Point};
+
+// the colors at evenly spaced intensities, from the lowest to the highest value
+const RAMP: [(u8, u8, u8); 5] = [
+    (0, 0, 0),
+    (96, 16, 128),
+    (224, 48, 48),
+    (255, 192, 0),
+    (255, 255, 255),
+];
+
+// the color of the given intensity, interpolated between the stops of the ramp
+pub fn heat_color(intensity: u8) -> (u8, u8, u8) {
+    let position = intensity as usize * (RAMP.len() - 1);
+    let (stop, offset) = (position / 255, position % 255);
+    if stop == RAMP.len() - 1 {
+        return RAMP[stop];
+    }
+    let lerp = |a: u8, b: u8| ((a as usize * (255 - offset)) + (b as usize * offset)) / 255;
+    let ((r0, g0, b0), (r1, g1, b1)) = (RAMP[stop], RAMP[stop + 1]);
+    (lerp(r0, r1) as u8, lerp(g0, g1) as u8, lerp(b0, b1) as u8)
+}
+
+// a frame spanning the given points, where each point is scaled against the highest value so
+// that it is the brightest, and points without a value are empty
+pub fn heatmap<I>(values: I) -> Frame
+where
+    I: IntoIterator<Item = (Point, u64)>,
+{
+    let values = values.into_iter().collect::<Vec<_>>();
+    let (x_min, x_max) = match (
+        values.iter().map(|(p, _)| p.x).min(),
+        values.iter().map(|(p, _)| p.x).max(),
+    ) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return Frame::new(0, 0),
+    };
+    let y_min = values.iter().map(|(p, _)| p.y).min().unwrap();
+    let y_max = values.iter().map(|(p, _)| p.y).max().unwrap();
+    let max = values.iter().map(|&(_, value)| value).max().unwrap().max(1);
+
+    let mut frame = Frame::new((x_max - x_min + 1) as usize, (y_max - y_min + 1) as usize);
+    for (p, value) in values {
+        let intensity = (value * 255) / max;
+        frame.set(
+            (p.y - y_min) as usize,
+            (p.x - x_min) as usize,
+            intensity as u8,
+        );
+    }
+    frame
+}
+
+// a heatmap of how often each point was counted
+pub fn from_counter(counter: &Counter<Point>) -> Frame {
+    heatmap(counter.iter().map(|(p, &count)| (p.clone(), count as u64)))
+}
+
+// a heatmap of the cells of a grid
+pub fn from_array<const W: usize, const H: usize>(array: &Array2D<u8, W, H>) -> Frame {
+    heatmap(
+        array
+            .iter_with_coords()
+            .map(|(coord, &value)| (Point::new(coord.col as i64, coord.row as i64), value as u64)),
+    )
+}
+
+// puzzles with values spread over a grid can be drawn as a single heatmap of them
+pub trait Heatmap {
+    fn heatmap(&self) -> Frame;
+}
@@ -0,0 +1,204 @@
+/*
+** src/viz/mod.rs
+*/
+
+mod ascii;
+mod gif;
+pub mod heatmap;
+mod svg;
+
+pub use self::ascii::render_ascii;
+pub use self::gif::render_gif;
+pub use self::heatmap::Heatmap;
+pub use self::svg::{render_heatmap_svg, render_lines, render_svg, OVERLAP_COLORS, PATH_COLOR};
+
+use crate::puzzles::{day_11, day_13, day_15, day_20, day_5, day_9};
+use crate::types::{Coord, Result};
+
+// a single image of a puzzle's state, as a grid of cell intensities
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    // row-major, where 0 is empty and 255 is the brightest
+    pub cells: Vec<u8>,
+    // row-major, cells drawn apart from their intensity, such as a path through the grid
+    // note: empty if nothing is highlighted
+    pub highlighted: Vec<bool>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        let cells = vec![0; width * height];
+        Self {
+            width,
+            height,
+            cells,
+            highlighted: vec![],
+        }
+    }
+
+    pub fn from_fn<F>(width: usize, height: usize, f: F) -> Self
+    where
+        F: Fn(usize, usize) -> u8,
+    {
+        let cells = Coord::iter_rect(width, height)
+            .map(|coord| f(coord.row, coord.col))
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+            highlighted: vec![],
+        }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> u8 {
+        self.cells[(i * self.width) + j]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, intensity: u8) {
+        self.cells[(i * self.width) + j] = intensity;
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn is_highlighted(&self, i: usize, j: usize) -> bool {
+        self.highlighted
+            .get((i * self.width) + j)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    // highlights the cells along a path, on top of their intensities
+    pub fn with_path<I>(mut self, path: I) -> Self
+    where
+        I: IntoIterator<Item = Coord>,
+    {
+        self.highlighted.resize(self.cells.len(), false);
+        for coord in path {
+            self.highlighted[(coord.row * self.width) + coord.col] = true;
+        }
+        self
+    }
+}
+
+// puzzles with a natural visual output produce a sequence of frames, starting from their input
+pub trait Visualize {
+    fn frames(&self) -> impl Iterator<Item = Frame>;
+}
+
+// constructs the given day's puzzle and collects its frames, if it can be visualized
+pub fn frames(day: usize, input: &'static str) -> Option<Result<Vec<Frame>>> {
+    let frames = match day {
+        9 => day_9::Day9::new(input).map(|day| day.frames().collect()),
+        11 => day_11::Day11::new(input).map(|day| day.frames().collect()),
+        13 => day_13::Day13::new(input).map(|day| day.frames().collect()),
+        15 => day_15::Day15::new(input).map(|day| day.frames().collect()),
+        20 => day_20::Day20::new(input).map(|day| day.frames().collect()),
+        _ => return None,
+    };
+    Some(frames)
+}
+
+// constructs the given day's puzzle and collects its heatmap, if it has one
+pub fn heatmap(day: usize, input: &'static str) -> Option<Result<Frame>> {
+    let frame = match day {
+        5 => day_5::Day5::new(input).map(|day| day.heatmap()),
+        9 => day_9::Day9::new(input).map(|day| day.heatmap()),
+        15 => day_15::Day15::new(input).map(|day| day.heatmap()),
+        _ => return None,
+    };
+    Some(frame)
+}
+
+// constructs the given day's puzzle and draws it as a single svg, if its state is better drawn as
+// shapes than as a grid of cells
+pub fn drawing(day: usize, input: &'static str) -> Option<Result<String>> {
+    match day {
+        5 => Some(day_5::Day5::new(input).map(|day| {
+            let overlaps = day.overlap_counts(true).into_iter().collect::<Vec<_>>();
+            render_lines(&day.lines(true), &overlaps)
+        })),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let frame = Frame::from_fn(3, 2, |i, j| ((i * 3) + j) as u8 * 51);
+        assert_eq!(render_ascii(&frame), " .-\n+#@");
+
+        let svg = render_svg(&frame);
+        assert!(svg.starts_with("<svg"));
+        // the empty cell is left as the background
+        assert_eq!(svg.matches("<rect x=").count(), 5);
+    }
+
+    #[test]
+    fn test_render_path() {
+        let frame = Frame::from_fn(3, 2, |i, j| ((i * 3) + j) as u8 * 51).with_path([
+            Coord::new(0, 0),
+            Coord::new(1, 0),
+            Coord::new(1, 1),
+        ]);
+        assert!(frame.is_highlighted(1, 1) && !frame.is_highlighted(0, 1));
+        assert_eq!(render_ascii(&frame), "o.-\noo@");
+
+        // the path is drawn over the background, even across empty cells
+        let svg = render_svg(&frame);
+        assert_eq!(svg.matches("<rect x=").count(), 6);
+        assert_eq!(svg.matches(PATH_COLOR).count(), 3);
+    }
+
+    #[test]
+    fn test_render_lines() {
+        let input = "0,9 -> 5,9\n8,0 -> 0,8\n9,4 -> 3,4\n2,2 -> 2,1\n7,0 -> 7,4\n6,4 -> 2,0\n\
+                     0,9 -> 2,9\n3,4 -> 1,4\n0,0 -> 8,8\n5,5 -> 8,2";
+        let svg = drawing(5, input).unwrap().unwrap();
+        assert!(svg.contains("viewBox=\"-1 -1 11 11\""));
+        assert_eq!(svg.matches("<line ").count(), 10);
+        // of the 12 points covered by at least two lines, 2 are covered by three
+        assert_eq!(svg.matches(OVERLAP_COLORS[0]).count(), 10);
+        assert_eq!(svg.matches(OVERLAP_COLORS[1]).count(), 2);
+        assert_eq!(svg.matches(OVERLAP_COLORS[2]).count(), 0);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        use crate::types::{Counter, Point};
+
+        let counter = Counter::from(
+            [(2, 5), (3, 5), (3, 5), (4, 7)]
+                .into_iter()
+                .map(|(x, y)| Point::new(x, y)),
+        );
+        let frame = heatmap::from_counter(&counter);
+        // spans the counted points, with the most counted the brightest
+        assert_eq!((frame.width, frame.height), (3, 3));
+        assert_eq!(frame.cells, vec![127, 255, 0, 0, 0, 0, 0, 0, 127]);
+        assert_eq!(heatmap::heat_color(0), (0, 0, 0));
+        assert_eq!(heatmap::heat_color(255), (255, 255, 255));
+
+        let svg = render_heatmap_svg(&frame);
+        assert_eq!(svg.matches("<rect x=").count(), 3);
+        assert!(svg.contains("fill=\"rgb(255,255,255)\""));
+        assert_eq!(heatmap::heatmap(vec![]).cells.len(), 0);
+    }
+
+    #[test]
+    fn test_render_gif() {
+        let frames = vec![Frame::new(2, 2), Frame::from_fn(3, 1, |_, j| j as u8)];
+        let mut gif = vec![];
+        render_gif(&frames, 100, &mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        // the logical screen is sized to fit the largest frame
+        assert_eq!(&gif[6..10], &[12, 0, 8, 0]);
+    }
+}
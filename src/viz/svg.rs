@@ -0,0 +1,109 @@
+/*
+** src/viz/svg.rs
+*/
+
+use super::{heatmap, Frame};
+use crate::types::{Line, Point};
+
+use std::fmt::Write;
+
+// size of each cell, in pixels
+const CELL_SIZE: usize = 8;
+// the fill of highlighted cells, whatever their intensity
+pub const PATH_COLOR: &str = "rgb(255,64,64)";
+// the stroke of line segments
+const LINE_COLOR: &str = "rgb(96,160,255)";
+// the fills of points covered by 2, 3, and 4 or more segments
+pub const OVERLAP_COLORS: [&str; 3] = ["rgb(255,220,64)", "rgb(255,140,0)", "rgb(255,32,32)"];
+
+// draws each cell filled by the color of its intensity
+fn render_cells<F>(frame: &Frame, fill: F) -> String
+where
+    F: Fn(u8) -> String,
+{
+    let width = frame.width * CELL_SIZE;
+    let height = frame.height * CELL_SIZE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    svg += "<rect width=\"100%\" height=\"100%\" fill=\"black\"/>\n";
+
+    // note: empty cells are left as the background, unless highlighted
+    for (i, row) in frame.rows().enumerate() {
+        for (j, &cell) in row.iter().enumerate() {
+            let fill = match frame.is_highlighted(i, j) {
+                true => PATH_COLOR.to_string(),
+                false if cell > 0 => fill(cell),
+                false => continue,
+            };
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                j * CELL_SIZE,
+                i * CELL_SIZE,
+                CELL_SIZE,
+                CELL_SIZE,
+                fill
+            );
+        }
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+pub fn render_svg(frame: &Frame) -> String {
+    render_cells(frame, |cell| format!("rgb({},{},{})", cell, cell, cell))
+}
+
+// as render_svg, but colored along the heatmap ramp rather than in grayscale
+pub fn render_heatmap_svg(frame: &Frame) -> String {
+    render_cells(frame, |cell| {
+        let (r, g, b) = heatmap::heat_color(cell);
+        format!("rgb({},{},{})", r, g, b)
+    })
+}
+
+// draws line segments, with the points at which they overlap marked by the number of segments
+// over each
+// note: drawn in the segments' own coordinates, one unit to a pixel, so that y grows downwards as
+// it does in the puzzles
+pub fn render_lines(lines: &[Line], overlaps: &[(Point, usize)]) -> String {
+    let points = || lines.iter().flat_map(|line| [&line.p0, &line.p1]);
+    let x_min = points().map(|p| p.x).min().unwrap_or(0) - 1;
+    let y_min = points().map(|p| p.y).min().unwrap_or(0) - 1;
+    let width = points().map(|p| p.x).max().unwrap_or(0) + 1 - x_min;
+    let height = points().map(|p| p.y).max().unwrap_or(0) + 1 - y_min;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+        width, height, x_min, y_min, width, height
+    );
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"100%\" height=\"100%\" fill=\"black\"/>",
+        x_min, y_min
+    );
+
+    for line in lines.iter() {
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"0.5\"/>",
+            line.p0.x, line.p0.y, line.p1.x, line.p1.y, LINE_COLOR
+        );
+    }
+    // note: the most-covered points are drawn last, so that they are not hidden by the rest
+    let mut overlaps = overlaps.iter().collect::<Vec<_>>();
+    overlaps.sort_by_key(|(p, count)| (*count, p.x, p.y));
+    for (p, count) in overlaps.into_iter().filter(|(_, count)| *count >= 2) {
+        let color = OVERLAP_COLORS[(count - 2).min(OVERLAP_COLORS.len() - 1)];
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"0.75\" fill=\"{}\"/>",
+            p.x, p.y, color
+        );
+    }
+
+    svg += "</svg>\n";
+    svg
+}
@@ -0,0 +1,15 @@
+/*
+** src/wasm.rs
+*/
+
+use wasm_bindgen::prelude::*;
+
+// entry point for the browser playground, see www/index.html
+// note: panics abort on wasm32 rather than unwind, trapping the whole instance, so malformed input
+// must surface as an error from parsing rather than be caught
+#[wasm_bindgen]
+pub fn solve(day: usize, part: usize, input: String) -> Result<String, JsValue> {
+    crate::solve(day, part, &input)
+        .map(|solution| solution.to_string())
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
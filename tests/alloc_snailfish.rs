@@ -0,0 +1,95 @@
+/*
+** tests/alloc_snailfish.rs
+*/
+
+// counts the heap allocations made while solving Day 18, which needs a global allocator and so a
+// test binary of its own
+
+use aoc2021::inputs;
+use aoc2021::puzzles;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    // note: counted per thread, as the tests run alongside each other
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // note: the count may already be gone while a thread is torn down
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// the number of allocations the given closure makes on this thread
+fn allocations<F, T>(f: F) -> (usize, T)
+where
+    F: FnOnce() -> T,
+{
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (ALLOCATIONS.with(Cell::get) - before, result)
+}
+
+const TEST_HOMEWORK: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]";
+
+#[test]
+fn test_snailfish_sum_allocations() {
+    let puzzle = puzzles::from_lines(18, TEST_HOMEWORK.lines())
+        .unwrap()
+        .unwrap();
+    // one vector for each of the 9 sums, which reducing never grows, and one for the magnitude
+    let (count, answer) = allocations(|| puzzle.part_1());
+    assert_eq!(answer.unwrap().to_string(), "4140");
+    assert_eq!(count, 10);
+}
+
+// run with: cargo test --release --test alloc_snailfish -- --ignored --nocapture
+// note: only the calling thread is counted, so the parallel search of part 2 is undercounted
+#[test]
+#[ignore]
+fn bench_snailfish_allocations() {
+    // note: the puzzle inputs are optional, so a missing input is skipped
+    let input = match inputs::load(18) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+    let puzzle = puzzles::from_lines(18, input.lines()).unwrap().unwrap();
+    for part in [1, 2] {
+        let (count, answer) = allocations(|| match part {
+            1 => puzzle.part_1(),
+            _ => puzzle.part_2(),
+        });
+        println!("part {}: {}, {} allocations", part, answer.unwrap(), count);
+    }
+}